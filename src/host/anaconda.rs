@@ -0,0 +1,223 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, Host, HostError,
+    DEFAULT_USER_AGENT,
+};
+
+/// A Host implementation for conda packages published on anaconda.org
+///
+/// Every version of a conda package is built for several platforms, so all of
+/// the platform specific files of one version are grouped together.
+pub struct AnacondaHost {
+    /// The channel the package is published in (i.e. "conda-forge")
+    pub channel: String,
+
+    /// The package name
+    pub package: String,
+
+    /// The complete URL the host was created from
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// Response format for the anaconda.org package API
+#[derive(Deserialize, Debug)]
+pub struct AnacondaPackageResponse {
+    /// The package name
+    pub name: String,
+    /// All files uploaded for this package, across versions and platforms
+    pub files: Vec<AnacondaFile>,
+}
+
+/// A single uploaded file of a conda package
+#[derive(Deserialize, Debug)]
+pub struct AnacondaFile {
+    /// The version this file belongs to
+    pub version: String,
+    /// The file name relative to the channel (i.e. "linux-64/numpy-...conda")
+    pub basename: String,
+    /// Protocol relative download URL
+    pub download_url: String,
+    /// When the file was uploaded (i.e. "2024-02-06 02:10:11.493000+00:00")
+    pub upload_time: Option<String>,
+}
+
+impl AnacondaHost {
+    /// Creates a new AnacondaHost instance from a URL
+    ///
+    /// Both `https://anaconda.org/<channel>/<name>` package pages and
+    /// `https://conda.anaconda.org/<channel>/<subdir>/<file>` download URLs are accepted.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        let channel = path
+            .first()
+            .ok_or_else(|| HostError::parse_error(url, "missing channel in anaconda URL"))?;
+
+        let package = match url.host_str() {
+            Some("conda.anaconda.org") => {
+                let file = path.get(2).ok_or_else(|| {
+                    HostError::parse_error(url, "missing package file in conda URL")
+                })?;
+                package_from_filename(file).ok_or_else(|| {
+                    HostError::parse_error(url, format!("invalid conda package file: {file}"))
+                })?
+            }
+            _ => path
+                .get(1)
+                .ok_or_else(|| HostError::parse_error(url, "missing package in anaconda URL"))?
+                .to_string(),
+        };
+
+        debug!("Created AnacondaHost for {}/{}", channel, package);
+        Ok(Self {
+            channel: channel.to_string(),
+            package,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// Derives the package name from a conda file name (`<name>-<version>-<build>.<ext>`)
+fn package_from_filename(file: &str) -> Option<String> {
+    let mut parts = file.rsplitn(3, '-');
+    let _build = parts.next()?;
+    let _version = parts.next()?;
+    parts.next().map(String::from)
+}
+
+/// Parses the upload timestamps used by the anaconda.org API
+fn parse_upload_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%:z")
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+#[async_trait]
+impl Host for AnacondaHost {
+    #[tracing::instrument(skip(self), fields(channel = %self.channel, project = %self.package))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let uri = format!(
+            "https://api.anaconda.org/package/{}/{}",
+            self.channel, self.package
+        );
+        debug!("Fetching package data from: {}", uri);
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch package data".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .json::<AnacondaPackageResponse>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse package data from {uri}"),
+                source: e,
+            })?;
+
+        let mut versions: BTreeMap<String, VersionMetadata> = BTreeMap::new();
+        for file in response.files {
+            let uploaded = file.upload_time.as_deref().and_then(parse_upload_time);
+            let url = if file.download_url.starts_with("//") {
+                format!("https:{}", file.download_url)
+            } else {
+                file.download_url
+            };
+
+            let metadata = versions
+                .entry(file.version.clone())
+                .or_insert_with(|| VersionMetadata::new(file.version, vec![]));
+            metadata.downloads.push(VersionedAsset {
+                url,
+                kind: AssetKind::Release,
+                released_at: uploaded,
+                updated_at: uploaded,
+                checksum: None,
+                size: None,
+                download_count: None,
+                verifies: vec![],
+            });
+            // The version is released once its first build has been uploaded
+            metadata.released_at = match (metadata.released_at, uploaded) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        info!("Found {} versions of {}", versions.len(), response.name);
+        Ok(sort_versions(versions.into_values().map(|mut v| {
+            v.downloads.sort();
+            v
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://anaconda.org/conda-forge/numpy").unwrap();
+        let host = AnacondaHost::from_url(&url).unwrap();
+        assert_eq!(host.channel, "conda-forge");
+        assert_eq!(host.package, "numpy");
+
+        let url = Url::parse(
+            "https://conda.anaconda.org/conda-forge/linux-64/python-dateutil-2.9.0-pyhd8ed1ab_0.conda",
+        )
+        .unwrap();
+        let host = AnacondaHost::from_url(&url).unwrap();
+        assert_eq!(host.channel, "conda-forge");
+        assert_eq!(host.package, "python-dateutil");
+
+        let url = Url::parse("https://anaconda.org/conda-forge").unwrap();
+        assert!(AnacondaHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_parse_upload_time() {
+        let parsed = parse_upload_time("2024-02-06 02:10:11.493000+00:00").unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-02-06T02:10:11.493+00:00");
+        assert!(parse_upload_time("yesterday").is_none());
+    }
+}
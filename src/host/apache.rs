@@ -0,0 +1,319 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use async_trait::async_trait;
+use tracing::debug;
+use url::Url;
+
+use crate::{versioning::VersionExtractor, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    listing::{self, ListingEntry},
+    retry::RetryPolicy,
+    sort_versions, Host, HostError, DEFAULT_USER_AGENT,
+};
+
+/// Archive suffixes published by Apache projects
+const ARCHIVE_SUFFIXES: &[&str] = &[".tar.gz", ".tgz", ".tar.bz2", ".tar.xz", ".zip"];
+
+/// Distribution qualifiers appended to Apache archive names
+const QUALIFIERS: &[&str] = &["-bin", "-src", "-source"];
+
+/// A Host implementation for projects distributed through the Apache `dist` tree
+///
+/// Projects live under `https://downloads.apache.org/<project>/`, usually with
+/// one subdirectory per version holding `-bin`/`-src` archives next to `.asc`
+/// signatures and `.sha512` checksums. Older releases move to `archive.apache.org`.
+///
+/// Archives on `downloads.apache.org` are served through `closer.lua`, which
+/// redirects to a nearby mirror, while signatures and checksums always point at
+/// the canonical site as the Apache release policy requires.
+pub struct ApacheHost {
+    /// The project name (i.e. "maven", "httpd")
+    pub project: String,
+
+    /// The directory listing the project's releases (i.e. "maven/maven-3/")
+    pub directory: Url,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+impl ApacheHost {
+    /// Creates a new ApacheHost instance from a URL
+    ///
+    /// The release directory is everything up to the first version directory
+    /// or file name in the path.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?
+            .collect::<Vec<&str>>();
+        let is_file = !url.path().ends_with('/');
+        let path = parts.iter().filter(|p| !p.is_empty()).copied();
+        let count = parts.iter().filter(|p| !p.is_empty()).count();
+
+        let directory = path
+            .enumerate()
+            .take_while(|(i, segment)| {
+                let is_version = segment.starts_with(|c: char| c.is_ascii_digit());
+                let is_file_name = is_file && *i + 1 == count;
+                !is_version && !is_file_name
+            })
+            .map(|(_, segment)| segment)
+            .collect::<Vec<_>>();
+        // archive.apache.org keeps the tree under a leading `dist/`
+        let project = directory
+            .iter()
+            .find(|segment| **segment != "dist")
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+
+        let mut base = url.clone();
+        base.set_path(&format!("{}/", directory.join("/")));
+        base.set_query(None);
+        base.set_fragment(None);
+
+        Ok(Self {
+            project: project.to_string(),
+            directory: base,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Whether an archive named `name` belongs to this project
+    fn is_project(&self, name: &str) -> bool {
+        name == self.project || name == format!("apache-{}", self.project)
+    }
+
+    /// Returns the download URL for an asset, routing archives through `closer.lua`
+    fn download_url(&self, url: &Url, kind: &AssetKind) -> String {
+        match (url.host_str(), kind) {
+            (Some("downloads.apache.org"), AssetKind::Release) => format!(
+                "https://www.apache.org/dyn/closer.lua{}?action=download",
+                url.path()
+            ),
+            _ => url.to_string(),
+        }
+    }
+
+    /// Groups release files by version
+    ///
+    /// Files inside a version directory take that directory's version; files
+    /// in the flat project directory are matched by name.
+    fn collect(&self, files: &[(Option<String>, ListingEntry)]) -> Vec<VersionMetadata> {
+        let extractor = VersionExtractor::new();
+        let names = files
+            .iter()
+            .map(|(_, e)| e.name.as_str())
+            .collect::<BTreeSet<_>>();
+
+        let mut versions: BTreeMap<String, BTreeSet<VersionedAsset>> = BTreeMap::new();
+        for (dir_version, entry) in files {
+            let (artifact, kind) = listing::split_companion(&entry.name);
+            if kind != AssetKind::Release && !names.contains(artifact) {
+                continue;
+            }
+            let Some(stem) = strip_archive(artifact) else {
+                continue;
+            };
+
+            let version = match dir_version {
+                Some(version) => version.clone(),
+                None => match extractor.extract(stem) {
+                    Ok(m) if self.is_project(&m.name) => m.version,
+                    _ => continue,
+                },
+            };
+            versions
+                .entry(version)
+                .or_default()
+                .insert(VersionedAsset::new(
+                    self.download_url(&entry.url, &kind),
+                    kind,
+                ));
+        }
+
+        versions
+            .into_iter()
+            .map(|(version, downloads)| {
+                VersionMetadata::new(version, downloads.into_iter().collect())
+            })
+            .collect()
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// Strips the archive suffix and any `-bin`/`-src` qualifier from a file name
+///
+/// Returns None for files that are not release archives (i.e. `KEYS`).
+fn strip_archive(name: &str) -> Option<&str> {
+    let stem = ARCHIVE_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))?;
+    Some(
+        QUALIFIERS
+            .iter()
+            .find_map(|q| stem.strip_suffix(q))
+            .unwrap_or(stem),
+    )
+}
+
+#[async_trait]
+impl Host for ApacheHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries =
+            listing::list_directory_as(&self.directory, &self.user_agent, &self.retry).await?;
+
+        let mut files = vec![];
+        for entry in entries {
+            if listing::is_version_dir(&self.project, &entry) {
+                debug!("Descending into version directory: {}", entry.url);
+                let version = entry
+                    .name
+                    .strip_prefix(&format!("{}-", self.project))
+                    .unwrap_or(&entry.name)
+                    .to_string();
+                for child in
+                    listing::list_directory_as(&entry.url, &self.user_agent, &self.retry).await?
+                {
+                    // i.e. maven/maven-3/3.9.9/{binaries,source}/
+                    if child.is_dir {
+                        for nested in
+                            listing::list_directory_as(&child.url, &self.user_agent, &self.retry)
+                                .await?
+                        {
+                            files.push((Some(version.clone()), nested));
+                        }
+                    } else {
+                        files.push((Some(version.clone()), child));
+                    }
+                }
+            } else if !entry.is_dir {
+                files.push((None, entry));
+            }
+        }
+
+        Ok(sort_versions(self.collect(&files)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse(
+            "https://downloads.apache.org/maven/maven-3/3.9.9/binaries/apache-maven-3.9.9-bin.tar.gz",
+        )
+        .unwrap();
+        let host = ApacheHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "maven");
+        assert_eq!(
+            host.directory.as_str(),
+            "https://downloads.apache.org/maven/maven-3/"
+        );
+
+        let url = Url::parse("https://archive.apache.org/dist/httpd/httpd-2.4.62.tar.bz2").unwrap();
+        let host = ApacheHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "httpd");
+        assert_eq!(
+            host.directory.as_str(),
+            "https://archive.apache.org/dist/httpd/"
+        );
+
+        let url = Url::parse("https://downloads.apache.org/").unwrap();
+        assert!(ApacheHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_collect() {
+        let url = Url::parse("https://downloads.apache.org/maven/maven-3/").unwrap();
+        let host = ApacheHost::from_url(&url).unwrap();
+        let base =
+            Url::parse("https://downloads.apache.org/maven/maven-3/3.9.9/binaries/").unwrap();
+        let body = r#"
+            <a href="apache-maven-3.9.9-bin.tar.gz">apache-maven-3.9.9-bin.tar.gz</a>
+            <a href="apache-maven-3.9.9-bin.tar.gz.asc">apache-maven-3.9.9-bin.tar.gz.asc</a>
+            <a href="apache-maven-3.9.9-bin.tar.gz.sha512">apache-maven-3.9.9-bin.tar.gz.sha512</a>
+            <a href="KEYS">KEYS</a>
+        "#;
+        let files = listing::parse_listing(&base, body)
+            .unwrap()
+            .into_iter()
+            .map(|e| (Some("3.9.9".to_string()), e))
+            .collect::<Vec<_>>();
+
+        let versions = host.collect(&files);
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "3.9.9");
+        let downloads = versions[0]
+            .downloads
+            .iter()
+            .map(|d| (d.url.as_str(), d.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            downloads,
+            vec![
+                (
+                    "https://downloads.apache.org/maven/maven-3/3.9.9/binaries/apache-maven-3.9.9-bin.tar.gz.asc",
+                    AssetKind::Signature
+                ),
+                (
+                    "https://downloads.apache.org/maven/maven-3/3.9.9/binaries/apache-maven-3.9.9-bin.tar.gz.sha512",
+                    AssetKind::Checksum
+                ),
+                (
+                    "https://www.apache.org/dyn/closer.lua/maven/maven-3/3.9.9/binaries/apache-maven-3.9.9-bin.tar.gz?action=download",
+                    AssetKind::Release
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flat_directory() {
+        let url = Url::parse("https://downloads.apache.org/httpd/").unwrap();
+        let host = ApacheHost::from_url(&url).unwrap();
+        let body = r#"
+            <a href="httpd-2.4.62.tar.bz2">httpd-2.4.62.tar.bz2</a>
+            <a href="httpd-2.4.62.tar.bz2.asc">httpd-2.4.62.tar.bz2.asc</a>
+            <a href="mod_fcgid-2.3.9.tar.gz">mod_fcgid-2.3.9.tar.gz</a>
+            <a href="KEYS">KEYS</a>
+        "#;
+        let files = listing::parse_listing(&host.directory, body)
+            .unwrap()
+            .into_iter()
+            .map(|e| (None, e))
+            .collect::<Vec<_>>();
+
+        let versions = host.collect(&files);
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "2.4.62");
+        assert_eq!(versions[0].downloads.len(), 2);
+    }
+}
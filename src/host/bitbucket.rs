@@ -0,0 +1,238 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, strip_tag_prefixes, Host,
+    HostError, DEFAULT_USER_AGENT,
+};
+
+/// Maximum number of tag pages fetched before giving up on pagination
+const MAX_PAGES: usize = 50;
+
+/// Base URL of the Bitbucket website, serving the tag archives
+const WEB_URL: &str = "https://bitbucket.org/";
+
+/// Bitbucket Cloud host implementation for interacting with Bitbucket repositories.
+pub struct BitbucketHost {
+    /// The workspace owning the repository.
+    pub workspace: String,
+    /// The name of the repository.
+    pub repo: String,
+    /// The URL of the repository.
+    pub url: Url,
+    /// The `User-Agent` sent with every request.
+    pub user_agent: String,
+    /// Policy for retrying requests that failed for transient reasons.
+    pub retry: RetryPolicy,
+}
+
+/// A single page of a paginated Bitbucket 2.0 API response.
+#[derive(Deserialize, Debug)]
+pub struct BitbucketPage<T> {
+    /// The items on this page
+    pub values: Vec<T>,
+    /// URL of the next page, if any
+    pub next: Option<String>,
+}
+
+/// Response structure for the Bitbucket refs/tags endpoint.
+#[derive(Deserialize, Debug)]
+pub struct BitbucketTag {
+    /// The name of the tag
+    pub name: String,
+    /// The commit this tag points to
+    pub target: Option<BitbucketTarget>,
+}
+
+/// Commit information in a Bitbucket tag response.
+#[derive(Deserialize, Debug)]
+pub struct BitbucketTarget {
+    /// The SHA hash of the commit
+    pub hash: String,
+    /// When the commit was authored
+    pub date: Option<DateTime<Utc>>,
+}
+
+impl BitbucketHost {
+    /// Creates a new BitbucketHost instance from a Bitbucket repository URL.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        debug!("Creating BitbucketHost from URL: {}", url);
+        let mut parts = url.path().split('/').filter(|x| !x.is_empty());
+        let workspace = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing workspace in Bitbucket URL"))?
+            .to_string();
+        let repo = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing repository name in Bitbucket URL"))?
+            .trim_end_matches(".git")
+            .to_string();
+        Ok(Self {
+            workspace,
+            repo,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Fetches all tags, following the `next` cursor of each page.
+    #[tracing::instrument(skip(self), fields(owner = %self.workspace, repo = %self.repo))]
+    async fn fetch_tags(&self) -> Result<Vec<BitbucketTag>, HostError> {
+        let mut next = Some(format!(
+            "https://api.bitbucket.org/2.0/repositories/{}/{}/refs/tags?pagelen=100",
+            self.workspace, self.repo
+        ));
+        let mut tags = vec![];
+        let mut pages = 0;
+
+        while let Some(url) = next {
+            if pages == MAX_PAGES {
+                warn!("Stopping after {} pages of tags", MAX_PAGES);
+                break;
+            }
+            debug!("Fetching tags from: {}", url);
+            let page = self
+                .retry
+                .send(http_client(&self.user_agent).get(&url))
+                .await
+                .map_err(|e| HostError::ApiRequest {
+                    context: "failed to fetch tags".into(),
+                    source: e,
+                })
+                .and_then(check_status)?
+                .json::<BitbucketPage<BitbucketTag>>()
+                .await
+                .map_err(|e| HostError::ApiResponse {
+                    context: format!("failed to parse tags response from {url}"),
+                    source: e,
+                })?;
+            tags.extend(page.values);
+            next = page.next;
+            pages += 1;
+        }
+
+        info!("Successfully fetched {} tags", tags.len());
+        Ok(tags)
+    }
+
+    /// Returns the URL of the autogenerated archive of `tag`, with the tag percent-encoded.
+    fn archive_url(&self, tag: &str) -> String {
+        let mut url = Url::parse(WEB_URL).expect("valid Bitbucket URL");
+        url.path_segments_mut()
+            .expect("Bitbucket URL has a path")
+            .pop_if_empty()
+            .push(&self.workspace)
+            .push(&self.repo)
+            .push("get")
+            .push(&format!("{tag}.tar.gz"));
+        url.to_string()
+    }
+
+    /// Converts tags into versions, newest first.
+    fn collect(&self, tags: Vec<BitbucketTag>) -> Vec<VersionMetadata> {
+        sort_versions(tags.into_iter().map(|tag| {
+            let released_at = tag.target.as_ref().and_then(|t| t.date);
+            let downloads = vec![VersionedAsset {
+                released_at,
+                ..VersionedAsset::new(self.archive_url(&tag.name), AssetKind::Autogenerated)
+            }];
+            VersionMetadata {
+                released_at,
+                prerelease: is_prerelease(&tag.name),
+                source_ref: tag.target.map(|t| t.hash),
+                tag: Some(tag.name.clone()),
+                ..VersionMetadata::new(strip_tag_prefixes(&self.repo, &tag.name), downloads)
+            }
+        }))
+    }
+
+    /// Sets the `User-Agent` sent with every request.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons.
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for BitbucketHost {
+    #[tracing::instrument(skip(self), fields(owner = %self.workspace, repo = %self.repo))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let tags = self.fetch_tags().await?;
+        Ok(self.collect(tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://bitbucket.org/multicoreware/x265_git/downloads/").unwrap();
+        let host = BitbucketHost::from_url(&url).unwrap();
+        assert_eq!(host.workspace, "multicoreware");
+        assert_eq!(host.repo, "x265_git");
+
+        let url = Url::parse("https://bitbucket.org/multicoreware").unwrap();
+        assert!(BitbucketHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_parse_page() {
+        let page: BitbucketPage<BitbucketTag> = serde_json::from_str(
+            r#"{
+                "values": [
+                    {"name": "4.1", "target": {"hash": "1d3b6e4", "date": "2024-11-22T09:10:11+00:00"}}
+                ],
+                "next": "https://api.bitbucket.org/2.0/repositories/multicoreware/x265_git/refs/tags?page=2"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(page.values[0].name, "4.1");
+        assert!(page.next.is_some());
+    }
+
+    /// Tests that tags are normalised into versions pointing at their commit
+    #[test]
+    fn test_collect() {
+        let url = Url::parse("https://bitbucket.org/multicoreware/x265_git").unwrap();
+        let host = BitbucketHost::from_url(&url).unwrap();
+        let tags: Vec<BitbucketTag> = serde_json::from_str(
+            r#"[
+                {"name": "v4.1", "target": {"hash": "1d3b6e4", "date": "2024-11-22T09:10:11+00:00"}},
+                {"name": "v4.2-rc1", "target": {"hash": "8f0c2a1"}},
+                {"name": "release/4.0", "target": null}
+            ]"#,
+        )
+        .unwrap();
+
+        let versions = host.collect(tags);
+        assert_eq!(versions[0].version, "4.2-rc1");
+        assert!(versions[0].prerelease);
+        assert_eq!(versions[1].version, "4.1");
+        assert!(!versions[1].prerelease);
+        assert_eq!(versions[1].tag.as_deref(), Some("v4.1"));
+        assert_eq!(versions[1].source_ref.as_deref(), Some("1d3b6e4"));
+        assert_eq!(
+            versions[2].downloads[0].url,
+            "https://bitbucket.org/multicoreware/x265_git/get/release%2F4.0.tar.gz"
+        );
+    }
+}
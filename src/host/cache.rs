@@ -0,0 +1,52 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Storage for conditional HTTP requests, so unchanged API responses are
+//! answered with a `304 Not Modified` instead of being downloaded again.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/// A response body stored along with the `ETag` it was served with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// The `ETag` header of the response, sent back as `If-None-Match`
+    pub etag: String,
+
+    /// The response body
+    pub body: String,
+
+    /// The `Link` header of the response, needed to follow pagination
+    pub link: Option<String>,
+}
+
+/// A store of cached responses keyed by request URL
+///
+/// Implementations must be safe to share between concurrent lookups.
+pub trait EtagCache: Send + Sync {
+    /// Returns the cached response for `url`, if any
+    fn get(&self, url: &str) -> Option<CachedResponse>;
+
+    /// Stores the latest response for `url`
+    fn put(&self, url: &str, response: CachedResponse);
+}
+
+/// An [`EtagCache`] kept in memory for the lifetime of the process
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl EtagCache for MemoryCache {
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        self.entries.lock().ok()?.get(url).cloned()
+    }
+
+    fn put(&self, url: &str, response: CachedResponse) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(url.to_string(), response);
+        }
+    }
+}
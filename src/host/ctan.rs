@@ -0,0 +1,237 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{check_status, http_client, retry::RetryPolicy, Host, HostError, DEFAULT_USER_AGENT};
+
+/// Base URL of the CTAN package API
+const API_URL: &str = "https://www.ctan.org/json/2.0/pkg";
+
+/// Redirector picking a nearby CTAN mirror for downloads
+const MIRROR_URL: &str = "https://mirrors.ctan.org";
+
+/// A Host implementation for TeX packages published on CTAN
+///
+/// CTAN only describes the current release of a package, so at most one
+/// version is ever returned.
+pub struct CtanHost {
+    /// The package name (i.e. "pgf")
+    pub package: String,
+
+    /// The complete URL the host was created from
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// Response format for the CTAN package API
+#[derive(Deserialize, Debug)]
+pub struct CtanPackageResponse {
+    /// The package name
+    pub name: String,
+    /// The current release, if the package has one
+    pub version: Option<CtanVersion>,
+    /// Where the package lives in the CTAN tree
+    pub ctan: Option<CtanLocation>,
+}
+
+/// The release information of a CTAN package
+#[derive(Deserialize, Debug)]
+pub struct CtanVersion {
+    /// The version number, often empty for packages only versioned by date
+    #[serde(default)]
+    pub number: String,
+    /// The release date as `YYYY-MM-DD`
+    #[serde(default)]
+    pub date: String,
+}
+
+/// The location of a CTAN package in the mirror tree
+#[derive(Deserialize, Debug)]
+pub struct CtanLocation {
+    /// Path below the mirror root (i.e. "/graphics/pgf/base")
+    pub path: String,
+    /// Whether the path is a single file rather than a directory
+    #[serde(default)]
+    pub file: bool,
+}
+
+impl CtanHost {
+    /// Creates a new CtanHost instance from a `https://ctan.org/pkg/<name>` URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        if path.first() != Some(&"pkg") {
+            return Err(HostError::invalid_url(url, "expected a /pkg/ URL"));
+        }
+        let package = path
+            .get(1)
+            .ok_or_else(|| HostError::parse_error(url, "missing package in CTAN URL"))?;
+
+        debug!("Created CtanHost for {}", package);
+        Ok(Self {
+            package: package.to_string(),
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Maps the current release of a package response to version metadata
+    ///
+    /// Packages without a version number are versioned by their release date.
+    /// Directories are downloaded as the zip archive the mirrors generate.
+    fn collect(&self, response: CtanPackageResponse) -> Vec<VersionMetadata> {
+        let Some(release) = response.version else {
+            return vec![];
+        };
+        let version = match release.number.trim() {
+            "" => release.date.trim().to_string(),
+            number => number.to_string(),
+        };
+        if version.is_empty() {
+            return vec![];
+        }
+        let released_at = NaiveDate::parse_from_str(release.date.trim(), "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| Utc.from_utc_datetime(&date));
+
+        let downloads = response
+            .ctan
+            .map(|location| {
+                let path = location.path.trim_matches('/');
+                let url = match location.file {
+                    true => format!("{MIRROR_URL}/{path}"),
+                    false => format!("{MIRROR_URL}/{path}.zip"),
+                };
+                VersionedAsset {
+                    released_at,
+                    ..VersionedAsset::new(url, AssetKind::Release)
+                }
+            })
+            .into_iter()
+            .collect();
+
+        vec![VersionMetadata {
+            released_at,
+            prerelease: is_prerelease(&version),
+            ..VersionMetadata::new(version, downloads)
+        }]
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for CtanHost {
+    #[tracing::instrument(skip(self), fields(project = %self.package))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let uri = format!("{API_URL}/{}", self.package);
+        debug!("Fetching package data from: {}", uri);
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch package data".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .json::<CtanPackageResponse>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse package data from {uri}"),
+                source: e,
+            })?;
+
+        info!("Fetched the current release of {}", response.name);
+        Ok(self.collect(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the package name is taken from package pages, ignoring the query
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://ctan.org/pkg/pgf").unwrap();
+        assert_eq!(CtanHost::from_url(&url).unwrap().package, "pgf");
+
+        let url = Url::parse("https://www.ctan.org/pkg/pgf?lang=en").unwrap();
+        assert_eq!(CtanHost::from_url(&url).unwrap().package, "pgf");
+    }
+
+    /// Tests that directories download as zips and dates stand in for missing versions
+    #[test]
+    fn test_package() {
+        let url = Url::parse("https://ctan.org/pkg/pgf").unwrap();
+        let host = CtanHost::from_url(&url).unwrap();
+        let response: CtanPackageResponse = serde_json::from_str(
+            r#"{
+                "id": "pgf",
+                "name": "pgf",
+                "version": {"number": "3.1.10", "date": "2023-01-15"},
+                "ctan": {"path": "/graphics/pgf/base", "file": false}
+            }"#,
+        )
+        .unwrap();
+
+        let versions = host.collect(response);
+        assert_eq!(versions[0].version, "3.1.10");
+        assert_eq!(
+            versions[0].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2023-01-15T00:00:00+00:00")
+        );
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://mirrors.ctan.org/graphics/pgf/base.zip"
+        );
+
+        // Single files download as they are, and an empty version falls back to the date
+        let response: CtanPackageResponse = serde_json::from_str(
+            r#"{
+                "name": "lipsum",
+                "version": {"number": "", "date": "2021-09-20"},
+                "ctan": {"path": "/macros/latex/contrib/lipsum.tds.zip", "file": true}
+            }"#,
+        )
+        .unwrap();
+        let versions = host.collect(response);
+        assert_eq!(versions[0].version, "2021-09-20");
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://mirrors.ctan.org/macros/latex/contrib/lipsum.tds.zip"
+        );
+
+        let response: CtanPackageResponse = serde_json::from_str(r#"{"name": "empty"}"#).unwrap();
+        assert!(host.collect(response).is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use tracing::debug;
+use url::Url;
+
+use crate::{AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{listing, retry::RetryPolicy, sort_versions, Host, HostError, DEFAULT_USER_AGENT};
+
+/// A Host implementation for projects published on download.eclipse.org
+///
+/// Eclipse projects publish builds under `<project>/downloads/drops/` (or `drops4/`)
+/// where every build gets its own directory named after the build ID, such as
+/// `R-4.31-202402290520` for releases or `I20240301-1800` for integration builds.
+pub struct EclipseHost {
+    /// The project path (i.e. "eclipse" or "modeling/emf")
+    pub project: String,
+
+    /// The drops directory name (i.e. "drops" or "drops4")
+    pub drops: String,
+
+    /// Whether non-release builds should be returned as prereleases
+    pub include_prereleases: bool,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// Details parsed from an Eclipse build ID
+#[derive(Debug, PartialEq, Eq)]
+struct EclipseBuild {
+    /// Human readable version (i.e. "4.31", "4.32M1")
+    version: String,
+    /// Whether this is a release (`R-`) build
+    release: bool,
+    /// Build timestamp encoded in the ID
+    timestamp: Option<NaiveDateTime>,
+}
+
+impl EclipseHost {
+    /// Creates a new EclipseHost instance from a URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        let downloads = path
+            .iter()
+            .position(|p| *p == "downloads")
+            .filter(|i| *i > 0)
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let drops = path
+            .get(downloads + 1)
+            .filter(|p| p.starts_with("drops"))
+            .unwrap_or(&"drops");
+
+        Ok(Self {
+            project: path[..downloads].join("/"),
+            drops: drops.to_string(),
+            include_prereleases: false,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Also return milestone, integration and other non-release builds, flagged as prerelease
+    pub fn with_prereleases(self, include_prereleases: bool) -> Self {
+        Self {
+            include_prereleases,
+            ..self
+        }
+    }
+
+    /// Returns the URL of the project's drops directory
+    fn drops_url(&self) -> Result<Url, HostError> {
+        let uri = format!(
+            "https://download.eclipse.org/{}/downloads/{}/",
+            self.project, self.drops
+        );
+        Url::parse(&uri).map_err(|e| HostError::invalid_url(&uri, e))
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// Parses a build ID such as `R-4.31-202402290520` or `I20240301-1800`
+fn parse_build_id(id: &str) -> Option<EclipseBuild> {
+    let kind = id.chars().next().filter(|c| c.is_ascii_uppercase())?;
+    let rest = id[1..].trim_start_matches('-');
+
+    match rest.rsplit_once('-') {
+        Some((version, stamp))
+            if stamp.len() == 12 && stamp.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            Some(EclipseBuild {
+                version: version.to_string(),
+                release: kind == 'R',
+                timestamp: NaiveDateTime::parse_from_str(stamp, "%Y%m%d%H%M").ok(),
+            })
+        }
+        // Integration and nightly builds are only identified by their timestamp
+        _ if rest.starts_with(|c: char| c.is_ascii_digit()) => Some(EclipseBuild {
+            version: id.to_string(),
+            release: false,
+            timestamp: None,
+        }),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl Host for EclipseHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries =
+            listing::list_directory_as(&self.drops_url()?, &self.user_agent, &self.retry).await?;
+
+        let mut versions = vec![];
+        for entry in entries.into_iter().filter(|e| e.is_dir) {
+            let Some(build) = parse_build_id(&entry.name) else {
+                debug!("Skipping unrecognised drop: {}", entry.name);
+                continue;
+            };
+            if !build.release && !self.include_prereleases {
+                continue;
+            }
+
+            let released_at = build.timestamp.map(|t| Utc.from_utc_datetime(&t));
+            versions.push(VersionMetadata {
+                version: build.version,
+                downloads: vec![VersionedAsset {
+                    url: entry.url.to_string(),
+                    kind: AssetKind::Release,
+                    released_at,
+                    updated_at: None,
+                    checksum: None,
+                    size: None,
+                    download_count: None,
+                    verifies: vec![],
+                }],
+                release_notes: None,
+                released_at,
+                prerelease: !build.release,
+                source_ref: None,
+                tag: None,
+            });
+        }
+
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_build_id() {
+        let build = parse_build_id("R-4.31-202402290520").unwrap();
+        assert_eq!(build.version, "4.31");
+        assert!(build.release);
+        assert_eq!(
+            build.timestamp.unwrap().to_string(),
+            "2024-02-29 05:20:00".to_string()
+        );
+
+        let build = parse_build_id("S-4.32M1-202404041850").unwrap();
+        assert_eq!(build.version, "4.32M1");
+        assert!(!build.release);
+
+        let build = parse_build_id("I20240301-1800").unwrap();
+        assert_eq!(build.version, "I20240301-1800");
+        assert!(!build.release);
+
+        assert!(parse_build_id("index.html").is_none());
+    }
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse(
+            "https://download.eclipse.org/eclipse/downloads/drops4/R-4.31-202402290520/",
+        )
+        .unwrap();
+        let host = EclipseHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "eclipse");
+        assert_eq!(
+            host.drops_url().unwrap().as_str(),
+            "https://download.eclipse.org/eclipse/downloads/drops4/"
+        );
+
+        let url = Url::parse("https://download.eclipse.org/downloads/").unwrap();
+        assert!(EclipseHost::from_url(&url).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, classify_asset, http_client, link_companions, retry::RetryPolicy, sort_versions,
+    strip_tag_prefixes, with_trailing_slash, Host, HostError, DEFAULT_USER_AGENT,
+};
+
+/// Number of releases requested per page, the default maximum of Forgejo and Gitea
+const PER_PAGE: usize = 50;
+
+/// Maximum number of pages fetched before giving up on pagination
+const MAX_PAGES: usize = 50;
+
+/// Forgejo host implementation for repositories on Codeberg and other Forgejo
+/// or Gitea instances.
+///
+/// Releases come from the `/api/v1` API of the instance the URL points at.
+/// Attachments are classified like GitHub release assets, by file name as
+/// Forgejo reports no content type.
+pub struct ForgejoHost {
+    /// The owner of the repository.
+    pub owner: String,
+    /// The name of the repository.
+    pub repo: String,
+    /// The URL of the repository.
+    pub url: Url,
+    /// Base URL of the API requests are sent to.
+    pub api_base: Url,
+    /// The `User-Agent` sent with every request.
+    pub user_agent: String,
+    /// Policy for retrying requests that failed for transient reasons.
+    pub retry: RetryPolicy,
+}
+
+/// Response structure for the Forgejo releases endpoint.
+#[derive(Deserialize, Debug)]
+pub struct ForgejoRelease {
+    /// The tag the release was made from
+    pub tag_name: String,
+    /// The release notes
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Whether the release is a draft
+    #[serde(default)]
+    pub draft: bool,
+    /// Whether the release is marked as a prerelease
+    #[serde(default)]
+    pub prerelease: bool,
+    /// When the release was published
+    pub published_at: Option<DateTime<Utc>>,
+    /// The source archive generated for the tag
+    pub tarball_url: Option<String>,
+    /// Files attached to the release
+    #[serde(default)]
+    pub assets: Vec<ForgejoAttachment>,
+}
+
+/// A file attached to a Forgejo release.
+#[derive(Deserialize, Debug)]
+pub struct ForgejoAttachment {
+    /// The file name
+    pub name: String,
+    /// Size of the file in bytes
+    pub size: Option<u64>,
+    /// Number of times the file was downloaded
+    pub download_count: Option<u64>,
+    /// When the file was uploaded
+    pub created_at: Option<DateTime<Utc>>,
+    /// Where the file is downloaded from
+    pub browser_download_url: String,
+}
+
+impl ForgejoHost {
+    /// Creates a new ForgejoHost instance from a `<instance>/<owner>/<repo>` URL.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        debug!("Creating ForgejoHost from URL: {}", url);
+        let mut parts = url.path().split('/').filter(|x| !x.is_empty());
+        let owner = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing owner in Forgejo URL"))?
+            .to_string();
+        let repo = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing repository name in Forgejo URL"))?
+            .trim_end_matches(".git")
+            .to_string();
+        let api_base = url
+            .join("/api/v1/")
+            .map_err(|e| HostError::invalid_url(url, e))?;
+        Ok(Self {
+            owner,
+            repo,
+            url: url.clone(),
+            api_base,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Sets the base URL of the API used for requests.
+    pub fn with_api_base(self, api_base: Url) -> Self {
+        Self {
+            api_base: with_trailing_slash(api_base),
+            ..self
+        }
+    }
+
+    /// Fetches a single page of the releases of this repository.
+    async fn fetch_page(&self, page: usize) -> Result<Vec<ForgejoRelease>, HostError> {
+        let path = format!("repos/{}/{}/releases", self.owner, self.repo);
+        let mut url = self
+            .api_base
+            .join(&path)
+            .map_err(|e| HostError::invalid_url(format!("{}{path}", self.api_base), e))?;
+        url.query_pairs_mut()
+            .append_pair("page", &page.to_string())
+            .append_pair("limit", &PER_PAGE.to_string());
+        debug!("Fetching releases from: {}", url);
+
+        self.retry
+            .send(http_client(&self.user_agent).get(url.clone()))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch releases".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .json::<Vec<ForgejoRelease>>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse releases from {url}"),
+                source: e,
+            })
+    }
+
+    /// Fetches every page of the releases of this repository.
+    async fn fetch_releases(&self) -> Result<Vec<ForgejoRelease>, HostError> {
+        let mut releases = vec![];
+        for page in 1..=MAX_PAGES {
+            let found = self.fetch_page(page).await?;
+            let last = found.len() < PER_PAGE;
+            releases.extend(found);
+            if last {
+                return Ok(releases);
+            }
+        }
+        warn!("Stopping after {} pages of releases", MAX_PAGES);
+        Ok(releases)
+    }
+
+    /// Maps published releases to version metadata, skipping drafts.
+    ///
+    /// Versions are named like GitHub's, without the repository name or a
+    /// leading `v`, while [`VersionMetadata::tag`] keeps the tag as spelled.
+    fn collect(&self, releases: Vec<ForgejoRelease>) -> Vec<VersionMetadata> {
+        releases
+            .into_iter()
+            .filter(|release| !release.draft)
+            .map(|release| {
+                let released_at = release.published_at;
+                let mut downloads = release
+                    .tarball_url
+                    .into_iter()
+                    .map(|url| VersionedAsset {
+                        released_at,
+                        ..VersionedAsset::new(url, AssetKind::Autogenerated)
+                    })
+                    .collect::<Vec<_>>();
+                downloads.extend(release.assets.into_iter().map(|asset| VersionedAsset {
+                    released_at: asset.created_at,
+                    size: asset.size,
+                    download_count: asset.download_count,
+                    ..VersionedAsset::new(
+                        asset.browser_download_url,
+                        classify_asset(&asset.name, None),
+                    )
+                }));
+                link_companions(&mut downloads);
+
+                let version = strip_tag_prefixes(&self.repo, &release.tag_name).to_string();
+                VersionMetadata {
+                    release_notes: release.body.filter(|body| !body.is_empty()),
+                    released_at,
+                    prerelease: release.prerelease || is_prerelease(&release.tag_name),
+                    tag: Some(release.tag_name),
+                    ..VersionMetadata::new(version, downloads)
+                }
+            })
+            .collect()
+    }
+
+    /// Sets the `User-Agent` sent with every request.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons.
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for ForgejoHost {
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let releases = self.fetch_releases().await?;
+        info!(
+            "Found {} releases of {}/{}",
+            releases.len(),
+            self.owner,
+            self.repo
+        );
+        Ok(sort_versions(self.collect(releases)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{MockResponse, MockServer};
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://codeberg.org/forgejo/forgejo/releases").unwrap();
+        let host = ForgejoHost::from_url(&url).unwrap();
+        assert_eq!(host.owner, "forgejo");
+        assert_eq!(host.repo, "forgejo");
+        assert_eq!(host.api_base.as_str(), "https://codeberg.org/api/v1/");
+
+        let url = Url::parse("https://codeberg.org/dnkl/foot.git").unwrap();
+        assert_eq!(ForgejoHost::from_url(&url).unwrap().repo, "foot");
+
+        let url = Url::parse("https://codeberg.org/dnkl").unwrap();
+        assert!(ForgejoHost::from_url(&url).is_err());
+    }
+
+    /// Tests that release attachments of mixed types are classified like GitHub assets
+    #[tokio::test]
+    async fn test_versions() {
+        let releases = r#"[
+            {
+                "tag_name": "v2.0.0",
+                "body": "New release",
+                "draft": false,
+                "prerelease": false,
+                "published_at": "2024-09-01T12:00:00Z",
+                "tarball_url": "https://codeberg.org/owner/tool/archive/v2.0.0.tar.gz",
+                "assets": [
+                    {"name": "tool-2.0.0.tar.gz", "size": 2048, "download_count": 7, "created_at": "2024-09-01T12:05:00Z", "browser_download_url": "https://codeberg.org/owner/tool/releases/download/v2.0.0/tool-2.0.0.tar.gz"},
+                    {"name": "tool-2.0.0.tar.gz.asc", "size": 833, "download_count": 2, "created_at": "2024-09-01T12:05:00Z", "browser_download_url": "https://codeberg.org/owner/tool/releases/download/v2.0.0/tool-2.0.0.tar.gz.asc"},
+                    {"name": "SHA256SUMS", "size": 180, "download_count": 1, "created_at": "2024-09-01T12:05:00Z", "browser_download_url": "https://codeberg.org/owner/tool/releases/download/v2.0.0/SHA256SUMS"},
+                    {"name": "tool-2.0.0-linux-amd64", "size": 9000, "download_count": 40, "created_at": "2024-09-01T12:05:00Z", "browser_download_url": "https://codeberg.org/owner/tool/releases/download/v2.0.0/tool-2.0.0-linux-amd64"}
+                ]
+            },
+            {"tag_name": "v2.1.0-rc.1", "draft": false, "prerelease": true, "published_at": "2024-10-01T12:00:00Z"},
+            {"tag_name": "v3.0.0", "draft": true, "prerelease": false, "published_at": null}
+        ]"#;
+        let server = MockServer::with_routes(vec![(
+            "/repos/owner/tool/releases?page=1&limit=50",
+            MockResponse::new(200, releases),
+        )])
+        .await;
+
+        let url = Url::parse("https://codeberg.org/owner/tool").unwrap();
+        let versions = ForgejoHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_user_agent("distro-bot/1.0")
+            .versions()
+            .await
+            .unwrap();
+        assert_eq!(
+            server.requests()[0].header("user-agent"),
+            Some("distro-bot/1.0")
+        );
+
+        // Drafts are skipped
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, "2.1.0-rc.1");
+        assert_eq!(versions[0].tag.as_deref(), Some("v2.1.0-rc.1"));
+        assert!(versions[0].prerelease);
+        assert!(versions[0].downloads.is_empty());
+
+        let release = &versions[1];
+        assert_eq!(release.release_notes.as_deref(), Some("New release"));
+        let kinds = release
+            .downloads
+            .iter()
+            .map(|asset| asset.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                AssetKind::Autogenerated,
+                AssetKind::SourceArchive,
+                AssetKind::Signature,
+                AssetKind::Checksum,
+                AssetKind::Binary,
+            ]
+        );
+        assert_eq!(release.downloads[1].size, Some(2048));
+        assert_eq!(release.downloads[4].download_count, Some(40));
+        assert_eq!(
+            release.downloads[2].verifies,
+            ["https://codeberg.org/owner/tool/releases/download/v2.0.0/tool-2.0.0.tar.gz"]
+        );
+    }
+
+    /// Tests that a transient failure of the API is retried
+    #[tokio::test]
+    async fn test_retry() {
+        let failed = std::sync::atomic::AtomicBool::new(false);
+        let server = MockServer::start(move |_| {
+            match failed.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                false => MockResponse::new(502, "Bad Gateway"),
+                true => MockResponse::new(200, r#"[{"tag_name": "v1.0.0"}]"#),
+            }
+        })
+        .await;
+
+        let url = Url::parse("https://codeberg.org/owner/tool").unwrap();
+        let versions = ForgejoHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_retry(RetryPolicy::new(2, std::time::Duration::ZERO))
+            .versions()
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(server.requests().len(), 2);
+    }
+}
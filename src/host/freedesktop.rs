@@ -0,0 +1,191 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use url::Url;
+
+use crate::{versioning::VersionExtractor, VersionMetadata};
+
+use super::{listing, retry::RetryPolicy, sort_versions, Host, HostError, DEFAULT_USER_AGENT};
+
+/// A Host implementation for projects published on freedesktop.org
+///
+/// Two layouts are supported:
+/// - `https://www.freedesktop.org/software/<project>/releases/`, one flat
+///   directory of tarballs per project
+/// - `https://xorg.freedesktop.org/releases/individual/<component>/` or
+///   `https://www.x.org/pub/individual/<component>/`, where many X.Org projects
+///   share one directory per component (`lib`, `xserver`, ...)
+///
+/// Detached `.sig` signatures next to the tarballs are attached to their release.
+pub struct FreedesktopHost {
+    /// The project name used to match tarballs (i.e. "libX11", "libinput")
+    pub project: String,
+
+    /// The X.Org component directory (i.e. "lib", "xserver"), if any
+    pub component: Option<String>,
+
+    /// The directory the project's tarballs are listed in
+    pub directory: Url,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+impl FreedesktopHost {
+    /// Creates a new FreedesktopHost instance from a URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        let tarball_project = |file: Option<&&str>| {
+            file.and_then(|file| VersionExtractor::new().extract(file).ok())
+                .map(|m| m.name)
+        };
+
+        let (project, component, directory) = match path.as_slice() {
+            ["software", project, "releases", rest @ ..] => (
+                tarball_project(rest.first()).unwrap_or_else(|| project.to_string()),
+                None,
+                format!("software/{project}/releases/"),
+            ),
+            [root @ ("releases" | "archive" | "pub"), "individual", component, rest @ ..] => {
+                // Component directories are shared, so only a tarball names the project
+                let project = tarball_project(rest.first()).ok_or_else(|| {
+                    HostError::parse_error(url, "expected a tarball URL in an X.Org component")
+                })?;
+                (
+                    project,
+                    Some(component.to_string()),
+                    format!("{root}/individual/{component}/"),
+                )
+            }
+            _ => return Err(HostError::invalid_url(url, "invalid URL format")),
+        };
+
+        let mut base = url.clone();
+        base.set_path(&directory);
+        base.set_query(None);
+        base.set_fragment(None);
+
+        Ok(Self {
+            project,
+            component,
+            directory: base,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for FreedesktopHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries =
+            listing::list_directory_as(&self.directory, &self.user_agent, &self.retry).await?;
+        let files = entries
+            .into_iter()
+            .filter(|e| !e.is_dir)
+            .collect::<Vec<_>>();
+        let versions = listing::collect_versions(&self.project, &files);
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetKind;
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://www.freedesktop.org/software/libinput/releases/").unwrap();
+        let host = FreedesktopHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "libinput");
+        assert_eq!(host.component, None);
+        assert_eq!(
+            host.directory.as_str(),
+            "https://www.freedesktop.org/software/libinput/releases/"
+        );
+
+        let url =
+            Url::parse("https://xorg.freedesktop.org/releases/individual/lib/libX11-1.8.10.tar.xz")
+                .unwrap();
+        let host = FreedesktopHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "libX11");
+        assert_eq!(host.component.as_deref(), Some("lib"));
+        assert_eq!(
+            host.directory.as_str(),
+            "https://xorg.freedesktop.org/releases/individual/lib/"
+        );
+
+        let url =
+            Url::parse("https://www.x.org/pub/individual/xserver/xwayland-24.1.4.tar.xz").unwrap();
+        let host = FreedesktopHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "xwayland");
+        assert_eq!(host.component.as_deref(), Some("xserver"));
+        assert_eq!(
+            host.directory.as_str(),
+            "https://www.x.org/pub/individual/xserver/"
+        );
+
+        let url = Url::parse("https://xorg.freedesktop.org/releases/individual/lib/").unwrap();
+        assert!(FreedesktopHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_component_listing() {
+        let base = Url::parse("https://xorg.freedesktop.org/releases/individual/xserver/").unwrap();
+        let body = r#"
+            <a href="xorg-server-21.1.14.tar.xz">xorg-server-21.1.14.tar.xz</a>
+            <a href="xorg-server-21.1.14.tar.xz.sig">xorg-server-21.1.14.tar.xz.sig</a>
+            <a href="xwayland-24.1.4.tar.xz">xwayland-24.1.4.tar.xz</a>
+        "#;
+        let entries = listing::parse_listing(&base, body).unwrap();
+        let versions = listing::collect_versions("xorg-server", &entries);
+
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "21.1.14");
+        let downloads = versions[0]
+            .downloads
+            .iter()
+            .map(|d| (d.url.as_str(), d.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            downloads,
+            vec![
+                (
+                    "https://xorg.freedesktop.org/releases/individual/xserver/xorg-server-21.1.14.tar.xz",
+                    AssetKind::Release
+                ),
+                (
+                    "https://xorg.freedesktop.org/releases/individual/xserver/xorg-server-21.1.14.tar.xz.sig",
+                    AssetKind::Signature
+                ),
+            ]
+        );
+    }
+}
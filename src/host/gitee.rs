@@ -0,0 +1,391 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::collections::BTreeSet;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, classify_asset, http_client, retry::RetryPolicy, sort_versions,
+    strip_tag_prefixes, with_trailing_slash, Host, HostError, DEFAULT_USER_AGENT,
+};
+
+/// Base URL of the Gitee v5 API
+const API_URL: &str = "https://gitee.com/api/v5/";
+
+/// Base URL of repository pages and archives on Gitee
+const WEB_URL: &str = "https://gitee.com";
+
+/// Number of items requested per page, the most the API allows
+const PER_PAGE: usize = 100;
+
+/// Maximum number of pages fetched per list before giving up on pagination
+const MAX_PAGES: usize = 50;
+
+/// Gitee host implementation for repositories on `gitee.com`.
+///
+/// The v5 API mirrors GitHub's, with tags and releases listed separately and
+/// merged by tag name here.
+pub struct GiteeHost {
+    /// The owner of the repository.
+    pub owner: String,
+    /// The name of the repository.
+    pub repo: String,
+    /// The URL of the repository.
+    pub url: Url,
+    /// Base URL of the API requests are sent to.
+    pub api_base: Url,
+    /// Token sent as the `access_token` query parameter, if any.
+    pub access_token: Option<String>,
+    /// The `User-Agent` sent with every request.
+    pub user_agent: String,
+    /// Policy for retrying requests that failed for transient reasons.
+    pub retry: RetryPolicy,
+}
+
+/// Response structure for the Gitee tags endpoint.
+#[derive(Deserialize, Debug)]
+pub struct GiteeTag {
+    /// The name of the tag
+    pub name: String,
+    /// The commit this tag points to
+    pub commit: Option<GiteeCommit>,
+}
+
+/// Commit information in a Gitee tag response.
+#[derive(Deserialize, Debug)]
+pub struct GiteeCommit {
+    /// The SHA hash of the commit
+    pub sha: String,
+    /// When the commit was made
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// Response structure for the Gitee releases endpoint.
+#[derive(Deserialize, Debug)]
+pub struct GiteeRelease {
+    /// The tag the release was made from
+    pub tag_name: String,
+    /// The release notes
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Whether the release is marked as a prerelease
+    #[serde(default)]
+    pub prerelease: bool,
+    /// When the release was created
+    pub created_at: Option<DateTime<Utc>>,
+    /// Files attached to the release
+    #[serde(default)]
+    pub assets: Vec<GiteeAsset>,
+}
+
+/// A file attached to a Gitee release.
+#[derive(Deserialize, Debug)]
+pub struct GiteeAsset {
+    /// The file name
+    #[serde(default)]
+    pub name: String,
+    /// Where the file is downloaded from
+    pub browser_download_url: String,
+}
+
+impl GiteeHost {
+    /// Creates a new GiteeHost instance from a `gitee.com/<owner>/<repo>` URL.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        debug!("Creating GiteeHost from URL: {}", url);
+        let mut parts = url.path().split('/').filter(|x| !x.is_empty());
+        let owner = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing owner in Gitee URL"))?
+            .to_string();
+        let repo = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing repository name in Gitee URL"))?
+            .trim_end_matches(".git")
+            .to_string();
+        Ok(Self {
+            owner,
+            repo,
+            url: url.clone(),
+            api_base: Url::parse(API_URL).expect("valid Gitee API URL"),
+            access_token: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Sets the base URL of the API used for requests.
+    pub fn with_api_base(self, api_base: Url) -> Self {
+        Self {
+            api_base: with_trailing_slash(api_base),
+            ..self
+        }
+    }
+
+    /// Sets the token sent as the `access_token` query parameter.
+    ///
+    /// Anonymous requests work for public repositories but are rate limited
+    /// more strictly.
+    pub fn with_access_token(self, access_token: impl Into<String>) -> Self {
+        Self {
+            access_token: Some(access_token.into()),
+            ..self
+        }
+    }
+
+    /// Returns the archive Gitee generates for `tag`
+    fn archive_url(&self, tag: &str) -> String {
+        format!(
+            "{WEB_URL}/{}/{}/repository/archive/{tag}.tar.gz",
+            self.owner, self.repo
+        )
+    }
+
+    /// Fetches a single page of the `endpoint` list of this repository.
+    ///
+    /// The token is part of the request URL, so errors name the URL without it.
+    async fn fetch_page<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        page: usize,
+    ) -> Result<Vec<T>, HostError> {
+        let path = format!("repos/{}/{}/{endpoint}", self.owner, self.repo);
+        let mut url = self
+            .api_base
+            .join(&path)
+            .map_err(|e| HostError::invalid_url(format!("{}{path}", self.api_base), e))?;
+        url.query_pairs_mut()
+            .append_pair("page", &page.to_string())
+            .append_pair("per_page", &PER_PAGE.to_string());
+        debug!("Fetching {} from: {}", endpoint, url);
+
+        let mut request = url.clone();
+        if let Some(token) = &self.access_token {
+            request.query_pairs_mut().append_pair("access_token", token);
+        }
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(request))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: format!("failed to fetch {url}"),
+                source: e.without_url(),
+            })?;
+        let response = check_status(response).map_err(|e| match e {
+            HostError::NotFound { .. } => HostError::NotFound {
+                url: url.to_string(),
+            },
+            HostError::Unauthorized { .. } => HostError::Unauthorized {
+                url: url.to_string(),
+            },
+            HostError::ApiRequest { context, source } => HostError::ApiRequest {
+                context: format!("{context} from {url}"),
+                source: source.without_url(),
+            },
+            e => e,
+        })?;
+        response
+            .json::<Vec<T>>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse {endpoint} response from {url}"),
+                source: e.without_url(),
+            })
+    }
+
+    /// Fetches every page of the `endpoint` list of this repository.
+    async fn fetch_list<T: DeserializeOwned>(&self, endpoint: &str) -> Result<Vec<T>, HostError> {
+        let mut items = vec![];
+        for page in 1..=MAX_PAGES {
+            let found = self.fetch_page::<T>(endpoint, page).await?;
+            let last = found.len() < PER_PAGE;
+            items.extend(found);
+            if last {
+                return Ok(items);
+            }
+        }
+        warn!("Stopping after {} pages of {}", MAX_PAGES, endpoint);
+        Ok(items)
+    }
+
+    /// Combines tags and releases into one version per tag name.
+    ///
+    /// Versions are named like GitHub's, without the repository name or a
+    /// leading `v`, while [`VersionMetadata::tag`] keeps the tag as spelled.
+    fn collect(&self, tags: &[GiteeTag], releases: &[GiteeRelease]) -> Vec<VersionMetadata> {
+        let names = tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .chain(releases.iter().map(|release| release.tag_name.as_str()))
+            .collect::<BTreeSet<&str>>();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let commit = tags
+                    .iter()
+                    .find(|tag| tag.name == name)
+                    .and_then(|tag| tag.commit.as_ref());
+                let release = releases.iter().find(|release| release.tag_name == name);
+                let released_at = release
+                    .and_then(|release| release.created_at)
+                    .or_else(|| commit.and_then(|commit| commit.date));
+
+                let mut downloads = vec![VersionedAsset {
+                    released_at,
+                    ..VersionedAsset::new(self.archive_url(name), AssetKind::Autogenerated)
+                }];
+                downloads.extend(release.into_iter().flat_map(|release| {
+                    release.assets.iter().map(|asset| VersionedAsset {
+                        released_at,
+                        ..VersionedAsset::new(
+                            asset.browser_download_url.clone(),
+                            classify_asset(&asset.name, None),
+                        )
+                    })
+                }));
+
+                VersionMetadata {
+                    release_notes: release.and_then(|release| release.body.clone()),
+                    released_at,
+                    prerelease: release
+                        .map(|release| release.prerelease)
+                        .unwrap_or_else(|| is_prerelease(name)),
+                    source_ref: commit.map(|commit| commit.sha.clone()),
+                    tag: Some(name.to_string()),
+                    ..VersionMetadata::new(strip_tag_prefixes(&self.repo, name), downloads)
+                }
+            })
+            .collect()
+    }
+
+    /// Sets the `User-Agent` sent with every request.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons.
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for GiteeHost {
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let (tags, releases) = futures_util::try_join!(
+            self.fetch_list::<GiteeTag>("tags"),
+            self.fetch_list::<GiteeRelease>("releases"),
+        )?;
+        info!(
+            "Found {} tags and {} releases of {}/{}",
+            tags.len(),
+            releases.len(),
+            self.owner,
+            self.repo
+        );
+        Ok(sort_versions(self.collect(&tags, &releases)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{MockResponse, MockServer};
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://gitee.com/openharmony/docs/releases").unwrap();
+        let host = GiteeHost::from_url(&url).unwrap();
+        assert_eq!(host.owner, "openharmony");
+        assert_eq!(host.repo, "docs");
+
+        let url = Url::parse("https://gitee.com/mindspore/mindspore.git").unwrap();
+        assert_eq!(GiteeHost::from_url(&url).unwrap().repo, "mindspore");
+
+        let url = Url::parse("https://gitee.com/openharmony").unwrap();
+        assert!(GiteeHost::from_url(&url).is_err());
+    }
+
+    /// Tests that tags and releases are merged and the token is sent but not reported
+    #[tokio::test]
+    async fn test_versions() {
+        let tags = r#"[
+            {"name": "v1.1.0", "commit": {"sha": "abc123", "date": "2024-05-01T08:00:00+08:00"}},
+            {"name": "v1.0.0", "commit": {"sha": "def456", "date": "2024-01-01T08:00:00+08:00"}}
+        ]"#;
+        let releases = r#"[
+            {
+                "tag_name": "v1.1.0",
+                "body": "Bug fixes",
+                "prerelease": false,
+                "created_at": "2024-05-02T10:00:00+08:00",
+                "assets": [
+                    {"name": "tool-1.1.0-linux-x86_64.tar.gz", "browser_download_url": "https://gitee.com/owner/tool/releases/download/v1.1.0/tool-1.1.0-linux-x86_64.tar.gz"}
+                ]
+            },
+            {"tag_name": "v1.2.0-rc.1", "prerelease": true, "created_at": "2024-06-01T10:00:00+08:00"}
+        ]"#;
+        let server = MockServer::start(move |request| {
+            let path = request.path.split('?').next().unwrap_or_default();
+            match path {
+                "/repos/owner/tool/tags" => MockResponse::new(200, tags),
+                "/repos/owner/tool/releases" => MockResponse::new(200, releases),
+                _ => MockResponse::new(404, "Not Found"),
+            }
+        })
+        .await;
+
+        let url = Url::parse("https://gitee.com/owner/tool").unwrap();
+        let host = GiteeHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_access_token("secret");
+        let versions = host.versions().await.unwrap();
+
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[0].version, "1.2.0-rc.1");
+        assert_eq!(versions[0].tag.as_deref(), Some("v1.2.0-rc.1"));
+        assert!(versions[0].prerelease);
+        assert_eq!(versions[1].version, "1.1.0");
+        assert_eq!(versions[1].release_notes.as_deref(), Some("Bug fixes"));
+        assert_eq!(versions[1].source_ref.as_deref(), Some("abc123"));
+        assert_eq!(
+            versions[1].downloads[0].url,
+            "https://gitee.com/owner/tool/repository/archive/v1.1.0.tar.gz"
+        );
+        assert_eq!(versions[1].downloads[1].kind, AssetKind::Binary);
+        assert_eq!(
+            versions[2].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-01-01T00:00:00+00:00")
+        );
+
+        let requests = server.requests();
+        assert!(requests
+            .iter()
+            .all(|r| r.path.contains("per_page=100") && r.path.contains("access_token=secret")));
+
+        // Errors name the request URL without the token
+        let url = Url::parse("https://gitee.com/owner/missing").unwrap();
+        let error = GiteeHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_access_token("secret")
+            .versions()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, HostError::NotFound { .. }));
+        assert!(!error.to_string().contains("secret"), "{error}");
+    }
+}
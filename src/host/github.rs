@@ -2,21 +2,107 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use tracing::{debug, info};
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use tracing::{debug, info, warn};
 use url::Url;
 
-use crate::{AssetKind, VersionMetadata, VersionedAsset};
+use crate::{
+    versioning::{is_prerelease, Version},
+    AssetKind, VersionMetadata, VersionedAsset,
+};
 
-use super::{Host, HostError};
+use super::{
+    cache::{CachedResponse, EtagCache},
+    check_status, classify_asset, http_client, link_companions, listing, merge_assets, newest,
+    retry::RetryPolicy,
+    sort_versions, strip_tag_prefixes, with_trailing_slash, Host, HostError, DEFAULT_USER_AGENT,
+};
 
 /// The GitHub API version to use for requests
 const GH_API_VERSION: &str = "2022-11-28";
 
+/// The default GitHub REST API endpoint
+const GH_API_URL: &str = "https://api.github.com/";
+
+/// Default maximum number of pages fetched from a paginated endpoint
+const DEFAULT_MAX_PAGES: usize = 50;
+
+/// Largest page size accepted by the API, used by default
+const MAX_PER_PAGE: u8 = 100;
+
+/// Maximum number of tag-only versions dated from their commit per listing
+const MAX_TAG_DATES: usize = 50;
+
+/// Maximum number of release-only versions whose tag is resolved to a commit per listing
+const MAX_SOURCE_REFS: usize = 50;
+
+/// Maximum number of releases whose checksum manifest is downloaded per listing
+const MAX_MANIFESTS: usize = 50;
+
+/// Number of per-version requests, such as commit dates, in flight at once by default
+const DEFAULT_CONCURRENCY: usize = 5;
+
+/// Maximum number of annotated tag objects followed to reach a commit
+const MAX_TAG_DEPTH: usize = 4;
+
+/// Number of assets fetched per release by [`GRAPHQL_QUERY`]
+const MAX_GRAPHQL_ASSETS: usize = 100;
+
+/// GraphQL query fetching a page of tags and a page of releases at once
+///
+/// Either connection is skipped once all of its pages were fetched. Annotated
+/// tags are peeled one level to reach their commit, like the REST tags endpoint.
+/// Releases with more than [`MAX_GRAPHQL_ASSETS`] assets are fetched again
+/// through the REST API.
+const GRAPHQL_QUERY: &str = r#"
+query($owner: String!, $repo: String!, $first: Int!, $refs: String, $releases: String, $withRefs: Boolean!, $withReleases: Boolean!, $assets: Int!) {
+  repository(owner: $owner, name: $repo) {
+    refs(refPrefix: "refs/tags/", first: $first, after: $refs, orderBy: {field: TAG_COMMIT_DATE, direction: DESC}) @include(if: $withRefs) {
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        id
+        name
+        target {
+          __typename
+          oid
+          ... on Commit { committedDate }
+          ... on Tag { target { __typename oid ... on Commit { committedDate } } }
+        }
+      }
+    }
+    releases(first: $first, after: $releases, orderBy: {field: CREATED_AT, direction: DESC}) @include(if: $withReleases) {
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        tagName
+        name
+        description
+        isDraft
+        isPrerelease
+        createdAt
+        publishedAt
+        tagCommit { oid }
+        releaseAssets(first: $assets) {
+          pageInfo { hasNextPage endCursor }
+          nodes { name contentType size downloadCount createdAt updatedAt downloadUrl digest }
+        }
+      }
+    }
+  }
+}
+"#;
+
 /// GitHub host implementation for interacting with GitHub repositories.
 pub struct GithubHost {
     /// The owner of the repository.
@@ -25,11 +111,201 @@ pub struct GithubHost {
     pub repo: String,
     /// The URL of the repository.
     pub url: Url,
+    /// Filter deciding which tags are versions, and how they are named.
+    pub tag_filter: TagFilter,
+    /// Base URL of the REST API (i.e. `https://<host>/api/v3/` for GitHub Enterprise).
+    pub api_base: Url,
+    /// Maximum number of pages fetched for tags and releases.
+    pub max_pages: usize,
+    /// Number of tags or releases requested per page, at most 100.
+    pub per_page: u8,
+    /// Number of versions after which no further pages are fetched.
+    pub max_versions: Option<usize>,
+    /// API token sent as a bearer token; kept private so it is never printed.
+    token: Option<String>,
+    /// Cache of previous responses, revalidated with conditional requests.
+    cache: Option<Arc<dyn EtagCache>>,
+    /// Whether `SHA256SUMS` release assets are downloaded to fill in missing checksums.
+    pub checksum_manifests: bool,
+    /// Whether tag-only versions are dated from their commit.
+    pub tag_dates: bool,
+    /// Number of per-version requests, such as commit dates, in flight at once.
+    pub concurrency: usize,
+    /// Policy for retrying requests that failed for transient reasons.
+    pub retry: RetryPolicy,
+    /// The `User-Agent` sent with every request.
+    pub user_agent: String,
+    /// HTTP client shared by all requests, so connections are reused.
+    client: reqwest::Client,
+    /// The owner and name the repository was renamed to, once a redirect revealed it.
+    moved_to: Mutex<Option<(String, String)>>,
+    /// Whether tags and releases are fetched through the GraphQL API when a token is set.
+    pub graphql: bool,
+    /// Whether versions are collected from tags, releases or both.
+    pub source: VersionSource,
+    /// Filter selecting which uploaded release assets are kept.
+    pub asset_filter: Option<AssetFilter>,
+    /// Whether prereleases are dropped from listings.
+    pub stable: bool,
+    /// Set once the repository was seen without any releases, to skip asking again.
+    ///
+    /// This is deliberately not recorded in the [`EtagCache`], which may persist
+    /// between runs and would then hide releases published later.
+    no_releases: AtomicBool,
+}
+
+/// Which listings of a repository versions are collected from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VersionSource {
+    /// Both tags and releases, merged by tag name.
+    #[default]
+    Both,
+    /// Only tags, for repositories without releases.
+    TagsOnly,
+    /// Only releases, for repositories whose tags are noise (i.e. CI tags).
+    ReleasesOnly,
+}
+
+impl VersionSource {
+    /// Whether tags are fetched.
+    pub fn tags(self) -> bool {
+        self != Self::ReleasesOnly
+    }
+
+    /// Whether releases are fetched.
+    pub fn releases(self) -> bool {
+        self != Self::TagsOnly
+    }
+}
+
+/// Selects and normalises the tags of a repository before they become versions.
+///
+/// Include and exclude patterns are matched against the raw tag name, and the
+/// strip prefix is removed from the tags that remain (i.e. `release/1.2.3` => `1.2.3`).
+/// Tags without the strip prefix lose a leading project name, `release-` and
+/// `v` once a project is set (i.e. `gtk-4.14.1` or `release-v1.4` => `4.14.1`, `1.4`).
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    /// Only tags matching this pattern are kept.
+    pub include: Option<Regex>,
+    /// Tags matching this pattern are dropped.
+    pub exclude: Option<Regex>,
+    /// Prefix removed from tag names when emitting version strings.
+    pub strip_prefix: Option<String>,
+    /// Project name removed from tag names along with `release-` and `v` prefixes.
+    pub project: Option<String>,
+}
+
+impl TagFilter {
+    /// Keeps only tags matching `pattern`.
+    pub fn include(self, pattern: Regex) -> Self {
+        Self {
+            include: Some(pattern),
+            ..self
+        }
+    }
+
+    /// Drops tags matching `pattern`.
+    pub fn exclude(self, pattern: Regex) -> Self {
+        Self {
+            exclude: Some(pattern),
+            ..self
+        }
+    }
+
+    /// Strips `prefix` from tag names when emitting version strings.
+    pub fn strip_prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            strip_prefix: Some(prefix.into()),
+            ..self
+        }
+    }
+
+    /// Strips `name` and common prefixes from tag names when emitting version strings.
+    pub fn project(self, name: impl Into<String>) -> Self {
+        Self {
+            project: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Whether `tag` passes the include and exclude patterns.
+    pub fn matches(&self, tag: &str) -> bool {
+        self.include.as_ref().is_none_or(|re| re.is_match(tag))
+            && !self.exclude.as_ref().is_some_and(|re| re.is_match(tag))
+    }
+
+    /// Returns the version string for `tag`, with the strip prefix removed.
+    pub fn normalize<'a>(&self, tag: &'a str) -> &'a str {
+        if let Some(version) = self
+            .strip_prefix
+            .as_deref()
+            .and_then(|prefix| tag.strip_prefix(prefix))
+            .filter(|version| !version.is_empty())
+        {
+            return version;
+        }
+        match &self.project {
+            Some(project) => strip_tag_prefixes(project, tag),
+            None => tag,
+        }
+    }
+}
+
+/// Selects release assets by file name, using a glob or a regular expression.
+///
+/// Globs support `*` and `?` and are matched against the whole file name at the
+/// end of the asset's download URL. Debug symbols and source maps are dropped
+/// even when they match, unless kept with [`AssetFilter::keep_debug`].
+#[derive(Debug, Clone)]
+pub struct AssetFilter {
+    pattern: Regex,
+    keep_debug: bool,
+}
+
+impl AssetFilter {
+    /// Creates a filter from a glob pattern (i.e. `*-linux-x86_64.tar.gz`).
+    pub fn glob(pattern: &str) -> Self {
+        let mut re = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => re.push_str("[^/]*"),
+                '?' => re.push_str("[^/]"),
+                c => re.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        re.push('$');
+        Self::regex(Regex::new(&re).expect("escaped glob is a valid regex"))
+    }
+
+    /// Creates a filter from a regular expression searched in the file name.
+    pub fn regex(pattern: Regex) -> Self {
+        Self {
+            pattern,
+            keep_debug: false,
+        }
+    }
+
+    /// Keeps matching debug symbols and source maps instead of dropping them.
+    pub fn keep_debug(self, keep_debug: bool) -> Self {
+        Self { keep_debug, ..self }
+    }
+
+    /// Whether the asset at `url` is kept by this filter.
+    pub fn matches(&self, url: &str) -> bool {
+        let file = url.rsplit('/').next().unwrap_or(url);
+        self.pattern.is_match(file)
+            && (self.keep_debug || classify_asset(file, None) != AssetKind::Debug)
+    }
 }
 
 impl GithubHost {
     /// Creates a new GithubHost instance from a GitHub repository URL.
     ///
+    /// Besides the repository page, any URL below it (i.e. `/releases/tag/v1.0`,
+    /// `/tags` or `/tree/main`) and REST API URLs such as
+    /// `https://api.github.com/repos/<owner>/<repo>` are accepted.
+    ///
     /// # Arguments
     /// * `url` - The GitHub repository URL to parse
     ///
@@ -38,97 +314,641 @@ impl GithubHost {
     pub fn from_url(url: &Url) -> Result<Self, HostError> {
         debug!("Creating GithubHost from URL: {}", url);
         let path = url.path();
-        let mut parts = path.split('/').skip(1).filter(|x| !x.is_empty());
+        let mut parts = path.split('/').skip(1).filter(|x| !x.is_empty()).peekable();
+        // API URLs, either api.github.com/repos/... or <host>/api/v3/repos/... on Enterprise
+        let enterprise_api = url.host_str() != Some("github.com") && path.starts_with("/api/");
+        if enterprise_api {
+            parts.next();
+            parts.next_if(|x| x.starts_with('v'));
+        }
+        if enterprise_api || url.host_str() == Some("api.github.com") {
+            parts.next_if_eq(&"repos");
+        }
         let owner = parts
             .next()
-            .ok_or_else(|| HostError::ParseError("missing repository owner in GitHub URL".into()))?
+            .ok_or_else(|| HostError::parse_error(url, "missing repository owner in GitHub URL"))?
             .to_string();
         let repo = parts
             .next()
-            .ok_or_else(|| HostError::ParseError("missing repository name in GitHub URL".into()))?
+            .ok_or_else(|| HostError::parse_error(url, "missing repository name in GitHub URL"))?
+            .trim_end_matches(".git")
             .to_string();
         info!("Created GithubHost for {}/{}", owner, repo);
         Ok(Self {
             owner,
+            tag_filter: TagFilter::default().project(repo.clone()),
             repo,
             url: url.clone(),
+            api_base: api_base_from_env(url),
+            max_pages: DEFAULT_MAX_PAGES,
+            per_page: MAX_PER_PAGE,
+            max_versions: None,
+            token: token_from_env(),
+            cache: None,
+            checksum_manifests: false,
+            tag_dates: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            retry: RetryPolicy::default(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            client: http_client(DEFAULT_USER_AGENT),
+            moved_to: Mutex::new(None),
+            graphql: false,
+            source: VersionSource::default(),
+            asset_filter: None,
+            stable: false,
+            no_releases: AtomicBool::new(false),
         })
     }
 
+    /// Sets the filter applied to tags before they are emitted as versions.
+    ///
+    /// Filters without a project strip the repository name from tags.
+    pub fn with_tag_filter(self, tag_filter: TagFilter) -> Self {
+        let project = tag_filter.project.or_else(|| Some(self.repo.clone()));
+        Self {
+            tag_filter: TagFilter {
+                project,
+                ..tag_filter
+            },
+            ..self
+        }
+    }
+
+    /// Sets the base URL of the REST API used for requests.
+    pub fn with_api_base(self, api_base: Url) -> Self {
+        Self {
+            api_base: with_trailing_slash(api_base),
+            ..self
+        }
+    }
+
+    /// Sets the HTTP client used for requests, i.e. to configure timeouts.
+    ///
+    /// The default client already honours the `HTTPS_PROXY`, `HTTP_PROXY`
+    /// and `NO_PROXY` environment variables.
+    pub fn with_client(self, client: reqwest::Client) -> Self {
+        Self { client, ..self }
+    }
+
+    /// Creates a GithubHost for a repository on a GitHub Enterprise Server instance
+    ///
+    /// The REST API of such instances lives at `https://<host>/api/v3/`.
+    pub fn from_enterprise_url(url: &Url) -> Result<Self, HostError> {
+        let mut api_base = url.clone();
+        api_base.set_path("/api/v3/");
+        api_base.set_query(None);
+        api_base.set_fragment(None);
+        Ok(Self::from_url(url)?.with_api_base(api_base))
+    }
+
+    /// Sets the API token used to authenticate requests.
+    ///
+    /// By default the token is read from `GITHUB_TOKEN` or `GH_TOKEN`.
+    pub fn with_token(self, token: String) -> Self {
+        Self {
+            token: Some(token),
+            ..self
+        }
+    }
+
+    /// Sets the cache used to revalidate tag, release and commit responses.
+    ///
+    /// Cached responses are sent with `If-None-Match`, and a `304 Not Modified`
+    /// answer is served from the cache without downloading the body again.
+    pub fn with_cache(self, cache: Arc<dyn EtagCache>) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons.
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// Sets the `User-Agent` sent with every request.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets whether versions are collected from tags, releases or both.
+    ///
+    /// Skipping one of them halves the requests for repositories known to
+    /// use only the other. Even when releases are fetched, a repository found
+    /// without releases is not asked again for the lifetime of this host.
+    pub fn with_source(self, source: VersionSource) -> Self {
+        Self { source, ..self }
+    }
+
+    /// Fetches tags and releases through the GraphQL API, which needs far fewer requests.
+    ///
+    /// Tags and releases come back together, 100 of each per request, along with
+    /// the commit dates and commits the REST path looks up one request at a time.
+    /// GraphQL requires authentication, so without a token the REST API is used,
+    /// as it is when a query fails for any reason but a missing repository.
+    pub fn with_graphql(self, graphql: bool) -> Self {
+        Self { graphql, ..self }
+    }
+
+    /// Keeps only the uploaded release assets matching `asset_filter`.
+    ///
+    /// Source tarballs of tags and releases are always kept, and checksum
+    /// manifests are still read when the filter drops them.
+    pub fn with_asset_filter(self, asset_filter: AssetFilter) -> Self {
+        Self {
+            asset_filter: Some(asset_filter),
+            ..self
+        }
+    }
+
+    /// Downloads `SHA256SUMS` release assets to fill in checksums GitHub has no digest for.
+    ///
+    /// Costs one extra download per release carrying such a manifest.
+    pub fn with_checksum_manifests(self, checksum_manifests: bool) -> Self {
+        Self {
+            checksum_manifests,
+            ..self
+        }
+    }
+
+    /// Dates versions without a release from the commit of their tag.
+    ///
+    /// Costs one request per tag, so only the 50 newest tag-only versions of a
+    /// listing are dated, with at most [`GithubHost::concurrency`] requests in
+    /// flight at once.
+    pub fn with_tag_dates(self, tag_dates: bool) -> Self {
+        Self { tag_dates, ..self }
+    }
+
+    /// Sets the number of per-version requests, such as commit dates, in flight at once.
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of pages fetched for tags and releases.
+    pub fn with_max_pages(self, max_pages: usize) -> Self {
+        Self { max_pages, ..self }
+    }
+
+    /// Sets the number of tags or releases requested per page, clamped to 1..=100.
+    pub fn with_per_page(self, per_page: u8) -> Self {
+        Self {
+            per_page: per_page.clamp(1, MAX_PER_PAGE),
+            ..self
+        }
+    }
+
+    /// Limits the listing to the newest `max_versions` versions.
+    ///
+    /// Tags and releases are listed newest first, so pagination stops as soon as
+    /// enough of them were fetched, which keeps huge repositories cheap.
+    pub fn with_max_versions(self, max_versions: usize) -> Self {
+        Self {
+            max_versions: Some(max_versions),
+            ..self
+        }
+    }
+
+    /// Drops prereleases from listings.
+    ///
+    /// Unlike dropping them afterwards, this keeps paginating until
+    /// `max_versions` stable versions were found.
+    pub fn with_stable(self, stable: bool) -> Self {
+        Self { stable, ..self }
+    }
+
+    /// Whether `count` items are enough to satisfy `max_versions`.
+    ///
+    /// Only items passing the tag filter and, with [`GithubHost::with_stable`],
+    /// stable ones may be counted, as the others never become versions.
+    fn enough(&self, count: usize) -> bool {
+        self.max_versions.is_some_and(|max| count >= max)
+    }
+
+    /// Whether `version` is listed, which prereleases are not with [`GithubHost::with_stable`].
+    fn keeps(&self, version: &VersionMetadata) -> bool {
+        !self.stable || !version.prerelease
+    }
+
+    /// Whether a tag, or a release of it flagged `prerelease`, can become a listed version.
+    fn counts(&self, tag: &str, prerelease: bool) -> bool {
+        self.tag_filter.matches(tag)
+            && !(self.stable && (prerelease || is_prerelease(self.tag_filter.normalize(tag))))
+    }
+
+    /// Returns the URL of the first page of the list endpoint at `path`.
+    fn list_url(&self, path: &str) -> Result<String, HostError> {
+        Ok(format!(
+            "{}?per_page={}",
+            self.repo_api_url(path)?,
+            self.per_page
+        ))
+    }
+
+    /// Returns the owner and name of the repository if requests were redirected to a new name.
+    ///
+    /// This is only known after a request was made, i.e. after [`Host::versions`].
+    pub fn canonical_location(&self) -> Option<(String, String)> {
+        self.moved_to
+            .lock()
+            .expect("moved_to lock poisoned")
+            .clone()
+    }
+
+    /// Returns the API URL for `path` within this repository.
+    fn repo_api_url(&self, path: &str) -> Result<String, HostError> {
+        let path = format!("repos/{}/{}/{path}", self.owner, self.repo);
+        self.api_base
+            .join(&path)
+            .map(String::from)
+            .map_err(|e| HostError::invalid_url(format!("{}{path}", self.api_base), e))
+    }
+
     fn gh_client(&self, url: &str) -> Result<reqwest::RequestBuilder, HostError> {
-        debug!("Creating GitHub API client for URL: {}", url);
-        let client = reqwest::Client::new();
-        let client = client
+        debug!("Creating GitHub API request for URL: {}", url);
+        let client = self
+            .client
             .get(url)
             .header("Accept", "application/vnd.github.v3+json".to_string())
-            .header("User-Agent", "upstreams-rs".to_string())
+            .header("User-Agent", &self.user_agent)
             .header("X-GitHub-Api-Version", GH_API_VERSION);
+        // The token is for the API alone, never for URLs a response pointed elsewhere
+        let client = match &self.token {
+            Some(token) if self.is_api_url(url) => client.bearer_auth(token),
+            _ => client,
+        };
+        let client = match self.cache.as_ref().and_then(|cache| cache.get(url)) {
+            Some(cached) => client.header(reqwest::header::IF_NONE_MATCH, cached.etag),
+            None => client,
+        };
         Ok(client)
     }
 
-    /// Fetches tags from the GitHub REST API.
-    ///
-    /// # Returns
-    /// A Result containing either a vector of GithubTagResponse or an error
-    async fn fetch_tags(&self) -> Result<Vec<GithubTagResponse>, HostError> {
-        let tag_url = format!(
-            "https://api.github.com/repos/{}/{}/tags",
-            self.owner, self.repo
-        );
-        debug!("Fetching tags from: {}", tag_url);
+    /// Whether `url` has the same origin as the API base.
+    fn is_api_url(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|url| url.origin() == self.api_base.origin())
+    }
 
-        let tags = self
-            .gh_client(&tag_url)?
-            .send()
+    /// Fetches the body and `Link` header of `url`, revalidating any cached copy.
+    async fn fetch_cached(&self, url: &str, what: &str) -> Result<CachedResponse, HostError> {
+        let response = self
+            .retry
+            .send(self.gh_client(url)?)
             .await
             .map_err(|e| HostError::ApiRequest {
-                context: "failed to fetch tags".into(),
+                context: format!("failed to fetch {what}"),
                 source: e,
-            })?
-            .json::<Vec<GithubTagResponse>>()
+            })
+            .and_then(|response| self.check_response(response))?;
+        if response.url().as_str() != url {
+            self.note_redirect(&response.url().clone()).await;
+        }
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(cached) = self.cache.as_ref().and_then(|cache| cache.get(url)) {
+                debug!("Using cached {} for: {}", what, url);
+                return Ok(cached);
+            }
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(String::from)
+        };
+        let etag = header(reqwest::header::ETAG);
+        let link = header(reqwest::header::LINK);
+        let body = response.text().await.map_err(|e| HostError::ApiResponse {
+            context: format!("failed to read {what} response from {url}"),
+            source: e,
+        })?;
+
+        let fetched = CachedResponse {
+            etag: etag.clone().unwrap_or_default(),
+            body,
+            link,
+        };
+        if let (Some(cache), Some(_)) = (&self.cache, etag) {
+            cache.put(url, fetched.clone());
+        }
+        Ok(fetched)
+    }
+
+    /// Records the new name of a renamed repository from the URL a request was redirected to.
+    ///
+    /// The API redirects renamed repositories to either `repos/<owner>/<repo>/...`
+    /// or `repositories/<id>/...`, in which case the name is looked up by id.
+    async fn note_redirect(&self, location: &Url) {
+        if self.canonical_location().is_some() {
+            return;
+        }
+        let Some(path) = location.path().strip_prefix(self.api_base.path()) else {
+            return;
+        };
+        let mut parts = path.split('/');
+        let moved = match (parts.next(), parts.next(), parts.next()) {
+            (Some("repos"), Some(owner), Some(repo)) => Some((owner.to_string(), repo.to_string())),
+            (Some("repositories"), Some(id), _) => self.fetch_repository_name(id).await,
+            _ => None,
+        };
+        let Some((owner, repo)) = moved else {
+            return;
+        };
+        if owner.eq_ignore_ascii_case(&self.owner) && repo.eq_ignore_ascii_case(&self.repo) {
+            return;
+        }
+        warn!(
+            "Repository {}/{} has moved to {}/{}",
+            self.owner, self.repo, owner, repo
+        );
+        *self.moved_to.lock().expect("moved_to lock poisoned") = Some((owner, repo));
+    }
+
+    /// Looks up the owner and name of the repository with the numeric `id`.
+    async fn fetch_repository_name(&self, id: &str) -> Option<(String, String)> {
+        let url = self.api_base.join(&format!("repositories/{id}")).ok()?;
+        let response = self.retry.send(self.gh_client(url.as_str()).ok()?).await;
+        let repository = self
+            .check_response(response.ok()?)
+            .ok()?
+            .json::<GithubRepositoryResponse>()
             .await
-            .map_err(|e| HostError::ApiResponse {
-                context: "failed to parse tags response".into(),
-                source: e,
-            })?;
+            .ok()?;
+        Some((repository.owner.login, repository.name))
+    }
+
+    /// Checks a response status, reporting rate limits and a rejected token distinctly.
+    fn check_response(&self, response: reqwest::Response) -> Result<reqwest::Response, HostError> {
+        if let Some(error) = rate_limit(&response) {
+            return Err(error);
+        }
+        if self.token.is_some() && response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(HostError::InvalidToken {
+                url: response.url().to_string(),
+            });
+        }
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(HostError::Unauthorized {
+                url: self.url.to_string(),
+            });
+        }
+        check_status(response)
+    }
+
+    /// Reports a missing list endpoint as a missing repository.
+    ///
+    /// Tags and releases exist for every repository, so a 404 there means the
+    /// repository itself does not exist, or is private and hidden from us.
+    fn repository_error(&self, error: HostError) -> HostError {
+        match error {
+            HostError::NotFound { .. } => HostError::NotFound {
+                url: self.url.to_string(),
+            },
+            error => error,
+        }
+    }
+
+    /// Fetches every page of a list endpoint, following `Link: rel="next"` headers.
+    ///
+    /// Stops after `max_pages` pages so pathological repositories stay bounded,
+    /// or once `max_versions` items for which `counts` holds were fetched.
+    async fn fetch_paginated<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        what: &str,
+        counts: impl Fn(&T) -> bool,
+    ) -> Result<Vec<T>, HostError> {
+        let mut next = Some(self.list_url(path)?);
+        let mut items = vec![];
+        let mut counted = 0;
+        let mut pages = 0;
+
+        while let Some(url) = next {
+            if self.enough(counted) {
+                debug!("Stopping after {} {}", items.len(), what);
+                break;
+            }
+            if pages == self.max_pages {
+                warn!("Stopping after {} pages of {}", self.max_pages, what);
+                break;
+            }
+            let (page, link) = self.fetch_page(&url, what).await?;
+            next = link;
+            counted += page.iter().filter(|item| counts(item)).count();
+            items.extend(page);
+            pages += 1;
+        }
+
+        Ok(items)
+    }
 
-        info!("Successfully fetched {} tags", tags.len());
-        Ok(tags)
+    /// Fetches a single page of a list endpoint, along with the URL of the next page.
+    async fn fetch_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        what: &str,
+    ) -> Result<(Vec<T>, Option<String>), HostError> {
+        debug!("Fetching {} from: {}", what, url);
+        let response = self
+            .fetch_cached(url, what)
+            .await
+            .map_err(|e| self.repository_error(e))?;
+        let page = serde_json::from_str::<Vec<T>>(&response.body)
+            .map_err(|e| HostError::parse_error(url, format!("invalid {what} response: {e}")))?;
+        let next = match response.link.as_deref().and_then(next_link) {
+            Some(link) if !self.is_api_url(&link) => {
+                warn!("Not following the next page of {} to {}", what, link);
+                None
+            }
+            next => next,
+        };
+        Ok((page, next))
     }
 
-    /// Fetches releases from the GitHub REST API.
+    /// Fetches all published releases from the GitHub REST API, skipping drafts.
     ///
     /// # Returns
     /// A Result containing either a vector of GithubReleaseResponse or an error
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
     async fn fetch_releases(&self) -> Result<Vec<GithubReleaseResponse>, HostError> {
-        let releases_url = format!(
-            "https://api.github.com/repos/{}/{}/releases",
-            self.owner, self.repo
-        );
-        debug!("Fetching releases from: {}", releases_url);
+        if !self.source.releases() || self.no_releases.load(Ordering::Relaxed) {
+            debug!("Skipping releases");
+            return Ok(vec![]);
+        }
+        let mut releases: Vec<GithubReleaseResponse> = self
+            .fetch_paginated("releases", "releases", |release: &GithubReleaseResponse| {
+                !release.draft && self.counts(&release.tag_name, release.prerelease)
+            })
+            .await?;
+        if releases.is_empty() {
+            debug!("Repository has no releases, not asking again");
+            self.no_releases.store(true, Ordering::Relaxed);
+        }
+        // Drafts are not published yet and may point at tags that don't exist
+        releases.retain(|release| !release.draft);
+        info!("Successfully fetched {} releases", releases.len());
+        Ok(releases)
+    }
+
+    /// Fetches the release for a single tag from the GitHub REST API.
+    ///
+    /// # Returns
+    /// A Result containing the release, or None if the tag has no release
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
+    async fn fetch_release(&self, tag: &str) -> Result<Option<GithubReleaseResponse>, HostError> {
+        self.fetch_release_at(&format!("releases/tags/{tag}")).await
+    }
+
+    /// Fetches the release at `path` below the repository, or None on a 404.
+    async fn fetch_release_at(
+        &self,
+        path: &str,
+    ) -> Result<Option<GithubReleaseResponse>, HostError> {
+        let release_url = self.repo_api_url(path)?;
+        debug!("Fetching release from: {}", release_url);
 
-        let releases = self
-            .gh_client(&releases_url)?
-            .send()
+        let response = self
+            .retry
+            .send(self.gh_client(&release_url)?)
             .await
             .map_err(|e| HostError::ApiRequest {
-                context: "failed to fetch releases".into(),
+                context: "failed to fetch release".into(),
                 source: e,
-            })?
-            .json::<Vec<GithubReleaseResponse>>()
+            })
+            .and_then(|response| self.check_response(response));
+        let response = match response {
+            Err(HostError::NotFound { .. }) => return Ok(None),
+            response => response?,
+        };
+
+        let release = response
+            .json::<GithubReleaseResponse>()
             .await
             .map_err(|e| HostError::ApiResponse {
-                context: "failed to parse releases response".into(),
+                context: format!("failed to parse release response from {release_url}"),
                 source: e,
             })?;
+        Ok(Some(release))
+    }
+}
 
-        info!("Successfully fetched {} releases", releases.len());
-        Ok(releases)
+/// Returns a [`HostError::RateLimited`] if the response was rejected by the rate limiter.
+///
+/// GitHub answers with a 403 or 429 and `X-RateLimit-Remaining: 0`, giving the
+/// reset time as a unix timestamp in `X-RateLimit-Reset`.
+fn rate_limit(response: &reqwest::Response) -> Option<HostError> {
+    let status = response.status();
+    if status != reqwest::StatusCode::FORBIDDEN && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        return None;
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<i64>().ok())
+    };
+    let remaining = header("x-ratelimit-remaining")?;
+    if remaining != 0 {
+        return None;
+    }
+    let reset_at = header("x-ratelimit-reset")
+        .and_then(|t| DateTime::from_timestamp(t, 0))
+        .unwrap_or_else(Utc::now);
+    warn!("GitHub rate limit exceeded until {}", reset_at);
+    Some(HostError::RateLimited {
+        reset_at,
+        remaining: 0,
+    })
+}
+
+/// Reads an API token from `GITHUB_TOKEN`, falling back to `GH_TOKEN`.
+fn token_from_env() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|token| !token.is_empty())
+}
+
+/// Returns the REST API base, honouring `GITHUB_API_URL` as set by GitHub Actions.
+fn api_base_from_env(url: &Url) -> Url {
+    let server = std::env::var("GITHUB_SERVER_URL").ok();
+    let api = std::env::var("GITHUB_API_URL").ok();
+    env_api_base(url, server.as_deref(), api.as_deref())
+        .unwrap_or_else(|| Url::parse(GH_API_URL).expect("valid GitHub API URL"))
+}
+
+/// Returns the configured API base if it belongs to the instance hosting `url`.
+///
+/// A workflow running on an Enterprise instance sets `GITHUB_API_URL` to that
+/// instance, which must not be used for repositories on github.com. The
+/// instance is named by `GITHUB_SERVER_URL`, which defaults to github.com.
+fn env_api_base(url: &Url, server: Option<&str>, api: Option<&str>) -> Option<Url> {
+    let server = server
+        .filter(|server| !server.trim().is_empty())
+        .map_or(Ok(None), |server| Url::parse(server.trim()).map(Some))
+        .ok()?;
+    let server_host = server
+        .as_ref()
+        .and_then(Url::host_str)
+        .unwrap_or("github.com");
+    let repo_host = match url.host_str()? {
+        "api.github.com" => "github.com",
+        host => host,
+    };
+    if !server_host.eq_ignore_ascii_case(repo_host) {
+        debug!(
+            "Ignoring GITHUB_API_URL of {} for a repository on {}",
+            server_host, repo_host
+        );
+        return None;
+    }
+    parse_api_base(api?)
+}
+
+/// Parses a configured API base, ignoring empty or invalid values.
+fn parse_api_base(value: &str) -> Option<Url> {
+    if value.trim().is_empty() {
+        return None;
+    }
+    match Url::parse(value.trim()) {
+        Ok(url) => Some(with_trailing_slash(url)),
+        Err(e) => {
+            warn!("Ignoring invalid GitHub API URL {}: {}", value, e);
+            None
+        }
     }
 }
 
+/// Extracts the `rel="next"` target from an RFC 5988 `Link` header.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == r#"rel="next""#)
+            .then(|| {
+                target
+                    .trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
 /// Response structure for the GitHub tags REST API endpoint.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct GithubTagResponse {
     /// The name of the tag
     pub name: String,
@@ -143,7 +963,7 @@ pub struct GithubTagResponse {
 }
 
 /// Response structure for commit information in a GitHub tag response.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct GithubTagCommit {
     /// The SHA hash of the commit
     pub sha: String,
@@ -152,7 +972,7 @@ pub struct GithubTagCommit {
 }
 
 /// Response structure for the GitHub releases REST API endpoint.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct GithubReleaseResponse {
     /// The name of the tag associated with this release
     pub tag_name: String,
@@ -168,12 +988,82 @@ pub struct GithubReleaseResponse {
     pub zipball_url: String,
     /// When this release was created
     pub created_at: DateTime<Utc>,
-    /// When this release was published
-    pub published_at: DateTime<Utc>,
+    /// Whether the release is marked as a prerelease
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Whether the release is an unpublished draft
+    #[serde(default)]
+    pub draft: bool,
+    /// When this release was published, if it was and the date is valid
+    #[serde(default, deserialize_with = "lenient_date")]
+    pub published_at: Option<DateTime<Utc>>,
 }
 
-/// Response structure for release assets in a GitHub release response.
+/// Response structure for the GitHub git ref and annotated tag endpoints.
+#[derive(Deserialize, Debug)]
+pub struct GithubGitRef {
+    /// The object the ref or tag points to
+    pub object: GithubGitObject,
+}
+
+/// Response structure for the GitHub repository REST API endpoint.
+#[derive(Deserialize, Debug)]
+pub struct GithubRepositoryResponse {
+    /// The name of the repository
+    pub name: String,
+    /// The account owning the repository
+    pub owner: GithubAccount,
+}
+
+/// A GitHub user or organization.
+#[derive(Deserialize, Debug)]
+pub struct GithubAccount {
+    /// The login name of the account
+    pub login: String,
+}
+
+/// A git object referenced by a ref or an annotated tag.
+#[derive(Deserialize, Debug)]
+pub struct GithubGitObject {
+    /// The SHA hash of the object
+    pub sha: String,
+    /// The object type, i.e. "commit" or "tag"
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+/// Response structure for the GitHub commit REST API endpoint.
+#[derive(Deserialize, Debug)]
+pub struct GithubCommitResponse {
+    /// The git commit object
+    pub commit: GithubCommitDetail,
+}
+
+/// The git commit object in a GitHub commit response.
+#[derive(Deserialize, Debug)]
+pub struct GithubCommitDetail {
+    /// Who committed the change, and when
+    pub committer: Option<GithubCommitSignature>,
+}
+
+/// Name and date of a commit author or committer.
 #[derive(Deserialize, Debug)]
+pub struct GithubCommitSignature {
+    /// When the commit was made, if the date is valid
+    #[serde(default, deserialize_with = "lenient_date")]
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// Deserializes an optional ISO 8601 date, treating invalid values as missing.
+fn lenient_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.and_then(|v| v.parse().ok()))
+}
+
+/// Response structure for release assets in a GitHub release response.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct GithubReleaseAsset {
     /// The filename of the asset
     pub name: String,
@@ -193,87 +1083,997 @@ pub struct GithubReleaseAsset {
     pub updated_at: DateTime<Utc>,
     /// Direct download URL for the asset
     pub browser_download_url: String,
+    /// Digest of the asset as `<algorithm>:<hex>`, on assets uploaded since GitHub computes them
+    #[serde(default)]
+    pub digest: Option<String>,
 }
 
-#[async_trait]
-impl Host for GithubHost {
-    /// Fetches all versions available for this repository
-    ///
-    /// # Returns
-    /// A Result containing either a vector of VersionedAsset or an error
-    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
-        debug!("Fetching versions for {}/{}", self.owner, self.repo);
-        let tags = self.fetch_tags().await?;
-        let releases = self.fetch_releases().await?;
+/// Response envelope of the GitHub GraphQL API.
+#[derive(Deserialize, Debug)]
+pub struct GithubGraphqlResponse {
+    /// The requested data, missing if the query failed as a whole
+    pub data: Option<GithubGraphqlData>,
+    /// Errors reported for the query
+    #[serde(default)]
+    pub errors: Vec<GithubGraphqlError>,
+}
 
-        // Combine tags and releases into a single list of version strings
-        let version_strings = tags
-            .iter()
-            .map(|tag| tag.name.clone())
-            .chain(releases.iter().map(|release| release.tag_name.clone()))
-            .collect::<BTreeSet<String>>();
-
-        info!("Found {} unique versions", version_strings.len());
-        let mut found = Vec::new();
-
-        for version in version_strings {
-            debug!("Processing version: {}", version);
-            let mut downloads = BTreeSet::new();
-            for tag in tags.iter().filter(|tag| tag.name == version) {
-                downloads.insert(VersionedAsset {
-                    url: tag.tarball_url.clone(),
-                    kind: AssetKind::Autogenerated,
-                    released_at: None,
-                    updated_at: None,
-                });
-            }
-            for release in releases
-                .iter()
-                .filter(|release| release.tag_name == version)
-            {
-                downloads.insert(VersionedAsset {
-                    url: release.tarball_url.clone(),
-                    kind: AssetKind::Release,
-                    released_at: Some(release.created_at),
-                    updated_at: Some(release.published_at),
-                });
-                for asset in release.assets.iter() {
-                    // TODO: Specialise asset kind based on content type
-                    let kind = AssetKind::Autogenerated;
-                    downloads.insert(VersionedAsset {
-                        url: asset.browser_download_url.clone(),
-                        kind,
-                        released_at: Some(asset.created_at),
-                        updated_at: Some(asset.updated_at),
-                    });
-                }
-            }
+/// The data returned for the tags and releases query.
+#[derive(Deserialize, Debug)]
+pub struct GithubGraphqlData {
+    /// The repository, missing if it does not exist or is not visible
+    pub repository: Option<GithubGraphqlRepository>,
+}
 
-            // Find the release notes for this version
-            let release_notes = releases
-                .iter()
-                .find(|release| release.tag_name == version)
-                .map(|release| release.body.clone());
-            let released_at = releases
+/// The tags and releases of a repository, each missing once fully fetched.
+#[derive(Deserialize, Debug)]
+pub struct GithubGraphqlRepository {
+    /// A page of tag refs
+    pub refs: Option<GithubGraphqlConnection<GithubGraphqlRef>>,
+    /// A page of releases
+    pub releases: Option<GithubGraphqlConnection<GithubGraphqlRelease>>,
+}
+
+/// A page of a paginated GraphQL connection.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubGraphqlConnection<T> {
+    /// Where the page ends, and whether more pages follow
+    pub page_info: GithubGraphqlPageInfo,
+    /// The items on this page
+    pub nodes: Vec<T>,
+}
+
+/// Pagination state of a GraphQL connection.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubGraphqlPageInfo {
+    /// Whether another page follows this one
+    pub has_next_page: bool,
+    /// Cursor to pass as `after` to fetch the next page
+    pub end_cursor: Option<String>,
+}
+
+/// A tag ref in a GraphQL response.
+#[derive(Deserialize, Debug)]
+pub struct GithubGraphqlRef {
+    /// GitHub's internal node ID for this ref
+    pub id: String,
+    /// The name of the tag, without `refs/tags/`
+    pub name: String,
+    /// The commit or annotated tag object the ref points to
+    pub target: GithubGraphqlTarget,
+}
+
+/// A git object in a GraphQL response.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubGraphqlTarget {
+    /// The type of the object (i.e. `Commit`, `Tag` or `Tree`)
+    #[serde(rename = "__typename", default)]
+    pub kind: Option<String>,
+    /// The SHA hash of the object
+    pub oid: String,
+    /// When the commit was made, for commits
+    #[serde(default, deserialize_with = "lenient_date")]
+    pub committed_date: Option<DateTime<Utc>>,
+    /// The object an annotated tag points to, for tags
+    pub target: Option<Box<GithubGraphqlTarget>>,
+}
+
+impl GithubGraphqlTarget {
+    /// Returns the object an annotated tag points to, or this object otherwise.
+    fn peel(self) -> Self {
+        match self.target {
+            Some(target) => *target,
+            None => self,
+        }
+    }
+}
+
+/// A release in a GraphQL response.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubGraphqlRelease {
+    /// The name of the tag associated with this release
+    pub tag_name: String,
+    /// The title of the release
+    pub name: Option<String>,
+    /// The description/body text of the release
+    pub description: Option<String>,
+    /// Whether the release is an unpublished draft
+    pub is_draft: bool,
+    /// Whether the release is marked as a prerelease
+    pub is_prerelease: bool,
+    /// When this release was created
+    pub created_at: DateTime<Utc>,
+    /// When this release was published, if it was and the date is valid
+    #[serde(default, deserialize_with = "lenient_date")]
+    pub published_at: Option<DateTime<Utc>>,
+    /// The commit the release tag points to
+    pub tag_commit: Option<GithubGraphqlTarget>,
+    /// The first [`MAX_GRAPHQL_ASSETS`] assets attached to this release
+    pub release_assets: GithubGraphqlConnection<GithubGraphqlAsset>,
+}
+
+/// A release asset in a GraphQL response.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubGraphqlAsset {
+    /// The filename of the asset
+    pub name: String,
+    /// MIME type of the asset
+    pub content_type: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Number of times this asset has been downloaded
+    pub download_count: u64,
+    /// When this asset was created
+    pub created_at: DateTime<Utc>,
+    /// When this asset was last updated
+    pub updated_at: DateTime<Utc>,
+    /// Direct download URL for the asset
+    pub download_url: String,
+    /// The digest of the asset as `<algorithm>:<hex>`, if GitHub computed one
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+/// An error reported by the GraphQL API.
+#[derive(Deserialize, Debug)]
+pub struct GithubGraphqlError {
+    /// Human readable description of the error
+    pub message: String,
+    /// The kind of error, i.e. "NOT_FOUND"
+    #[serde(rename = "type", default)]
+    pub kind: Option<String>,
+}
+
+/// Progress of [`GithubHost::versions_stream`] through the tag pages.
+enum StreamState {
+    /// Nothing was fetched yet
+    Start,
+    /// Tag pages are being fetched
+    Tags {
+        /// All published releases of the repository
+        releases: Vec<GithubReleaseResponse>,
+        /// Checksums read from `SHA256SUMS` release assets, keyed by tag name
+        manifests: BTreeMap<String, BTreeMap<String, String>>,
+        /// URL of the next page of tags, if any
+        next: Option<String>,
+        /// Number of tag pages fetched so far
+        pages: usize,
+        /// Versions already yielded, so tags shifting between pages are not repeated
+        seen: BTreeSet<String>,
+    },
+    /// Every version was yielded
+    Done,
+}
+
+impl GithubHost {
+    /// Streams the versions of the repository as the pages of tags are fetched.
+    ///
+    /// Releases are fetched along with the first page of tags, as there are
+    /// usually far fewer of them. Each page of tags then becomes a batch of
+    /// versions, and versions that only exist as a release come last. Versions
+    /// are yielded in the order the API lists them, which is newest first for
+    /// most repositories, so callers can stop early without fetching every page.
+    pub fn versions_stream(
+        &self,
+    ) -> impl Stream<Item = Result<VersionMetadata, HostError>> + Send + '_ {
+        stream::try_unfold(StreamState::Start, move |state| async move {
+            let (page, state) = match state {
+                StreamState::Start => {
+                    let url = self.list_url("tags")?;
+                    let tags = async {
+                        match self.source.tags() {
+                            true => self.fetch_page(&url, "tags").await,
+                            false => Ok((vec![], None)),
+                        }
+                    };
+                    let (page, releases) = tokio::join!(tags, self.fetch_releases());
+                    let (tags, next) = page?;
+                    let mut releases = releases?;
+                    let manifests = self.fetch_checksum_manifests(&releases).await;
+                    self.filter_assets(&mut releases);
+                    let mut seen = BTreeSet::new();
+                    let found = self
+                        .collect_tag_page(tags, &releases, &manifests, &mut seen)
+                        .await;
+                    let state = StreamState::Tags {
+                        releases,
+                        manifests,
+                        next,
+                        pages: 1,
+                        seen,
+                    };
+                    (found, state)
+                }
+                StreamState::Tags {
+                    releases,
+                    manifests,
+                    next: Some(url),
+                    pages,
+                    mut seen,
+                } if pages < self.max_pages && !self.enough(seen.len()) => {
+                    let (tags, next) = self.fetch_page(&url, "tags").await?;
+                    let found = self
+                        .collect_tag_page(tags, &releases, &manifests, &mut seen)
+                        .await;
+                    let state = StreamState::Tags {
+                        releases,
+                        manifests,
+                        next,
+                        pages: pages + 1,
+                        seen,
+                    };
+                    (found, state)
+                }
+                StreamState::Tags {
+                    releases,
+                    manifests,
+                    next,
+                    seen,
+                    ..
+                } => {
+                    if next.is_some() && !self.enough(seen.len()) {
+                        warn!("Stopping after {} pages of tags", self.max_pages);
+                    }
+                    let mut found = collect_versions(&[], &releases, &self.tag_filter);
+                    found.retain(|v| !seen.contains(&v.version) && self.keeps(v));
+                    self.attach_manifest_checksums(&manifests, &mut found);
+                    self.fill_source_refs(&releases, &mut found).await;
+                    (found, StreamState::Done)
+                }
+                StreamState::Done => return Ok(None),
+            };
+            Ok(Some((stream::iter(page.into_iter().map(Ok)), state)))
+        })
+        .try_flatten()
+    }
+
+    /// Returns the GraphQL endpoint belonging to the REST API base.
+    ///
+    /// GitHub Enterprise serves it at `/api/graphql`, next to `/api/v3/`.
+    fn graphql_url(&self) -> Result<Url, HostError> {
+        let path = match self.api_base.path().ends_with("/v3/") {
+            true => "../graphql",
+            false => "graphql",
+        };
+        self.api_base
+            .join(path)
+            .map_err(|e| HostError::invalid_url(format!("{}{path}", self.api_base), e))
+    }
+
+    /// Fetches the next pages of tags and releases with a single GraphQL query.
+    ///
+    /// `refs` and `releases` hold the cursor to continue after, or `None` once
+    /// that connection was fully fetched.
+    async fn fetch_graphql_page(
+        &self,
+        token: &str,
+        refs: &Option<Option<String>>,
+        releases: &Option<Option<String>>,
+    ) -> Result<GithubGraphqlRepository, HostError> {
+        let url = self.graphql_url()?;
+        debug!("Querying tags and releases from: {}", url);
+        let query = serde_json::json!({
+            "query": GRAPHQL_QUERY,
+            "variables": {
+                "owner": self.owner,
+                "repo": self.repo,
+                "first": self.per_page,
+                "refs": refs.clone().flatten(),
+                "releases": releases.clone().flatten(),
+                "withRefs": refs.is_some(),
+                "withReleases": releases.is_some(),
+                "assets": MAX_GRAPHQL_ASSETS,
+            },
+        });
+        let request = self
+            .client
+            .post(url.as_str())
+            .header("User-Agent", &self.user_agent)
+            .bearer_auth(token)
+            .json(&query);
+        let response = self
+            .retry
+            .send(request)
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to send GraphQL query".into(),
+                source: e,
+            })
+            .and_then(|response| self.check_response(response))?
+            .json::<GithubGraphqlResponse>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse GraphQL response from {url}"),
+                source: e,
+            })?;
+
+        match response.data.and_then(|data| data.repository) {
+            Some(repository) => Ok(repository),
+            None if response
+                .errors
                 .iter()
-                .find(|release| release.tag_name == version)
-                .map(|release| release.created_at);
-            found.push(VersionMetadata {
-                version,
-                downloads: downloads.into_iter().collect(),
-                release_notes,
-                released_at,
+                .any(|e| e.kind.as_deref() == Some("NOT_FOUND")) =>
+            {
+                Err(HostError::NotFound {
+                    url: self.url.to_string(),
+                })
+            }
+            None => {
+                let messages = response
+                    .errors
+                    .iter()
+                    .map(|e| e.message.as_str())
+                    .collect::<Vec<_>>();
+                Err(HostError::parse_error(
+                    url,
+                    format!("GraphQL query failed: {}", messages.join("; ")),
+                ))
+            }
+        }
+    }
+
+    /// Fetches all versions through the GraphQL API.
+    ///
+    /// Tags and releases are mapped to their REST API equivalents, so the versions
+    /// match those of the REST path. Commit dates and the commits of release-only
+    /// versions come with the query instead of one request each; like on the REST
+    /// path, the dates are only used when enabled with
+    /// [`GithubHost::with_tag_dates`], and then for every version. Releases with
+    /// more assets than one query returns are fetched again through the REST API.
+    #[tracing::instrument(skip(self, token), fields(owner = %self.owner, repo = %self.repo))]
+    async fn versions_graphql(&self, token: &str) -> Result<Vec<VersionMetadata>, HostError> {
+        let mut refs = self.source.tags().then_some(None);
+        let mut release_cursor = self.source.releases().then_some(None);
+        let mut tags = vec![];
+        let mut releases = vec![];
+        let mut dates = BTreeMap::new();
+        let mut commits = BTreeMap::new();
+        let mut truncated = vec![];
+        let mut uncommitted = BTreeSet::new();
+        let mut pages = 0;
+
+        while refs.is_some() || release_cursor.is_some() {
+            if pages == self.max_pages {
+                warn!(
+                    "Stopping after {} pages of tags and releases",
+                    self.max_pages
+                );
+                break;
+            }
+            let repository = self
+                .fetch_graphql_page(token, &refs, &release_cursor)
+                .await?;
+            pages += 1;
+
+            refs = None;
+            if let Some(connection) = repository.refs {
+                let info = connection.page_info;
+                refs = info.has_next_page.then_some(info.end_cursor);
+                for node in connection.nodes {
+                    let target = node.target.peel();
+                    // Like on the REST path, such a tag is still a version, without a commit
+                    if target.kind.as_deref().is_some_and(|kind| kind != "Commit") {
+                        warn!(
+                            "Tag {} points at a {} instead of a commit",
+                            node.name,
+                            target.kind.as_deref().unwrap_or_default()
+                        );
+                        uncommitted.insert(node.name.clone());
+                    }
+                    if let Some(date) = target.committed_date {
+                        dates.insert(node.name.clone(), date);
+                    }
+                    tags.push(GithubTagResponse {
+                        zipball_url: self
+                            .repo_api_url(&format!("zipball/refs/tags/{}", node.name))?,
+                        tarball_url: self
+                            .repo_api_url(&format!("tarball/refs/tags/{}", node.name))?,
+                        commit: GithubTagCommit {
+                            url: self.repo_api_url(&format!("commits/{}", target.oid))?,
+                            sha: target.oid,
+                        },
+                        node_id: node.id,
+                        name: node.name,
+                    });
+                }
+                let counted = tags.iter().filter(|t| self.counts(&t.name, false)).count();
+                if self.enough(counted) {
+                    refs = None;
+                }
+            }
+
+            release_cursor = None;
+            if let Some(connection) = repository.releases {
+                let info = connection.page_info;
+                release_cursor = info.has_next_page.then_some(info.end_cursor);
+                // Drafts are not published yet and may point at tags that don't exist
+                for node in connection.nodes.into_iter().filter(|node| !node.is_draft) {
+                    if let Some(commit) = node.tag_commit {
+                        commits.insert(node.tag_name.clone(), commit.oid);
+                    }
+                    if node.release_assets.page_info.has_next_page {
+                        truncated.push(node.tag_name.clone());
+                    }
+                    let assets = node
+                        .release_assets
+                        .nodes
+                        .into_iter()
+                        .map(|asset| GithubReleaseAsset {
+                            name: asset.name,
+                            label: None,
+                            content_type: asset.content_type,
+                            state: "uploaded".to_string(),
+                            size: asset.size,
+                            download_count: asset.download_count,
+                            created_at: asset.created_at,
+                            updated_at: asset.updated_at,
+                            browser_download_url: asset.download_url,
+                            digest: asset.digest,
+                        })
+                        .collect();
+                    releases.push(GithubReleaseResponse {
+                        tarball_url: self.repo_api_url(&format!("tarball/{}", node.tag_name))?,
+                        zipball_url: self.repo_api_url(&format!("zipball/{}", node.tag_name))?,
+                        tag_name: node.tag_name,
+                        name: node.name.unwrap_or_default(),
+                        body: node.description.unwrap_or_default(),
+                        assets,
+                        created_at: node.created_at,
+                        prerelease: node.is_prerelease,
+                        draft: false,
+                        published_at: node.published_at,
+                    });
+                }
+                let counted = releases
+                    .iter()
+                    .filter(|r| self.counts(&r.tag_name, r.prerelease))
+                    .count();
+                if self.enough(counted) {
+                    release_cursor = None;
+                }
+            }
+        }
+        info!(
+            "Fetched {} tags and {} releases in {} queries",
+            tags.len(),
+            releases.len(),
+            pages
+        );
+        self.refetch_truncated(&mut releases, truncated).await;
+
+        let manifests = self.fetch_checksum_manifests(&releases).await;
+        self.filter_assets(&mut releases);
+        let mut found = collect_versions(&tags, &releases, &self.tag_filter);
+        found.retain(|v| self.keeps(v));
+        self.attach_manifest_checksums(&manifests, &mut found);
+        for version in found.iter_mut() {
+            if version
+                .tag
+                .as_ref()
+                .is_some_and(|tag| uncommitted.contains(tag))
+            {
+                version.source_ref = None;
+            }
+            let tag = version.tag.as_ref();
+            if version.released_at.is_none() && self.tag_dates {
+                version.released_at = tag.and_then(|tag| dates.get(tag)).copied();
+            }
+            if version.source_ref.is_none() {
+                version.source_ref = tag.and_then(|tag| commits.get(tag)).cloned();
+            }
+        }
+        Ok(found)
+    }
+
+    /// Replaces the releases named in `truncated` with their REST API response.
+    ///
+    /// A GraphQL query returns at most [`MAX_GRAPHQL_ASSETS`] assets per release.
+    /// Lookups run [`GithubHost::concurrency`] at a time, and a release that
+    /// fails to download keeps the assets it already has.
+    async fn refetch_truncated(
+        &self,
+        releases: &mut [GithubReleaseResponse],
+        truncated: Vec<String>,
+    ) {
+        let mut requests = Vec::with_capacity(truncated.len());
+        for tag in truncated {
+            requests.push(async move {
+                let release = self.fetch_release(&tag).await;
+                (tag, release)
+            });
+        }
+        let fetched = stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        for (tag, release) in fetched {
+            match release {
+                Ok(Some(full)) => {
+                    if let Some(release) = releases.iter_mut().find(|r| r.tag_name == tag) {
+                        release.assets = full.assets;
+                    }
+                }
+                Ok(None) => warn!("Release {} disappeared while listing its assets", tag),
+                Err(e) => warn!("Failed to fetch all assets of release {}: {}", tag, e),
+            }
+        }
+    }
+
+    /// Turns a page of tags into versions, skipping versions in `seen` and adding the rest.
+    async fn collect_tag_page(
+        &self,
+        tags: Vec<GithubTagResponse>,
+        releases: &[GithubReleaseResponse],
+        manifests: &BTreeMap<String, BTreeMap<String, String>>,
+        seen: &mut BTreeSet<String>,
+    ) -> Vec<VersionMetadata> {
+        let page = tags
+            .iter()
+            .filter(|tag| self.tag_filter.matches(&tag.name))
+            .map(|tag| self.tag_filter.normalize(&tag.name).to_string())
+            .filter(|version| !seen.contains(version))
+            .collect::<BTreeSet<_>>();
+        let mut found = collect_versions(&tags, releases, &self.tag_filter);
+        found.retain(|v| page.contains(&v.version) && self.keeps(v));
+        // Earlier pages used up part of the budget, as tags are listed newest first
+        let budget = MAX_TAG_DATES.saturating_sub(seen.len());
+        seen.extend(found.iter().map(|v| v.version.clone()));
+
+        self.attach_manifest_checksums(manifests, &mut found);
+        self.fill_commit_dates(&tags, &mut found, budget).await;
+        found
+    }
+
+    /// Sets the release date of tag-only versions from the date of their commit.
+    ///
+    /// Does nothing unless enabled with [`GithubHost::with_tag_dates`]. Only the
+    /// `budget` newest versions are dated, a few requests at a time. Failures
+    /// only leave the date unset, so one bad commit lookup does not fail the
+    /// whole listing.
+    async fn fill_commit_dates(
+        &self,
+        tags: &[GithubTagResponse],
+        found: &mut [VersionMetadata],
+        budget: usize,
+    ) {
+        if !self.tag_dates {
+            return;
+        }
+        let mut undated = found
+            .iter()
+            .enumerate()
+            .filter(|(_, version)| version.released_at.is_none())
+            .filter_map(|(index, version)| {
+                let tag = tags
+                    .iter()
+                    .find(|tag| version.tag.as_ref() == Some(&tag.name))?;
+                Some((index, Version::from(version.version.as_str()), tag))
+            })
+            .collect::<Vec<_>>();
+        undated.sort_by(|a, b| b.1.cmp(&a.1));
+        if undated.len() > budget {
+            debug!("Dating {} of {} tag-only versions", budget, undated.len());
+            undated.truncate(budget);
+        }
+
+        let mut requests = Vec::with_capacity(undated.len());
+        for (index, _, tag) in undated {
+            requests
+                .push(async move { (index, tag, self.fetch_commit_date(&tag.commit.url).await) });
+        }
+        let dates = stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        for (index, tag, date) in dates {
+            match date {
+                Ok(date) => found[index].released_at = date,
+                Err(e) => warn!("Failed to fetch commit date for {}: {}", tag.name, e),
+            }
+        }
+    }
+
+    /// Drops the uploaded assets of each release that the asset filter does not match.
+    fn filter_assets(&self, releases: &mut [GithubReleaseResponse]) {
+        let Some(filter) = &self.asset_filter else {
+            return;
+        };
+        for release in releases.iter_mut() {
+            release
+                .assets
+                .retain(|asset| filter.matches(&asset.browser_download_url));
+        }
+    }
+
+    /// Downloads and parses the `SHA256SUMS` assets of `releases`, keyed by tag name.
+    ///
+    /// Does nothing unless enabled with [`GithubHost::with_checksum_manifests`].
+    /// Only the manifests of the newest [`MAX_MANIFESTS`] releases are read,
+    /// [`GithubHost::concurrency`] at a time. A manifest that fails to download
+    /// is skipped with a warning.
+    async fn fetch_checksum_manifests(
+        &self,
+        releases: &[GithubReleaseResponse],
+    ) -> BTreeMap<String, BTreeMap<String, String>> {
+        let mut manifests = BTreeMap::new();
+        if !self.checksum_manifests {
+            return manifests;
+        }
+        let mut listed = releases
+            .iter()
+            .filter_map(|release| {
+                let manifest = release
+                    .assets
+                    .iter()
+                    .find(|asset| listing::is_sha256_manifest(&asset.name))?;
+                let version = Version::from(self.tag_filter.normalize(&release.tag_name));
+                Some((version, release, manifest))
+            })
+            .collect::<Vec<_>>();
+        listed.sort_by(|a, b| b.0.cmp(&a.0));
+        if listed.len() > MAX_MANIFESTS {
+            debug!(
+                "Reading {} of {} checksum manifests",
+                MAX_MANIFESTS,
+                listed.len()
+            );
+            listed.truncate(MAX_MANIFESTS);
+        }
+
+        let mut requests = Vec::with_capacity(listed.len());
+        for (_, release, manifest) in listed {
+            requests.push(async move {
+                let body = match Url::parse(&manifest.browser_download_url) {
+                    Ok(url) => listing::fetch_text_as(&url, &self.user_agent, &self.retry).await,
+                    Err(e) => Err(HostError::invalid_url(&manifest.browser_download_url, e)),
+                };
+                (release, manifest, body)
+            });
+        }
+        let fetched = stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        for (release, manifest, body) in fetched {
+            match body {
+                Ok(body) => {
+                    manifests.insert(
+                        release.tag_name.clone(),
+                        listing::parse_checksums(&body, "sha256"),
+                    );
+                }
+                Err(e) => warn!("Failed to fetch {}: {}", manifest.browser_download_url, e),
+            }
+        }
+        manifests
+    }
+
+    /// Fills in checksums missing from GitHub's digests using downloaded manifests.
+    fn attach_manifest_checksums(
+        &self,
+        manifests: &BTreeMap<String, BTreeMap<String, String>>,
+        found: &mut [VersionMetadata],
+    ) {
+        for version in found.iter_mut() {
+            if let Some(sums) = version.tag.as_ref().and_then(|tag| manifests.get(tag)) {
+                listing::attach_checksums(std::slice::from_mut(version), sums);
+            }
+        }
+    }
+
+    /// Sets the commit of release-only versions by resolving their tag.
+    ///
+    /// Versions listed by the tags endpoint already carry their commit. As with
+    /// commit dates, only the newest [`MAX_SOURCE_REFS`] are looked up,
+    /// [`GithubHost::concurrency`] at a time, and a failed lookup only leaves
+    /// the commit unset.
+    async fn fill_source_refs(
+        &self,
+        releases: &[GithubReleaseResponse],
+        found: &mut [VersionMetadata],
+    ) {
+        let mut unresolved = found
+            .iter()
+            .enumerate()
+            .filter(|(_, version)| version.source_ref.is_none())
+            .filter_map(|(index, version)| {
+                let release = releases
+                    .iter()
+                    .find(|release| version.tag.as_ref() == Some(&release.tag_name))?;
+                Some((index, Version::from(version.version.as_str()), release))
+            })
+            .collect::<Vec<_>>();
+        unresolved.sort_by(|a, b| b.1.cmp(&a.1));
+        if unresolved.len() > MAX_SOURCE_REFS {
+            debug!(
+                "Resolving {} of {} release-only tags",
+                MAX_SOURCE_REFS,
+                unresolved.len()
+            );
+            unresolved.truncate(MAX_SOURCE_REFS);
+        }
+
+        let mut requests = Vec::with_capacity(unresolved.len());
+        for (index, _, release) in unresolved {
+            requests.push(async move {
+                let sha = self.fetch_tag_commit(&release.tag_name).await;
+                (index, release, sha)
             });
         }
+        let commits = stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+        for (index, release, sha) in commits {
+            match sha {
+                Ok(sha) => found[index].source_ref = sha,
+                Err(e) => warn!("Failed to resolve tag {}: {}", release.tag_name, e),
+            }
+        }
+    }
 
+    /// Resolves the commit SHA of `tag`, peeling annotated tags.
+    async fn fetch_tag_commit(&self, tag: &str) -> Result<Option<String>, HostError> {
+        let mut url = self.repo_api_url(&format!("git/ref/tags/{tag}"))?;
+        // An annotated tag points at a tag object, which in turn points at the commit
+        for _ in 0..MAX_TAG_DEPTH {
+            debug!("Fetching tag from: {}", url);
+            let response = self.fetch_cached(&url, "tag").await?;
+            let git_ref = serde_json::from_str::<GithubGitRef>(&response.body)
+                .map_err(|e| HostError::parse_error(&url, format!("invalid tag response: {e}")))?;
+            match git_ref.object.kind.as_str() {
+                "commit" => return Ok(Some(git_ref.object.sha)),
+                "tag" => url = self.repo_api_url(&format!("git/tags/{}", git_ref.object.sha))?,
+                kind => {
+                    warn!("Tag {} points at a {} instead of a commit", tag, kind);
+                    return Ok(None);
+                }
+            }
+        }
+        warn!(
+            "Tag {} is nested more than {} levels deep",
+            tag, MAX_TAG_DEPTH
+        );
+        Ok(None)
+    }
+
+    /// Fetches the committer date of the commit at `url`.
+    async fn fetch_commit_date(&self, url: &str) -> Result<Option<DateTime<Utc>>, HostError> {
+        debug!("Fetching commit from: {}", url);
+        let response = self.fetch_cached(url, "commit").await?;
+        let commit = serde_json::from_str::<GithubCommitResponse>(&response.body)
+            .map_err(|e| HostError::parse_error(url, format!("invalid commit response: {e}")))?;
+        Ok(commit.commit.committer.and_then(|c| c.date))
+    }
+}
+
+#[async_trait]
+impl Host for GithubHost {
+    /// Fetches all versions available for this repository
+    ///
+    /// # Returns
+    /// A Result containing either a vector of VersionedAsset or an error
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        debug!("Fetching versions for {}/{}", self.owner, self.repo);
+        if self.graphql && self.token.is_none() {
+            warn!("The GraphQL API requires a token, falling back to the REST API");
+        }
+        let graphql = match self.token.as_deref().filter(|_| self.graphql) {
+            Some(token) => match self.versions_graphql(token).await {
+                // A missing repository is reported against its URL, unlike a missing endpoint
+                Err(HostError::NotFound { url }) if url == self.url.as_str() => {
+                    return Err(HostError::NotFound { url })
+                }
+                Err(e) => {
+                    warn!("GraphQL query failed, falling back to the REST API: {e}");
+                    None
+                }
+                Ok(found) => Some(found),
+            },
+            None => None,
+        };
+        let found = match graphql {
+            Some(found) => found,
+            None => self.versions_stream().try_collect::<Vec<_>>().await?,
+        };
         info!("Processed {} versions with assets", found.len());
-        Ok(found)
+        let mut versions = sort_versions(found);
+        if let Some(max) = self.max_versions {
+            versions.truncate(max);
+        }
+        Ok(versions)
+    }
+
+    /// Returns the web URL of the repository's new location if it was renamed
+    fn moved_to(&self) -> Option<Url> {
+        let (owner, repo) = self.canonical_location()?;
+        let mut url = self.url.clone();
+        if url.host_str() == Some("api.github.com") {
+            url.set_host(Some("github.com")).ok()?;
+        }
+        url.set_path(&format!("/{owner}/{repo}"));
+        url.set_query(None);
+        url.set_fragment(None);
+        Some(url)
+    }
+
+    /// Fetches the newest version from the `releases/latest` endpoint
+    ///
+    /// This is a single request for repositories with releases. Others fall back
+    /// to the newest tag on the first page of tags. The commit of the version is
+    /// not resolved, to keep the lookup cheap.
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
+    async fn latest(&self) -> Result<Option<VersionMetadata>, HostError> {
+        if self.source.releases() {
+            if let Some(release) = self.fetch_release_at("releases/latest").await? {
+                if self.tag_filter.matches(&release.tag_name) {
+                    let mut releases = [release];
+                    self.filter_assets(&mut releases);
+                    return Ok(collect_versions(&[], &releases, &self.tag_filter)
+                        .into_iter()
+                        .next());
+                }
+            }
+        }
+        if !self.source.tags() {
+            // The latest release may be filtered out, so look through all of them
+            return Ok(newest(self.versions().await?));
+        }
+
+        debug!("No latest release, using the first page of tags");
+        let url = self.list_url("tags")?;
+        let response = self.fetch_cached(&url, "tags").await?;
+        let tags = serde_json::from_str::<Vec<GithubTagResponse>>(&response.body)
+            .map_err(|e| HostError::parse_error(&url, format!("invalid tags response: {e}")))?;
+        let versions = sort_versions(collect_versions(&tags, &[], &self.tag_filter));
+        Ok(newest(versions))
+    }
+
+    /// Fetches a single version, looking up its release directly by tag
+    ///
+    /// Tags without a release fall back to listing all versions.
+    #[tracing::instrument(skip(self), fields(owner = %self.owner, repo = %self.repo))]
+    async fn version(&self, version: &str) -> Result<Option<VersionMetadata>, HostError> {
+        let mut tags = match &self.tag_filter.strip_prefix {
+            Some(prefix) => vec![format!("{prefix}{version}")],
+            None => vec![version.to_string()],
+        };
+        // Normalised versions were most likely tagged with a `v` prefix
+        if self.tag_filter.project.is_some() && version.starts_with(|c: char| c.is_ascii_digit()) {
+            tags.push(format!("v{version}"));
+        }
+        let wanted =
+            |v: &VersionMetadata| v.version == version || v.tag.as_deref() == Some(version);
+
+        for tag in &tags {
+            if let Some(release) = self.fetch_release(tag).await? {
+                let mut releases = [release];
+                self.filter_assets(&mut releases);
+                let mut found = collect_versions(&[], &releases, &self.tag_filter);
+                self.fill_source_refs(&releases, &mut found).await;
+                return Ok(found.into_iter().find(wanted));
+            }
+        }
+        debug!("No release for tags {:?}, listing all versions", tags);
+        Ok(self.versions().await?.into_iter().find(wanted))
     }
 }
 
+/// Maps tags and releases to version metadata, one entry per unique tag name
+///
+/// When a tag and a release share a name, their assets are merged so that
+/// each URL appears once with its most specific kind. Tags rejected by `filter`
+/// are skipped, and the remaining ones are normalised into version strings.
+fn collect_versions(
+    tags: &[GithubTagResponse],
+    releases: &[GithubReleaseResponse],
+    filter: &TagFilter,
+) -> Vec<VersionMetadata> {
+    // Combine tags and releases into a single list of version strings
+    let version_strings = tags
+        .iter()
+        .map(|tag| tag.name.clone())
+        .chain(releases.iter().map(|release| release.tag_name.clone()))
+        .filter(|name| filter.matches(name))
+        .collect::<BTreeSet<String>>();
+
+    debug!("Found {} unique versions", version_strings.len());
+    let mut found = Vec::new();
+
+    for version in version_strings {
+        debug!("Processing version: {}", version);
+        let mut downloads = vec![];
+        for tag in tags.iter().filter(|tag| tag.name == version) {
+            downloads.push(VersionedAsset {
+                url: tag.tarball_url.clone(),
+                kind: AssetKind::Autogenerated,
+                released_at: None,
+                updated_at: None,
+                checksum: None,
+                size: None,
+                download_count: None,
+                verifies: vec![],
+            });
+        }
+        for release in releases
+            .iter()
+            .filter(|release| release.tag_name == version)
+        {
+            downloads.push(VersionedAsset {
+                url: release.tarball_url.clone(),
+                kind: AssetKind::Release,
+                released_at: Some(release.created_at),
+                updated_at: release.published_at,
+                checksum: None,
+                size: None,
+                download_count: None,
+                verifies: vec![],
+            });
+            for asset in release.assets.iter() {
+                downloads.push(VersionedAsset {
+                    url: asset.browser_download_url.clone(),
+                    kind: classify_asset(&asset.name, Some(&asset.content_type)),
+                    released_at: Some(asset.created_at),
+                    updated_at: Some(asset.updated_at),
+                    checksum: asset.digest.clone(),
+                    size: Some(asset.size),
+                    download_count: Some(asset.download_count),
+                    verifies: vec![],
+                });
+            }
+        }
+        let mut downloads = merge_assets(downloads);
+        link_companions(&mut downloads);
+
+        // Find the release notes for this version
+        let release_notes = releases
+            .iter()
+            .find(|release| release.tag_name == version)
+            .map(|release| release.body.clone());
+        let released_at = releases
+            .iter()
+            .find(|release| release.tag_name == version)
+            .and_then(|release| release.published_at);
+        // Tag-only versions fall back to guessing from the tag name
+        let prerelease = releases
+            .iter()
+            .find(|release| release.tag_name == version)
+            .map(|release| release.prerelease)
+            .unwrap_or_else(|| is_prerelease(&version));
+        let source_ref = tags
+            .iter()
+            .find(|tag| tag.name == version)
+            .map(|tag| tag.commit.sha.clone());
+        found.push(VersionMetadata {
+            version: filter.normalize(&version).to_string(),
+            downloads,
+            release_notes,
+            released_at,
+            prerelease,
+            source_ref,
+            tag: Some(version),
+        });
+    }
+
+    found
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
+    use crate::host::{
+        cache::MemoryCache,
+        mock::{MockResponse, MockServer},
+    };
+    use futures_util::StreamExt;
 
     /// Tests that the from_url function correctly handles valid and invalid GitHub URLs
     #[tokio::test]
@@ -283,6 +2083,7 @@ mod tests {
             "https://github.com/microsoft/vscode/archive/refs/tags/1.84.0.tar.gz",
             "https://github.com/torvalds/linux/archive/refs/tags/v6.6.tar.gz",
             "https://github.com/redis/redis/archive/refs/tags/7.2.1.tar.gz",
+            "https://codeload.github.com/cli/cli/tar.gz/refs/tags/v2.63.2",
         ];
 
         let invalid_urls = [
@@ -299,10 +2100,1531 @@ mod tests {
             assert!(l.is_ok())
         }
 
+        let url =
+            Url::parse("https://codeload.github.com/cli/cli/tar.gz/refs/tags/v2.63.2").unwrap();
+        let host = GithubHost::from_url(&url).unwrap();
+        assert_eq!((host.owner.as_str(), host.repo.as_str()), ("cli", "cli"));
+
         for url in invalid_urls {
             let url = Url::parse(url).unwrap();
             let l = GithubHost::from_url(&url);
             assert!(l.is_err())
         }
     }
+
+    /// Tests that release pages, API URLs and clone URLs resolve to the repository
+    #[test]
+    fn test_from_url_shapes() {
+        for url in [
+            "https://github.com/cli/cli",
+            "https://github.com/cli/cli.git",
+            "https://github.com/cli/cli/releases",
+            "https://github.com/cli/cli/releases/tag/v2.63.2",
+            "https://github.com/cli/cli/releases/download/v2.63.2/gh_2.63.2_linux_amd64.tar.gz",
+            "https://github.com/cli/cli/tags",
+            "https://github.com/cli/cli/tree/trunk/pkg",
+            "https://api.github.com/repos/cli/cli",
+            "https://api.github.com/repos/cli/cli/releases/latest",
+            "https://github.example.com/api/v3/repos/cli/cli.git",
+        ] {
+            let host = GithubHost::from_url(&Url::parse(url).unwrap()).unwrap();
+            assert_eq!(
+                (host.owner.as_str(), host.repo.as_str()),
+                ("cli", "cli"),
+                "{url}"
+            );
+        }
+
+        let url = Url::parse("https://github.com/api/tools").unwrap();
+        let host = GithubHost::from_url(&url).unwrap();
+        assert_eq!((host.owner.as_str(), host.repo.as_str()), ("api", "tools"));
+
+        let url = Url::parse("https://api.github.com/repos/cli").unwrap();
+        assert!(GithubHost::from_url(&url).is_err());
+    }
+
+    pub(super) fn tag(name: &str) -> GithubTagResponse {
+        GithubTagResponse {
+            name: name.to_string(),
+            zipball_url: format!("https://api.github.com/repos/cli/cli/zipball/refs/tags/{name}"),
+            tarball_url: format!("https://api.github.com/repos/cli/cli/tarball/refs/tags/{name}"),
+            commit: GithubTagCommit {
+                sha: "c3b1f2e9d8a7".to_string(),
+                url: "https://api.github.com/repos/cli/cli/commits/c3b1f2e9d8a7".to_string(),
+            },
+            node_id: "REF_kwDO".to_string(),
+        }
+    }
+
+    pub(super) fn release(tag_name: &str, assets: &[&str]) -> GithubReleaseResponse {
+        let created_at = "2024-12-03T10:00:00Z".parse().unwrap();
+        GithubReleaseResponse {
+            tag_name: tag_name.to_string(),
+            name: format!("GitHub CLI {tag_name}"),
+            body: "Bug fixes".to_string(),
+            assets: assets
+                .iter()
+                .map(|name| GithubReleaseAsset {
+                    name: name.to_string(),
+                    label: None,
+                    content_type: "application/octet-stream".to_string(),
+                    state: "uploaded".to_string(),
+                    size: 1024,
+                    download_count: 10,
+                    created_at,
+                    updated_at: created_at,
+                    browser_download_url: format!(
+                        "https://github.com/cli/cli/releases/download/{tag_name}/{name}"
+                    ),
+                    digest: None,
+                })
+                .collect(),
+            tarball_url: format!(
+                "https://api.github.com/repos/cli/cli/tarball/refs/tags/{tag_name}"
+            ),
+            zipball_url: format!(
+                "https://api.github.com/repos/cli/cli/zipball/refs/tags/{tag_name}"
+            ),
+            created_at,
+            prerelease: false,
+            draft: false,
+            published_at: Some(created_at),
+        }
+    }
+
+    /// Tests that response fixtures survive a round trip through JSON unchanged
+    #[test]
+    fn test_response_round_trip() {
+        let tags = vec![tag("v2.63.2"), tag("v2.63.1")];
+        let json = serde_json::to_string(&tags).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<GithubTagResponse>>(&json).unwrap(),
+            tags
+        );
+
+        let mut draft = release("v2.64.0", &["gh_2.64.0_linux_amd64.tar.gz"]);
+        draft.draft = true;
+        let json = serde_json::to_string(&draft).unwrap();
+        assert_eq!(
+            serde_json::from_str::<GithubReleaseResponse>(&json).unwrap(),
+            draft
+        );
+        assert_ne!(draft, release("v2.64.0", &[]));
+    }
+
+    /// Tests that a tarball shared by a tag and its release is listed once, as a release
+    #[test]
+    fn test_merge_tag_and_release() {
+        let tags = [tag("v2.63.2"), tag("v2.63.1")];
+        let releases = [release("v2.63.2", &["gh_2.63.2_linux_amd64.tar.gz"])];
+
+        let versions = collect_versions(&tags, &releases, &TagFilter::default());
+        assert_eq!(versions.len(), 2);
+
+        let latest = versions.iter().find(|v| v.version == "v2.63.2").unwrap();
+        assert_eq!(latest.downloads.len(), 2);
+        let tarball = latest
+            .downloads
+            .iter()
+            .find(|d| d.url.ends_with("/tarball/refs/tags/v2.63.2"))
+            .unwrap();
+        assert_eq!(tarball.kind, AssetKind::Release);
+        assert!(tarball.released_at.is_some());
+        assert_eq!((tarball.size, tarball.download_count), (None, None));
+
+        let binary = latest
+            .downloads
+            .iter()
+            .find(|d| d.url.ends_with("gh_2.63.2_linux_amd64.tar.gz"))
+            .unwrap();
+        assert_eq!((binary.size, binary.download_count), (Some(1024), Some(10)));
+    }
+
+    /// Tests that tag filters drop unrelated tags and strip the release prefix
+    #[test]
+    fn test_tag_filter() {
+        let tags = [
+            tag("release/1.2.3"),
+            tag("release/1.2.4-rc1"),
+            tag("docs-v1"),
+            tag("cli/v2.0.0"),
+        ];
+        let filter = TagFilter::default()
+            .include(Regex::new("^release/").unwrap())
+            .exclude(Regex::new("-rc").unwrap())
+            .strip_prefix("release/");
+
+        let versions = collect_versions(&tags, &[], &filter);
+        let names = versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["1.2.3"]);
+        assert!(versions[0].downloads[0]
+            .url
+            .ends_with("/tarball/refs/tags/release/1.2.3"));
+
+        assert_eq!(filter.normalize("release/"), "release/");
+        assert_eq!(filter.normalize("v1.0"), "v1.0");
+    }
+
+    /// Tests that project names, `release-` and `v` are stripped from tags
+    #[test]
+    fn test_normalize_tags() {
+        let cases = [
+            (
+                "cosmic-applets",
+                "cosmic-applets-1.0.0-alpha.6",
+                "1.0.0-alpha.6",
+            ),
+            ("cli", "v2.63.2", "2.63.2"),
+            // Other words are kept, prerelease markers in particular
+            (
+                "cosmic-applets",
+                "epoch-1.0.0-alpha.6",
+                "epoch-1.0.0-alpha.6",
+            ),
+            ("tool", "beta-2.0", "beta-2.0"),
+            ("tool", "rc_v2.1", "rc_v2.1"),
+            ("gtk", "gtk-4.14.1", "4.14.1"),
+            ("cosmic-applets", "Cosmic-Applets-1.0.0", "1.0.0"),
+            ("project_name", "project_name-1.2.3", "1.2.3"),
+            ("tool", "release-1.4", "1.4"),
+            ("tool", "rel_v1.5", "1.5"),
+            ("tool", "nightly", "nightly"),
+            ("tool", "docs-latest", "docs-latest"),
+        ];
+        for (project, tag, version) in cases {
+            let filter = TagFilter::default().project(project);
+            assert_eq!(filter.normalize(tag), version, "{project}: {tag}");
+        }
+
+        // An explicit strip prefix still wins
+        let filter = TagFilter::default().project("gtk").strip_prefix("gtk-");
+        assert_eq!(filter.normalize("gtk-v4.14.1"), "v4.14.1");
+        assert_eq!(filter.normalize("v4.14.2"), "4.14.2");
+
+        let versions =
+            collect_versions(&[tag("v2.63.2")], &[], &TagFilter::default().project("cli"));
+        assert_eq!(versions[0].version, "2.63.2");
+        assert_eq!(versions[0].tag.as_deref(), Some("v2.63.2"));
+
+        // A prerelease tag does not merge into the stable version and flag it
+        let versions = sort_versions(collect_versions(
+            &[tag("beta-2.0"), tag("2.0")],
+            &[],
+            &TagFilter::default().project("tool"),
+        ));
+        let found = versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.prerelease))
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![("2.0", false), ("beta-2.0", true)]);
+    }
+
+    /// Tests that a single version is resolved from its release without listing tags
+    #[tokio::test]
+    async fn test_version_direct() {
+        let release = serde_json::json!({
+            "tag_name": "v2.63.2",
+            "name": "GitHub CLI 2.63.2",
+            "body": "Bug fixes",
+            "assets": [],
+            "tarball_url": "https://api.github.com/repos/cli/cli/tarball/v2.63.2",
+            "zipball_url": "https://api.github.com/repos/cli/cli/zipball/v2.63.2",
+            "created_at": "2024-12-03T10:00:00Z",
+            "published_at": "2024-12-03T10:00:00Z"
+        });
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/releases/tags/v2.63.2",
+                MockResponse::new(200, release.to_string()),
+            ),
+            (
+                "/repos/cli/cli/git/ref/tags/v2.63.2",
+                MockResponse::new(200, r#"{"object": {"sha": "f00d", "type": "commit"}}"#),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let version = host.version("v2.63.2").await.unwrap().unwrap();
+        assert_eq!(version.release_notes.as_deref(), Some("Bug fixes"));
+        assert_eq!(version.downloads[0].kind, AssetKind::Release);
+        assert_eq!(version.source_ref.as_deref(), Some("f00d"));
+
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|r| r.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "/repos/cli/cli/releases/tags/v2.63.2",
+                "/repos/cli/cli/git/ref/tags/v2.63.2"
+            ]
+        );
+    }
+
+    /// Tests that a tag without a release is found by listing all versions
+    #[tokio::test]
+    async fn test_version_fallback() {
+        let tags = r#"[
+            {"name": "v1.1.0", "zipball_url": "https://example.com/z", "tarball_url": "https://example.com/t", "commit": {"sha": "bbb", "url": "https://example.com/c"}, "node_id": "T"},
+            {"name": "v1.0.0", "zipball_url": "https://example.com/z", "tarball_url": "https://example.com/t", "commit": {"sha": "aaa", "url": "https://example.com/c"}, "node_id": "T"}
+        ]"#;
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/tags?per_page=100",
+                MockResponse::new(200, tags),
+            ),
+            (
+                "/repos/cli/cli/releases?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let version = host.version("v1.0.0").await.unwrap().unwrap();
+        assert_eq!(version.version, "1.0.0");
+        assert_eq!(version.source_ref.as_deref(), Some("aaa"));
+        assert!(host.version("v2.0.0").await.unwrap().is_none());
+
+        let paths = server
+            .requests()
+            .into_iter()
+            .map(|r| r.path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths[0], "/repos/cli/cli/releases/tags/v1.0.0");
+        assert!(paths.contains(&"/repos/cli/cli/tags?per_page=100".to_string()));
+    }
+
+    /// Tests that a failing GraphQL query falls back to the REST API
+    #[tokio::test]
+    async fn test_graphql_fallback() {
+        let tags = r#"[{"name": "v1.0.0", "zipball_url": "https://example.com/z", "tarball_url": "https://example.com/t", "commit": {"sha": "aaa", "url": "https://example.com/c"}, "node_id": "T"}]"#;
+        let server = MockServer::with_routes(vec![
+            ("/graphql", MockResponse::new(502, "Bad Gateway")),
+            (
+                "/repos/cli/cli/tags?per_page=100",
+                MockResponse::new(200, tags),
+            ),
+            (
+                "/repos/cli/cli/releases?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_retry(RetryPolicy::none())
+            .with_token("secret".to_string())
+            .with_graphql(true);
+        let versions = host.versions().await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "1.0.0");
+        assert_eq!(server.requests()[0].path, "/graphql");
+
+        // A missing repository is not retried through REST
+        let missing = r#"{"data": {"repository": null}, "errors": [{"type": "NOT_FOUND", "message": "Could not resolve"}]}"#;
+        let server =
+            MockServer::with_routes(vec![("/graphql", MockResponse::new(200, missing))]).await;
+        let host = host.with_api_base(server.url.clone());
+        assert!(matches!(
+            host.versions().await,
+            Err(HostError::NotFound { url }) if url == "https://github.com/cli/cli"
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    /// Tests that the latest version takes one request, falling back to tags without releases
+    #[tokio::test]
+    async fn test_latest() {
+        let release = serde_json::json!({
+            "tag_name": "v2.63.2", "name": "GitHub CLI 2.63.2", "body": "Bug fixes",
+            "assets": [], "tarball_url": "", "zipball_url": "",
+            "created_at": "2024-12-03T10:00:00Z", "published_at": "2024-12-03T10:00:00Z"
+        });
+        let tags = serde_json::json!(["v1.9.0", "v1.10.0", "v1.11.0-rc1"]
+            .iter()
+            .map(|name| serde_json::json!({
+                "name": name, "zipball_url": "", "tarball_url": "", "node_id": "",
+                "commit": {"sha": "abc", "url": ""}
+            }))
+            .collect::<Vec<_>>());
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/releases/latest",
+                MockResponse::new(200, release.to_string()),
+            ),
+            (
+                "/repos/tools/tools/tags?per_page=100",
+                MockResponse::new(200, tags.to_string()),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let latest = host.latest().await.unwrap().unwrap();
+        assert_eq!(latest.version, "2.63.2");
+        assert_eq!(server.requests().len(), 1);
+
+        let url = Url::parse("https://github.com/tools/tools").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let latest = host.latest().await.unwrap().unwrap();
+        assert_eq!(latest.version, "1.10.0");
+    }
+
+    #[test]
+    fn test_next_link() {
+        let header = r#"<https://api.github.com/repositories/1/tags?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/tags?per_page=100&page=5>; rel="last""#;
+        assert_eq!(
+            next_link(header).as_deref(),
+            Some("https://api.github.com/repositories/1/tags?per_page=100&page=2")
+        );
+        assert_eq!(next_link(r#"<https://a/?page=1>; rel="prev""#), None);
+    }
+
+    /// Tests that versions are collected across every page of tags
+    #[tokio::test]
+    async fn test_pagination() {
+        let server = MockServer::start(|request| {
+            let page = request
+                .path
+                .rsplit_once("&page=")
+                .and_then(|(_, p)| p.parse::<usize>().ok())
+                .unwrap_or(1);
+            if !request.path.starts_with("/repos/cli/cli/tags") {
+                return MockResponse::new(200, "[]");
+            }
+            let host = request.header("host").unwrap_or_default().to_string();
+            let tags = (0..2)
+                .map(|i| {
+                    let mut t = tag(&format!("v1.{page}.{i}"));
+                    t.commit.url = format!("http://{host}/repos/cli/cli/commits/{}", t.commit.sha);
+                    t
+                })
+                .collect::<Vec<_>>();
+            let response = MockResponse::new(200, serde_json::to_string(&tags).unwrap());
+            if page < 3 {
+                let next = format!(
+                    "<http://{host}/repos/cli/cli/tags?per_page=100&page={}>; rel=\"next\"",
+                    page + 1
+                );
+                response.header("Link", next)
+            } else {
+                response
+            }
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let versions = host.versions().await.unwrap();
+        assert_eq!(versions.len(), 6);
+        // The commit of each tag is kept for pinning sources
+        assert!(versions
+            .iter()
+            .all(|v| v.source_ref.as_deref() == Some("c3b1f2e9d8a7")));
+
+        let capped = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_max_pages(2);
+        assert_eq!(capped.versions().await.unwrap().len(), 4);
+
+        // Pagination stops once enough versions were fetched
+        let before = server.requests().len();
+        let limited = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_per_page(2)
+            .with_max_versions(3);
+        let versions = limited.versions().await.unwrap();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1.2.1", "1.2.0", "1.1.1"]
+        );
+        let tag_pages = server.requests()[before..]
+            .iter()
+            .filter(|r| r.path.starts_with("/repos/cli/cli/tags"))
+            .map(|r| r.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tag_pages,
+            vec![
+                "/repos/cli/cli/tags?per_page=2",
+                "/repos/cli/cli/tags?per_page=100&page=2"
+            ]
+        );
+
+        // Taking the first versions of the stream only fetches the first page
+        let before = server.requests().len();
+        let first = host
+            .versions_stream()
+            .take(2)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            first.iter().map(|v| v.version.as_str()).collect::<Vec<_>>(),
+            vec!["1.1.0", "1.1.1"]
+        );
+        let pages = server.requests()[before..]
+            .iter()
+            .filter(|r| r.path.starts_with("/repos/cli/cli/tags"))
+            .count();
+        assert_eq!(pages, 1);
+    }
+
+    /// Tests that only versions passing the filters count towards `max_versions`
+    #[tokio::test]
+    async fn test_max_versions_filtered() {
+        let server = MockServer::start(|request| {
+            if !request.path.starts_with("/repos/cli/cli/tags") {
+                return MockResponse::new(200, "[]");
+            }
+            let page = request
+                .path
+                .rsplit_once("&page=")
+                .and_then(|(_, p)| p.parse::<usize>().ok())
+                .unwrap_or(1);
+            let names = match page {
+                1 => ["v2.0.0-rc2", "v2.0.0-rc1"],
+                2 => ["v1.1.0", "v1.0.0"],
+                _ => ["v0.9.0", "v0.8.0"],
+            };
+            let tags = names.map(tag);
+            let host = request.header("host").unwrap_or_default().to_string();
+            let next = format!(
+                "<http://{host}/repos/cli/cli/tags?per_page=2&page={}>; rel=\"next\"",
+                page + 1
+            );
+            MockResponse::new(200, serde_json::to_string(&tags).unwrap()).header("Link", next)
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let stable = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_per_page(2)
+            .with_max_pages(5)
+            .with_max_versions(2)
+            .with_stable(true);
+        let excluded = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_per_page(2)
+            .with_max_pages(5)
+            .with_max_versions(2)
+            .with_tag_filter(TagFilter::default().exclude(Regex::new("-rc").unwrap()));
+        for host in [stable, excluded] {
+            let before = server.requests().len();
+            let versions = host.versions().await.unwrap();
+            assert_eq!(
+                versions
+                    .iter()
+                    .map(|v| v.version.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["1.1.0", "1.0.0"]
+            );
+            let tag_pages = server.requests()[before..]
+                .iter()
+                .filter(|r| r.path.starts_with("/repos/cli/cli/tags"))
+                .count();
+            assert_eq!(tag_pages, 2);
+        }
+    }
+
+    /// Tests that tags and releases are paginated together through GraphQL
+    #[tokio::test]
+    async fn test_graphql() {
+        let first = r#"{"data": {"repository": {
+            "refs": {
+                "pageInfo": {"hasNextPage": true, "endCursor": "c1"},
+                "nodes": [{
+                    "id": "REF_1",
+                    "name": "v2.0.0",
+                    "target": {"oid": "aaa", "target": {"oid": "bbb", "committedDate": "2024-06-01T10:00:00Z"}}
+                }]
+            },
+            "releases": {
+                "pageInfo": {"hasNextPage": false, "endCursor": "r1"},
+                "nodes": [
+                    {
+                        "tagName": "v2.1.0-rc1", "name": null, "description": "Testing",
+                        "isDraft": false, "isPrerelease": true,
+                        "createdAt": "2024-07-01T10:00:00Z", "publishedAt": "2024-07-02T10:00:00Z",
+                        "tagCommit": {"oid": "ccc"},
+                        "releaseAssets": {
+                            "pageInfo": {"hasNextPage": false, "endCursor": null},
+                            "nodes": [{
+                                "name": "gh_2.1.0-rc1_linux_amd64.tar.gz",
+                                "contentType": "application/gzip", "size": 2048, "downloadCount": 3,
+                                "createdAt": "2024-07-01T10:00:00Z", "updatedAt": "2024-07-01T10:00:00Z",
+                                "downloadUrl": "https://github.com/cli/cli/releases/download/v2.1.0-rc1/gh_2.1.0-rc1_linux_amd64.tar.gz",
+                                "digest": "sha256:abcd"
+                            }]
+                        }
+                    },
+                    {
+                        "tagName": "v3.0.0", "name": "Draft", "description": null,
+                        "isDraft": true, "isPrerelease": false,
+                        "createdAt": "2024-08-01T10:00:00Z", "publishedAt": null, "tagCommit": null,
+                        "releaseAssets": {"pageInfo": {"hasNextPage": false, "endCursor": null}, "nodes": []}
+                    }
+                ]
+            }
+        }}}"#;
+        let second = r#"{"data": {"repository": {
+            "refs": {
+                "pageInfo": {"hasNextPage": false, "endCursor": "c2"},
+                "nodes": [
+                    {
+                        "id": "REF_2",
+                        "name": "v1.0.0",
+                        "target": {"__typename": "Commit", "oid": "ddd", "committedDate": "2023-01-01T10:00:00Z"}
+                    },
+                    {
+                        "id": "REF_3",
+                        "name": "v0.1.0",
+                        "target": {"__typename": "Tag", "oid": "eee", "target": {"__typename": "Tree", "oid": "fff"}}
+                    }
+                ]
+            }
+        }}}"#;
+        let server = MockServer::start(move |request| {
+            match (
+                request.path.as_str(),
+                request.body.contains(r#""refs":"c1""#),
+            ) {
+                ("/graphql", false) => MockResponse::new(200, first),
+                ("/graphql", true) => MockResponse::new(200, second),
+                _ => MockResponse::new(404, "Not Found"),
+            }
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_token("secret".to_string())
+            .with_graphql(true);
+        let versions = host.versions().await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+        assert!(requests[1].body.contains(r#""withReleases":false"#));
+
+        let names = versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["2.1.0-rc1", "2.0.0", "1.0.0", "0.1.0"]);
+        assert!(versions[0].prerelease);
+        assert_eq!(versions[0].source_ref.as_deref(), Some("ccc"));
+        assert_eq!(versions[0].release_notes.as_deref(), Some("Testing"));
+        assert!(versions[0].downloads.iter().any(|d| d
+            .url
+            .ends_with("gh_2.1.0-rc1_linux_amd64.tar.gz")
+            && d.size == Some(2048)
+            && d.checksum.as_deref() == Some("sha256:abcd")));
+        // Annotated tags are peeled to their commit, which only dates tag-only
+        // versions when asked to, like on the REST path
+        assert_eq!(versions[1].source_ref.as_deref(), Some("bbb"));
+        assert_eq!(versions[1].released_at, None);
+        assert_eq!(
+            versions[2].downloads[0].url,
+            server
+                .url
+                .join("repos/cli/cli/tarball/refs/tags/v1.0.0")
+                .unwrap()
+                .as_str()
+        );
+        // A tag of a tree is kept, but has no commit
+        assert_eq!(versions[3].source_ref, None);
+        assert!(!versions[3].downloads.is_empty());
+
+        let versions = host.with_tag_dates(true).versions().await.unwrap();
+        assert_eq!(
+            versions[1].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-06-01T10:00:00+00:00")
+        );
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    /// Tests that releases with more assets than one query returns are fetched through REST
+    #[tokio::test]
+    async fn test_graphql_truncated_assets() {
+        let page = r#"{"data": {"repository": {
+            "refs": {"pageInfo": {"hasNextPage": false, "endCursor": null}, "nodes": []},
+            "releases": {
+                "pageInfo": {"hasNextPage": false, "endCursor": null},
+                "nodes": [{
+                    "tagName": "v2.0.0", "name": null, "description": null,
+                    "isDraft": false, "isPrerelease": false,
+                    "createdAt": "2024-07-01T10:00:00Z", "publishedAt": "2024-07-01T10:00:00Z",
+                    "tagCommit": {"oid": "ccc"},
+                    "releaseAssets": {
+                        "pageInfo": {"hasNextPage": true, "endCursor": "a1"},
+                        "nodes": [{
+                            "name": "gh_2.0.0_linux_amd64.tar.gz",
+                            "contentType": "application/gzip", "size": 2048, "downloadCount": 3,
+                            "createdAt": "2024-07-01T10:00:00Z", "updatedAt": "2024-07-01T10:00:00Z",
+                            "downloadUrl": "https://github.com/cli/cli/releases/download/v2.0.0/gh_2.0.0_linux_amd64.tar.gz"
+                        }]
+                    }
+                }]
+            }
+        }}}"#;
+        let full = serde_json::to_string(&release(
+            "v2.0.0",
+            &["gh_2.0.0_linux_amd64.tar.gz", "gh_2.0.0_linux_arm64.tar.gz"],
+        ))
+        .unwrap();
+        let server = MockServer::start(move |request| match request.path.as_str() {
+            "/graphql" => MockResponse::new(200, page),
+            "/repos/cli/cli/releases/tags/v2.0.0" => MockResponse::new(200, full.clone()),
+            _ => MockResponse::new(404, "Not Found"),
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let versions = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_token("secret".to_string())
+            .with_graphql(true)
+            .versions()
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+        assert!(server.requests()[0].body.contains(r#""assets":100"#));
+        let binaries = versions[0]
+            .downloads
+            .iter()
+            .filter(|d| d.url.contains("/releases/download/"))
+            .count();
+        assert_eq!(binaries, 2);
+        assert_eq!(versions[0].source_ref.as_deref(), Some("ccc"));
+    }
+
+    /// Tests that releases are skipped when disabled or known to be missing
+    #[tokio::test]
+    async fn test_skip_releases() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/tags?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+            (
+                "/repos/cli/cli/releases?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+        ])
+        .await;
+        let releases = || {
+            server
+                .requests()
+                .iter()
+                .filter(|r| r.path.starts_with("/repos/cli/cli/releases"))
+                .count()
+        };
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        host.versions().await.unwrap();
+        host.versions().await.unwrap();
+        assert_eq!(releases(), 1);
+    }
+
+    /// Tests that only the endpoints of the configured version source are requested
+    #[tokio::test]
+    async fn test_version_source() {
+        let server = &MockServer::start(|_| MockResponse::new(200, "[]")).await;
+        let url = &Url::parse("https://github.com/cli/cli").unwrap();
+        let paths = |source: VersionSource| async move {
+            let before = server.requests().len();
+            let host = GithubHost::from_url(url)
+                .unwrap()
+                .with_api_base(server.url.clone())
+                .with_source(source);
+            host.versions().await.unwrap();
+            host.versions_stream()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            server.requests()[before..]
+                .iter()
+                .map(|r| r.path.clone())
+                .collect::<BTreeSet<_>>()
+        };
+
+        assert_eq!(
+            paths(VersionSource::TagsOnly).await,
+            BTreeSet::from(["/repos/cli/cli/tags?per_page=100".to_string()])
+        );
+        assert_eq!(
+            paths(VersionSource::ReleasesOnly).await,
+            BTreeSet::from(["/repos/cli/cli/releases?per_page=100".to_string()])
+        );
+        assert_eq!(paths(VersionSource::Both).await.len(), 2);
+    }
+
+    /// Tests that tags and releases are requested at the same time
+    #[tokio::test]
+    async fn test_concurrent_listing() {
+        let server =
+            MockServer::start(|_| MockResponse::new(200, "[]").delay(Duration::from_millis(100)))
+                .await;
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .versions()
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(server.max_in_flight(), 2);
+    }
+
+    /// Tests that commits of release-only versions are resolved a few at a time, newest first
+    #[tokio::test]
+    async fn test_source_ref_budget() {
+        let releases = (0..60)
+            .map(|i| release(&format!("v1.{i}.0"), &[]))
+            .collect::<Vec<_>>();
+        let body = serde_json::to_string(&releases).unwrap();
+        let server = MockServer::start(move |request| {
+            if request.path.starts_with("/repos/cli/cli/releases") {
+                return MockResponse::new(200, body.clone());
+            }
+            if request.path.starts_with("/repos/cli/cli/git/ref/tags/") {
+                return MockResponse::new(200, r#"{"object": {"sha": "f00d", "type": "commit"}}"#)
+                    .delay(Duration::from_millis(20));
+            }
+            MockResponse::new(200, "[]")
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let versions = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_concurrency(3)
+            .versions()
+            .await
+            .unwrap();
+
+        let lookups = server
+            .requests()
+            .into_iter()
+            .filter(|r| r.path.contains("/git/ref/tags/"))
+            .count();
+        assert_eq!(lookups, MAX_SOURCE_REFS);
+        assert_eq!(server.max_in_flight(), 3);
+        assert_eq!(versions[0].version, "1.59.0");
+        assert_eq!(versions[0].source_ref.as_deref(), Some("f00d"));
+        assert_eq!(versions[59].source_ref, None);
+    }
+
+    /// Tests that the token is sent as a bearer token and rejections are reported
+    #[tokio::test]
+    async fn test_token() {
+        let server = MockServer::start(|request| match request.header("authorization") {
+            Some("Bearer good") => MockResponse::new(200, "[]"),
+            _ => MockResponse::new(401, r#"{"message": "Bad credentials"}"#),
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+
+        let good = host.with_token("good".to_string());
+        assert!(good.versions().await.unwrap().is_empty());
+
+        let expired = good.with_token("expired".to_string());
+        assert!(matches!(
+            expired.versions().await,
+            Err(HostError::InvalidToken { .. })
+        ));
+    }
+
+    /// Tests that links away from the API are neither followed nor sent the token
+    #[tokio::test]
+    async fn test_foreign_next_link() {
+        let elsewhere = MockServer::start(|_| MockResponse::new(200, "[]")).await;
+        let next = format!("<{}tags?page=2>; rel=\"next\"", elsewhere.url.as_str());
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/tags?per_page=100",
+                MockResponse::new(200, "[]").header("Link", next),
+            ),
+            (
+                "/repos/cli/cli/releases?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_token("secret".to_string());
+        assert!(host.versions().await.unwrap().is_empty());
+        assert!(elsewhere.requests().is_empty());
+
+        let foreign = elsewhere.url.join("tags").unwrap();
+        let request = host.gh_client(foreign.as_str()).unwrap().build().unwrap();
+        assert!(request.headers().get("authorization").is_none());
+        let own = server.url.join("repos/cli/cli/tags").unwrap();
+        let request = host.gh_client(own.as_str()).unwrap().build().unwrap();
+        assert!(request.headers().get("authorization").is_some());
+    }
+
+    /// Tests that `GITHUB_API_URL` values are used as the API base when valid
+    #[test]
+    fn test_parse_api_base() {
+        assert_eq!(
+            parse_api_base("https://github.example.com/api/v3")
+                .unwrap()
+                .as_str(),
+            "https://github.example.com/api/v3/"
+        );
+        assert_eq!(
+            parse_api_base("https://api.github.com").unwrap().as_str(),
+            "https://api.github.com/"
+        );
+        assert!(parse_api_base("").is_none());
+        assert!(parse_api_base("not a url").is_none());
+    }
+
+    /// Tests that `GITHUB_API_URL` is only used for repositories on the `GITHUB_SERVER_URL` instance
+    #[test]
+    fn test_env_api_base() {
+        let github = Url::parse("https://github.com/cli/cli").unwrap();
+        let enterprise = Url::parse("https://ghe.example.com/team/tool").unwrap();
+        let ghe_api = Some("https://ghe.example.com/api/v3");
+
+        // A workflow on an Enterprise instance looking up a github.com repository
+        assert!(env_api_base(&github, Some("https://ghe.example.com"), ghe_api).is_none());
+        assert_eq!(
+            env_api_base(&enterprise, Some("https://ghe.example.com"), ghe_api)
+                .unwrap()
+                .as_str(),
+            "https://ghe.example.com/api/v3/"
+        );
+
+        // Without a server, the API is assumed to be github.com's
+        assert!(env_api_base(&enterprise, None, ghe_api).is_none());
+        let api = Some("https://api.github.com");
+        assert!(env_api_base(&github, None, api).is_some());
+        let url = Url::parse("https://api.github.com/repos/cli/cli").unwrap();
+        assert!(env_api_base(&url, Some("https://github.com"), api).is_some());
+        assert!(env_api_base(&github, Some("https://github.com"), None).is_none());
+    }
+
+    /// Tests that asset filters keep one platform artifact plus the source tarball
+    #[tokio::test]
+    async fn test_asset_filter() {
+        let releases = [release(
+            "v2.63.2",
+            &[
+                "gh_2.63.2_linux_amd64.tar.gz",
+                "gh_2.63.2_linux_arm64.tar.gz",
+                "gh_2.63.2_macOS_amd64.zip",
+                "gh_2.63.2_windows_amd64.msi",
+            ],
+        )];
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/tags?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+            (
+                "/repos/cli/cli/releases?per_page=100",
+                MockResponse::new(200, serde_json::to_string(&releases).unwrap()),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let versions = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_asset_filter(AssetFilter::glob("*_linux_amd64.tar.gz"))
+            .versions()
+            .await
+            .unwrap();
+        let urls = versions[0]
+            .downloads
+            .iter()
+            .map(|d| d.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec![
+                "https://api.github.com/repos/cli/cli/tarball/refs/tags/v2.63.2",
+                "https://github.com/cli/cli/releases/download/v2.63.2/gh_2.63.2_linux_amd64.tar.gz",
+            ]
+        );
+
+        let filter = AssetFilter::regex(Regex::new(r"_(linux|macOS)_amd64\.").unwrap());
+        assert!(filter.matches("https://example.com/gh_2.63.2_macOS_amd64.zip"));
+        assert!(!filter.matches("https://example.com/gh_2.63.2_linux_arm64.tar.gz"));
+        assert!(!AssetFilter::glob("gh_?.tar.gz").matches("https://example.com/gh_12.tar.gz"));
+
+        // Debug symbols are dropped even when matched, unless asked for
+        let pdb = "https://example.com/gh_2.63.2_windows_amd64.pdb";
+        assert!(!AssetFilter::glob("*_windows_amd64.*").matches(pdb));
+        assert!(AssetFilter::glob("*_windows_amd64.*")
+            .keep_debug(true)
+            .matches(pdb));
+        assert!(AssetFilter::glob("*_windows_amd64.*")
+            .matches("https://example.com/gh_2.63.2_windows_amd64.msi"));
+    }
+
+    /// Tests that an exhausted rate limit is reported with its reset time
+    #[tokio::test]
+    async fn test_rate_limited() {
+        let server = MockServer::with_routes(vec![(
+            "/repos/cli/cli/tags?per_page=100",
+            MockResponse::new(403, r#"{"message": "API rate limit exceeded"}"#)
+                .header("X-RateLimit-Remaining", "0")
+                .header("X-RateLimit-Reset", "1733220000"),
+        )])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        match host.versions().await {
+            Err(HostError::RateLimited {
+                reset_at,
+                remaining,
+            }) => {
+                assert_eq!(reset_at.timestamp(), 1733220000);
+                assert_eq!(remaining, 0);
+            }
+            other => panic!("expected a rate limit error, got {other:?}"),
+        }
+    }
+
+    /// Tests that missing and forbidden repositories are reported by their URL
+    #[tokio::test]
+    async fn test_repository_errors() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/tags?per_page=100",
+                MockResponse::new(403, r#"{"message": "Resource not accessible"}"#)
+                    .header("X-RateLimit-Remaining", "4999"),
+            ),
+            (
+                "/repos/cli/clii/tags?per_page=100",
+                MockResponse::new(404, r#"{"message": "Not Found"}"#),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/clii").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let error = host.versions().await.unwrap_err();
+        assert!(matches!(error, HostError::NotFound { .. }));
+        assert_eq!(error.to_string(), "not found: https://github.com/cli/clii");
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let error = host.versions().await.unwrap_err();
+        assert!(matches!(error, HostError::Unauthorized { .. }));
+        assert_eq!(
+            error.to_string(),
+            "access denied: https://github.com/cli/cli"
+        );
+    }
+
+    /// Tests that a 404 is reported as [`HostError::NotFound`] with the requested URL
+    #[tokio::test]
+    async fn test_not_found() {
+        let server = MockServer::with_routes(vec![]).await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let missing = server.url.join("repos/cli/cli/releases/latest").unwrap();
+        let response = reqwest::get(missing.clone()).await.unwrap();
+        assert!(matches!(
+            host.check_response(response),
+            Err(HostError::NotFound { url }) if url == missing.as_str()
+        ));
+        assert_eq!(host.fetch_release("v9.9.9").await.unwrap(), None);
+    }
+
+    /// Tests that a renamed repository is followed and its new location reported
+    #[tokio::test]
+    async fn test_moved() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/old/name/tags?per_page=100",
+                MockResponse::new(301, "").header("Location", "/repositories/42/tags?per_page=100"),
+            ),
+            (
+                "/repositories/42/tags?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+            (
+                "/repos/old/name/releases?per_page=100",
+                MockResponse::new(301, "")
+                    .header("Location", "/repositories/42/releases?per_page=100"),
+            ),
+            (
+                "/repositories/42/releases?per_page=100",
+                MockResponse::new(200, "[]"),
+            ),
+            (
+                "/repositories/42",
+                MockResponse::new(200, r#"{"name": "new-name", "owner": {"login": "new"}}"#),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/old/name").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        assert_eq!(host.canonical_location(), None);
+        assert!(host.versions().await.unwrap().is_empty());
+        assert_eq!(
+            host.canonical_location(),
+            Some(("new".to_string(), "new-name".to_string()))
+        );
+        assert_eq!(
+            host.moved_to().map(String::from).as_deref(),
+            Some("https://github.com/new/new-name")
+        );
+    }
+
+    /// Tests asset classification against the assets of a cli/cli release
+    #[test]
+    fn test_classify_assets() {
+        let assets = [
+            ("gh_2.63.2_checksums.txt", AssetKind::Checksum),
+            ("gh_2.63.2_linux_386.deb", AssetKind::Installer),
+            ("gh_2.63.2_linux_amd64.rpm", AssetKind::Installer),
+            ("gh_2.63.2_linux_amd64.tar.gz", AssetKind::Binary),
+            ("gh_2.63.2_linux_arm64.tar.gz.asc", AssetKind::Signature),
+            ("gh_2.63.2_macOS_universal.pkg", AssetKind::Installer),
+            ("gh_2.63.2_windows_amd64.msi", AssetKind::Installer),
+            ("gh_2.63.2_windows_amd64.zip", AssetKind::Binary),
+            ("gh_2.63.2.spdx.json", AssetKind::Sbom),
+            ("gh_2.63.2_windows_amd64.pdb", AssetKind::Debug),
+            ("gh_2.63.2_linux_amd64.debug", AssetKind::Debug),
+            ("app.min.js.map", AssetKind::Debug),
+            ("SHA256SUMS", AssetKind::Checksum),
+            ("gh-2.63.2.tar.gz", AssetKind::SourceArchive),
+            ("gh-x86_64.AppImage", AssetKind::Installer),
+            ("gh-2.63.2-py3-none-any.whl", AssetKind::Binary),
+        ];
+
+        let names = assets.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let cli = release("v2.63.2", &names);
+        for (asset, (name, kind)) in cli.assets.iter().zip(assets) {
+            assert_eq!(
+                classify_asset(&asset.name, Some(&asset.content_type)),
+                kind,
+                "asset: {name}"
+            );
+        }
+
+        let mut signature = release("v2.63.2", &["gh_2.63.2_checksums.txt.gpg"])
+            .assets
+            .remove(0);
+        signature.content_type = "application/pgp-signature".to_string();
+        assert_eq!(
+            classify_asset(&signature.name, Some(&signature.content_type)),
+            AssetKind::Signature
+        );
+    }
+
+    /// Tests that signatures and checksums are linked to the assets they verify
+    #[test]
+    fn test_companions() {
+        let releases = [release(
+            "v1.2.3",
+            &[
+                "foo-1.2.3.tar.gz",
+                "foo-1.2.3.tar.gz.asc",
+                "foo-1.2.3.tar.gz.sha256",
+                "foo-1.2.3-linux-amd64.tar.gz",
+                "SHA256SUMS",
+                "SHA256SUMS.minisig",
+            ],
+        )];
+        let versions = collect_versions(&[], &releases, &TagFilter::default());
+        let download = "https://github.com/cli/cli/releases/download/v1.2.3/";
+        let verifies = |name: &str| {
+            let asset = versions[0]
+                .downloads
+                .iter()
+                .find(|d| d.url == format!("{download}{name}"))
+                .unwrap();
+            asset
+                .verifies
+                .iter()
+                .map(|url| url.trim_start_matches(download))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(verifies("foo-1.2.3.tar.gz.asc"), vec!["foo-1.2.3.tar.gz"]);
+        assert_eq!(
+            verifies("foo-1.2.3.tar.gz.sha256"),
+            vec!["foo-1.2.3.tar.gz"]
+        );
+        assert_eq!(verifies("SHA256SUMS.minisig"), vec!["SHA256SUMS"]);
+        let mut covered = verifies("SHA256SUMS");
+        covered.sort();
+        assert_eq!(
+            covered,
+            vec!["foo-1.2.3-linux-amd64.tar.gz", "foo-1.2.3.tar.gz"]
+        );
+        assert!(verifies("foo-1.2.3.tar.gz").is_empty());
+    }
+
+    /// Tests that invalid or missing publish dates are left unset
+    #[test]
+    fn test_lenient_dates() {
+        let parse = |published_at: &str| {
+            let json = format!(
+                r#"{{"tag_name": "v1", "name": "", "body": "", "assets": [],
+                    "tarball_url": "", "zipball_url": "",
+                    "created_at": "2024-12-03T10:00:00Z", "published_at": {published_at}}}"#
+            );
+            serde_json::from_str::<GithubReleaseResponse>(&json)
+                .unwrap()
+                .published_at
+        };
+        assert_eq!(
+            parse(r#""2024-12-04T08:30:00Z""#),
+            Some("2024-12-04T08:30:00Z".parse().unwrap())
+        );
+        assert_eq!(parse("null"), None);
+        assert_eq!(parse(r#""yesterday""#), None);
+    }
+
+    /// Tests that tag-only versions take the date of their commit
+    #[tokio::test]
+    async fn test_commit_dates() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/repos/cli/cli/commits/aaa" => MockResponse::new(
+                200,
+                r#"{"commit": {"committer": {"name": "A", "date": "2024-11-20T12:00:00Z"}}}"#,
+            ),
+            "/repos/cli/cli/commits/bbb" => MockResponse::new(500, "oops"),
+            _ => MockResponse::new(200, "[]"),
+        })
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone())
+            .with_tag_dates(true);
+
+        let mut dated = tag("v1.0.0");
+        dated.commit.url = server
+            .url
+            .join("repos/cli/cli/commits/aaa")
+            .unwrap()
+            .to_string();
+        let mut broken = tag("v0.9.0");
+        broken.commit.url = server
+            .url
+            .join("repos/cli/cli/commits/bbb")
+            .unwrap()
+            .to_string();
+        let tags = [dated, broken];
+
+        let mut versions = collect_versions(&tags, &[], &TagFilter::default());
+        host.fill_commit_dates(&tags, &mut versions, MAX_TAG_DATES)
+            .await;
+        let dates = versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.released_at.map(|d| d.to_rfc3339())))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dates,
+            vec![
+                ("v0.9.0", None),
+                ("v1.0.0", Some("2024-11-20T12:00:00+00:00".to_string())),
+            ]
+        );
+
+        // Only the newest versions within the budget are dated
+        let before = server.requests().len();
+        let mut versions = collect_versions(&tags, &[], &TagFilter::default());
+        host.fill_commit_dates(&tags, &mut versions, 1).await;
+        assert!(versions[1].released_at.is_some());
+        assert_eq!(server.requests().len(), before + 1);
+
+        // Nothing is requested unless enabled
+        let host = host.with_tag_dates(false);
+        let mut versions = collect_versions(&tags, &[], &TagFilter::default());
+        host.fill_commit_dates(&tags, &mut versions, MAX_TAG_DATES)
+            .await;
+        assert!(versions.iter().all(|v| v.released_at.is_none()));
+        assert_eq!(server.requests().len(), before + 1);
+    }
+
+    /// Tests that asset digests become checksums, and manifests fill in the rest when enabled
+    #[tokio::test]
+    async fn test_checksums() {
+        let manifest = "\
+            aaaa000000000000000000000000000000000000000000000000000000000000  gh_linux.tar.gz
+            bbbb000000000000000000000000000000000000000000000000000000000000  gh_macos.zip";
+        let server = MockServer::with_routes(vec![(
+            "/releases/download/v2.63.2/SHA256SUMS",
+            MockResponse::new(200, manifest),
+        )])
+        .await;
+
+        let mut digested = release(
+            "v2.63.2",
+            &["gh_linux.tar.gz", "gh_macos.zip", "SHA256SUMS"],
+        );
+        digested.assets[0].digest = Some("sha256:ffff".to_string());
+        digested.assets[2].browser_download_url = server
+            .url
+            .join("releases/download/v2.63.2/SHA256SUMS")
+            .unwrap()
+            .to_string();
+        let releases = [digested];
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let checksums = |host: &GithubHost, manifests| {
+            let mut found = collect_versions(&[], &releases, &TagFilter::default());
+            host.attach_manifest_checksums(&manifests, &mut found);
+            found[0]
+                .downloads
+                .iter()
+                .filter(|d| d.url.contains("/gh_"))
+                .map(|d| d.checksum.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let host = GithubHost::from_url(&url).unwrap();
+        let manifests = host.fetch_checksum_manifests(&releases).await;
+        assert!(manifests.is_empty());
+        assert_eq!(
+            checksums(&host, manifests),
+            vec![Some("sha256:ffff".to_string()), None]
+        );
+
+        let host = host.with_checksum_manifests(true);
+        let manifests = host.fetch_checksum_manifests(&releases).await;
+        assert_eq!(
+            checksums(&host, manifests),
+            vec![
+                Some("sha256:ffff".to_string()),
+                Some(
+                    "sha256:bbbb000000000000000000000000000000000000000000000000000000000000"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    /// Tests that versions carry the commit of their tag, peeling annotated tags
+    #[tokio::test]
+    async fn test_source_refs() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/repos/cli/cli/git/ref/tags/v2.0.0",
+                MockResponse::new(200, r#"{"object": {"sha": "7a9", "type": "tag"}}"#),
+            ),
+            (
+                "/repos/cli/cli/git/tags/7a9",
+                MockResponse::new(200, r#"{"object": {"sha": "c0ffee", "type": "commit"}}"#),
+            ),
+            (
+                "/repos/cli/cli/git/ref/tags/v3.0.0",
+                MockResponse::new(200, r#"{"object": {"sha": "beef", "type": "commit"}}"#),
+            ),
+            (
+                "/repos/cli/cli/git/ref/tags/v0.1.0",
+                MockResponse::new(200, r#"{"object": {"sha": "8b1", "type": "tag"}}"#),
+            ),
+            (
+                "/repos/cli/cli/git/tags/8b1",
+                MockResponse::new(200, r#"{"object": {"sha": "7ee", "type": "tree"}}"#),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+
+        // Lightweight and annotated tags resolve to commits, a tag of a tree does not
+        let tags = [tag("v1.0.0")];
+        let releases = [
+            release("v2.0.0", &[]),
+            release("v3.0.0", &[]),
+            release("v0.1.0", &[]),
+        ];
+        let mut versions = collect_versions(&tags, &releases, &TagFilter::default());
+        host.fill_source_refs(&releases, &mut versions).await;
+        let refs = versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.source_ref.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            refs,
+            vec![
+                ("v0.1.0", None),
+                ("v1.0.0", Some("c3b1f2e9d8a7")),
+                ("v2.0.0", Some("c0ffee")),
+                ("v3.0.0", Some("beef")),
+            ]
+        );
+    }
+
+    /// Tests that release flags win over the tag name heuristic
+    #[test]
+    fn test_prerelease() {
+        let mut flagged = release("v2.0.0", &[]);
+        flagged.prerelease = true;
+        let tags = [tag("v2.0.0"), tag("v1.9.0"), tag("v2.1.0-rc1")];
+
+        let versions = collect_versions(&tags, &[flagged], &TagFilter::default());
+        let flags = versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.prerelease))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flags,
+            vec![("v1.9.0", false), ("v2.0.0", true), ("v2.1.0-rc1", true)]
+        );
+    }
+
+    /// Tests that draft releases are dropped when fetching releases
+    #[tokio::test]
+    async fn test_skip_drafts() {
+        let release = |tag: &str, draft: bool| {
+            serde_json::json!({
+                "tag_name": tag, "name": tag, "body": "", "assets": [],
+                "tarball_url": "", "zipball_url": "",
+                "created_at": "2024-12-03T10:00:00Z", "published_at": null,
+                "draft": draft, "prerelease": false
+            })
+        };
+        let body = serde_json::json!([release("v3.0.0", true), release("v2.0.0", false)]);
+        let server = MockServer::with_routes(vec![(
+            "/repos/cli/cli/releases?per_page=100",
+            MockResponse::new(200, body.to_string()),
+        )])
+        .await;
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(server.url.clone());
+        let releases = host.fetch_releases().await.unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].tag_name, "v2.0.0");
+    }
+
+    #[test]
+    fn test_enterprise() {
+        let url = Url::parse("https://github.example.com/team/tool/releases").unwrap();
+        let host = GithubHost::from_enterprise_url(&url).unwrap();
+        assert_eq!(
+            host.repo_api_url("tags").unwrap(),
+            "https://github.example.com/api/v3/repos/team/tool/tags"
+        );
+
+        let host = GithubHost::from_url(&url)
+            .unwrap()
+            .with_api_base(Url::parse("https://github.example.com/api/v3").unwrap());
+        assert_eq!(host.api_base.as_str(), "https://github.example.com/api/v3/");
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = GithubHost::from_url(&url).unwrap();
+        assert_eq!(
+            host.repo_api_url("releases").unwrap(),
+            "https://api.github.com/repos/cli/cli/releases"
+        );
+    }
+
+    /// Tests that a warm cache revalidates both lists without downloading them again
+    #[tokio::test]
+    async fn test_etag_cache() {
+        let server = MockServer::start(|request| {
+            let (etag, body) = match request.path.as_str() {
+                "/repos/cli/cli/tags?per_page=100" => ("\"tags-1\"", "[]"),
+                "/repos/cli/cli/releases?per_page=100" => ("\"releases-1\"", "[]"),
+                _ => return MockResponse::new(404, "Not Found"),
+            };
+            if request.header("if-none-match") == Some(etag) {
+                MockResponse::new(304, "")
+            } else {
+                MockResponse::new(200, body).header("ETag", etag)
+            }
+        })
+        .await;
+
+        // Separate hosts, as one host remembers that there are no releases
+        let cache = Arc::new(MemoryCache::default());
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        let host = || {
+            GithubHost::from_url(&url)
+                .unwrap()
+                .with_api_base(server.url.clone())
+                .with_cache(cache.clone())
+        };
+
+        host().versions().await.unwrap();
+        host().versions().await.unwrap();
+
+        let warm = server.requests().split_off(2);
+        assert_eq!(warm.len(), 2);
+        assert!(warm.iter().all(|r| r.header("if-none-match").is_some()));
+    }
 }
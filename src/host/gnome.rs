@@ -2,15 +2,50 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use std::{collections::HashMap, vec};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    vec,
+};
 
 use async_trait::async_trait;
-use serde::Deserialize;
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 use url::Url;
 
-use crate::{AssetKind, VersionMetadata, VersionedAsset};
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
 
-use super::{Host, HostError};
+use super::{
+    check_status, http_client, listing, retry::RetryPolicy, sort_versions, with_trailing_slash,
+    Host, HostError, DEFAULT_USER_AGENT,
+};
+
+/// Directory of GNOME's download server holding one directory per project
+const SOURCES_URL: &str = "https://download.gnome.org/sources/";
+
+/// The `cache.json` format understood by [`GnomeHost`]
+const CACHE_FORMAT: u64 = 4;
+
+/// Number of release notes, checksum files or listings downloaded at once by default
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Largest part of a release notes or checksum file that is kept, in bytes
+const MAX_FILE_SIZE: usize = 64 * 1024;
+
+/// Modules known to release development series with an odd minor number (i.e. glib 2.81.x)
+const ODD_MINOR_MODULES: &[&str] = &[
+    "gdk-pixbuf",
+    "gjs",
+    "glib",
+    "glib-networking",
+    "gobject-introspection",
+    "gtk",
+    "gtk+",
+    "libsoup",
+    "NetworkManager",
+    "pango",
+];
 
 /// A Host implementation for accessing GNOME project releases
 ///
@@ -22,6 +57,35 @@ pub struct GnomeHost {
 
     /// The complete URL to the project's download location
     pub url: Url,
+
+    /// The `sources/` directory requests and downloads are resolved against,
+    /// pointing at `download.gnome.org` unless a mirror is configured
+    pub base_url: Url,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Whether the `.news` file of each version is downloaded as its release notes
+    pub release_notes: bool,
+
+    /// Whether the `.sha256sum` file of each version is downloaded to fill in checksums
+    pub checksum_manifests: bool,
+
+    /// Whether release dates are read from the listing of each series directory
+    pub dates: bool,
+
+    /// Number of release notes, checksum files or listings downloaded at once
+    pub concurrency: usize,
+
+    /// Names the project was released under before being renamed (i.e.
+    /// "gnome-todo" for "endeavour"), whose releases are included as well
+    pub former_names: Vec<String>,
+
+    /// Whether versions with an odd minor number are development releases
+    pub odd_minor_development: bool,
 }
 
 /// Metadata about a specific version of a GNOME project
@@ -38,7 +102,7 @@ pub struct GnomeHost {
 /// * `tarxz` - Optional URL to .tar.xz archive
 /// * `targz` - Optional URL to .tar.gz archive
 /// * `tarbz2` - Optional URL to .tar.bz2 archive
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct GnomeCacheComponentFile {
     pub news: Option<String>,
     pub changes: Option<String>,
@@ -52,111 +116,966 @@ pub struct GnomeCacheComponentFile {
 }
 
 /// Maps version strings to component files
-pub type GnomeCacheComponent = HashMap<String, GnomeCacheComponentFile>;
+pub type GnomeCacheComponent = BTreeMap<String, GnomeCacheComponentFile>;
 
 /// Maps component names to their versions
-pub type GnomeCacheVersion = HashMap<String, GnomeCacheComponent>;
+pub type GnomeCacheVersion = BTreeMap<String, GnomeCacheComponent>;
 
 /// Response format for the GNOME cache.json API
-#[derive(Deserialize)]
+///
+/// Sent as a four element array, which is also the shape it is serialized to.
+/// Maps are ordered so serializing the response gives the same output each time.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct GnomeCacheResponse {
+    /// The layout version of the cache, currently 4
     pub format: u8,
+    /// The files of each version, per component
     pub components: GnomeCacheVersion,
-    pub versions: HashMap<String, Vec<String>>,
-    pub meta: HashMap<String, Vec<String>>,
+    /// The versions of each component, oldest first
+    pub versions: BTreeMap<String, Vec<String>>,
+    /// Additional metadata, unused by [`GnomeHost`]
+    pub meta: BTreeMap<String, Vec<String>>,
+}
+
+impl Serialize for GnomeCacheResponse {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.format, &self.components, &self.versions, &self.meta).serialize(serializer)
+    }
 }
 
 impl GnomeHost {
     /// Creates a new GnomeHost instance from a URL
+    ///
+    /// Both `download.gnome.org/sources/<project>/...` URLs and the older
+    /// `ftp.gnome.org/pub/GNOME/sources/<project>/...` layout are accepted, as
+    /// are mirrors serving the tree below a `gnome` directory (i.e.
+    /// `mirror.example.org/pub/gnome/sources/<project>/...`). Requests and
+    /// downloads always go to `download.gnome.org` regardless.
     pub fn from_url(url: &Url) -> Result<Self, HostError> {
-        let parts = url
+        let mut path = url
             .path_segments()
-            .ok_or(HostError::InvalidUrl("invalid URL format".into()))?;
-        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
-        let source_path = path.first().unwrap_or(&(""));
-        if *source_path != "sources" {
-            return Err(HostError::InvalidUrl("invalid URL format".into()));
-        }
-        let project = path
-            .get(1)
-            .ok_or(HostError::InvalidUrl("invalid URL format".into()))?;
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?
+            .filter(|p| !p.is_empty());
+        let project = match path.next() {
+            Some("sources") => path.next(),
+            _ => mirror_project(url),
+        }
+        .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
         Ok(Self {
             project: project.to_string(),
             url: url.clone(),
+            base_url: Url::parse(SOURCES_URL).expect("valid sources URL"),
+            retry: RetryPolicy::default(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            release_notes: false,
+            checksum_manifests: false,
+            dates: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            former_names: vec![],
+            odd_minor_development: ODD_MINOR_MODULES.contains(&project),
         })
     }
-}
 
-#[async_trait]
-impl Host for GnomeHost {
-    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
-        let uri = format!(
-            "https://download.gnome.org/sources/{}/cache.json",
-            self.project
-        );
-        let response = reqwest::get(&uri)
+    /// Maps the releases of this project's components in a cache response to version metadata
+    ///
+    /// The response may list other components, so only those named after the
+    /// project or one of its former names are used, ignoring case. A version
+    /// listed under several of them becomes one entry with all downloads.
+    fn collect(&self, response: GnomeCacheResponse) -> Result<Vec<VersionMetadata>, HostError> {
+        let components = response
+            .components
+            .iter()
+            .filter_map(|(name, component)| {
+                let directory = std::iter::once(&self.project)
+                    .chain(&self.former_names)
+                    .find(|n| n.eq_ignore_ascii_case(name))?;
+                Some((name, directory, component))
+            })
+            .collect::<Vec<_>>();
+        if components.is_empty() {
+            return Err(HostError::parse_error(
+                self.file_url(&self.project, "cache.json"),
+                format!("component {} missing from cache data", self.project),
+            ));
+        }
+
+        let mut versions_set = vec![];
+        for (version, files, directory) in components
+            .iter()
+            .flat_map(|(_, directory, c)| c.iter().map(move |(v, f)| (v, f, directory.as_str())))
+        {
+            let file_url = |file: &str| self.file_url(directory, file);
+            let mut downloads = [&files.tarxz, &files.targz, &files.tarbz2]
+                .into_iter()
+                .flatten()
+                .map(|file| {
+                    VersionedAsset::new(
+                        file_url(file),
+                        listing::classify_release_file(directory, file),
+                    )
+                })
+                .collect::<Vec<_>>();
+            if let Some(sums) = &files.sha256sum {
+                let verifies = downloads.iter().map(|d| d.url.clone()).collect();
+                downloads.push(VersionedAsset {
+                    verifies,
+                    ..VersionedAsset::new(file_url(sums), AssetKind::Checksum)
+                });
+            }
+            if let Some(news) = &files.news {
+                downloads.push(VersionedAsset::new(file_url(news), AssetKind::ReleaseNotes));
+            }
+
+            let version = VersionMetadata {
+                version: version.clone(),
+                downloads,
+                release_notes: None,
+                released_at: None,
+                prerelease: is_unstable(version, self.odd_minor_development),
+                source_ref: None,
+                tag: None,
+            };
+            versions_set.push(version);
+        }
+
+        // GNOME's own ordering, oldest first, knows where `47.alpha` and `47.rc`
+        // belong. It is only used when it covers every version found.
+        let mut versions = sort_versions(versions_set);
+        if let [(name, _, _)] = components[..] {
+            let order = response
+                .versions
+                .get(name)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let position =
+                |version: &VersionMetadata| order.iter().position(|v| *v == version.version);
+            if versions.iter().all(|v| position(v).is_some()) {
+                versions.sort_by_cached_key(|v| Reverse(position(v)));
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Returns the download URL of `file`, relative to a project directory
+    fn file_url(&self, directory: &str, file: &str) -> String {
+        format!("{}{}/{}", self.base_url, directory, file)
+    }
+
+    /// Downloads the release notes linked from each version
+    ///
+    /// A failed download only leaves the notes of that version unset.
+    async fn fill_release_notes(&self, versions: &mut [VersionMetadata]) {
+        for (index, notes) in self.fetch_files(versions, AssetKind::ReleaseNotes).await {
+            match notes {
+                Ok(notes) => versions[index].release_notes = Some(notes),
+                Err(e) => warn!("Failed to fetch release notes: {}", e),
+            }
+        }
+    }
+
+    /// Downloads the checksum file of each version and attaches its digests
+    ///
+    /// The file may also list formats that were not emitted as downloads,
+    /// those entries are ignored. A failed download leaves the checksums unset.
+    async fn fill_checksums(&self, versions: &mut [VersionMetadata]) {
+        for (index, sums) in self.fetch_files(versions, AssetKind::Checksum).await {
+            match sums {
+                Ok(sums) => listing::attach_checksums(
+                    std::slice::from_mut(&mut versions[index]),
+                    &listing::parse_checksums(&sums, "sha256"),
+                ),
+                Err(e) => warn!("Failed to fetch checksums: {}", e),
+            }
+        }
+    }
+
+    /// Fetches the parsed `cache.json` of the project, i.e. to select versions differently
+    ///
+    /// Fails with [`HostError::Unsupported`] if GNOME changed the cache format.
+    pub async fn raw_cache(&self) -> Result<GnomeCacheResponse, HostError> {
+        self.fetch_cache(&self.project).await
+    }
+
+    /// Fetches and parses the `cache.json` of the project directory `directory`
+    async fn fetch_cache(&self, directory: &str) -> Result<GnomeCacheResponse, HostError> {
+        let uri = format!("{}{}/cache.json", self.base_url, directory);
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
             .await
             .map_err(|e| HostError::ApiRequest {
                 context: "failed to fetch cache data".into(),
                 source: e,
-            })?
-            .json::<GnomeCacheResponse>()
+            })
+            .and_then(check_status)?
+            .json::<serde_json::Value>()
             .await
             .map_err(|e| HostError::ApiResponse {
-                context: "failed to parse cache data".into(),
+                context: format!("failed to parse cache data from {uri}"),
                 source: e,
             })?;
+        parse_cache(&uri, response)
+    }
 
-        let mut versions_set = vec![];
+    /// Reads the release date of each download from its directory listing
+    ///
+    /// Every series directory (i.e. `2.82/`) is listed once. A version is
+    /// dated by its earliest download, and a listing that fails to download
+    /// only leaves the dates of that series unset.
+    async fn fill_dates(&self, versions: &mut [VersionMetadata]) {
+        let directories = versions
+            .iter()
+            .flat_map(|v| &v.downloads)
+            .filter_map(|d| d.url.rsplit_once('/').map(|(dir, _)| format!("{dir}/")))
+            .collect::<BTreeSet<_>>();
+
+        let client = http_client(&self.user_agent);
+        let mut requests = vec![];
+        for directory in &directories {
+            let client = &client;
+            requests.push(async move {
+                let listing = self
+                    .retry
+                    .send(client.get(directory))
+                    .await
+                    .map_err(|e| HostError::ApiRequest {
+                        context: format!("failed to fetch {directory}"),
+                        source: e,
+                    })
+                    .and_then(check_status);
+                let body = match listing {
+                    Ok(response) => response.text().await.map_err(|e| HostError::ApiResponse {
+                        context: format!("failed to read {directory}"),
+                        source: e,
+                    }),
+                    Err(e) => Err(e),
+                };
+                (directory, body)
+            });
+        }
+        let listings = stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
 
-        for (_component, versions) in response.components.iter() {
-            for (version, files) in versions.iter() {
-                let mut downloads = vec![];
-                if let Some(tarxz) = files.tarxz.as_ref() {
-                    downloads.push(VersionedAsset {
-                        url: format!(
-                            "https://download.gnome.org/sources/{}/{}",
-                            self.project, tarxz
-                        ),
-                        kind: AssetKind::Release,
-                        released_at: None,
-                        updated_at: None,
-                    });
+        let mut dates = BTreeMap::new();
+        for (directory, body) in listings {
+            match body {
+                Ok(body) => {
+                    for (file, date) in listing::parse_listing_dates(&body) {
+                        dates.insert(format!("{directory}{file}"), date);
+                    }
                 }
-                if let Some(targz) = files.targz.as_ref() {
-                    downloads.push(VersionedAsset {
-                        url: format!(
-                            "https://download.gnome.org/sources/{}/{}",
-                            self.project, targz
-                        ),
-                        kind: AssetKind::Release,
-                        released_at: None,
-                        updated_at: None,
-                    });
+                Err(e) => warn!("Failed to fetch release dates: {}", e),
+            }
+        }
+
+        for version in versions.iter_mut() {
+            for download in version.downloads.iter_mut() {
+                download.released_at = dates.get(&download.url).copied();
+            }
+            version.released_at = version.downloads.iter().filter_map(|d| d.released_at).min();
+        }
+    }
+
+    /// Downloads the first asset of `kind` of each version, keyed by version index
+    ///
+    /// Files are fetched [`GnomeHost::concurrency`] at a time and cut off after 64 KiB.
+    async fn fetch_files(
+        &self,
+        versions: &[VersionMetadata],
+        kind: AssetKind,
+    ) -> Vec<(usize, Result<String, HostError>)> {
+        let client = http_client(&self.user_agent);
+        let mut requests = vec![];
+        for (index, version) in versions.iter().enumerate() {
+            let Some(file) = version.downloads.iter().find(|d| d.kind == kind) else {
+                continue;
+            };
+            let client = &client;
+            requests.push(async move { (index, self.fetch_file(client, &file.url).await) });
+        }
+        stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Downloads the text at `url`, keeping at most [`MAX_FILE_SIZE`] bytes
+    async fn fetch_file(&self, client: &reqwest::Client, url: &str) -> Result<String, HostError> {
+        let mut response = self
+            .retry
+            .send(client.get(url))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: format!("failed to fetch {url}"),
+                source: e,
+            })
+            .and_then(check_status)?;
+        let mut body = vec![];
+        while body.len() < MAX_FILE_SIZE {
+            let chunk = response.chunk().await.map_err(|e| HostError::ApiResponse {
+                context: format!("failed to read {url}"),
+                source: e,
+            })?;
+            match chunk {
+                Some(chunk) => body.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        body.truncate(MAX_FILE_SIZE);
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Downloads the `.news` file of each version as its release notes
+    ///
+    /// Costs one request per version. Without it, the file is still listed
+    /// among the downloads as [`AssetKind::ReleaseNotes`].
+    pub fn with_release_notes(self, release_notes: bool) -> Self {
+        Self {
+            release_notes,
+            ..self
+        }
+    }
+
+    /// Downloads the `.sha256sum` file of each version to fill in checksums
+    ///
+    /// Costs one request per version. Without it, the file is still listed
+    /// among the downloads as [`AssetKind::Checksum`].
+    pub fn with_checksum_manifests(self, checksum_manifests: bool) -> Self {
+        Self {
+            checksum_manifests,
+            ..self
+        }
+    }
+
+    /// Fetches from a mirror of the `sources/` directory instead of `download.gnome.org`
+    ///
+    /// Download URLs of the returned versions point at the mirror as well.
+    pub fn with_base_url(self, base_url: Url) -> Self {
+        Self {
+            base_url: with_trailing_slash(base_url),
+            ..self
+        }
+    }
+
+    /// Includes the releases published under former names of the project
+    ///
+    /// Each name costs one more `cache.json` request. Versions released under
+    /// several names are merged into one entry.
+    pub fn with_former_names(self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            former_names: names.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Reads release dates from the listing of each series directory
+    ///
+    /// `cache.json` carries no dates, so this costs one request per series.
+    pub fn with_dates(self, dates: bool) -> Self {
+        Self { dates, ..self }
+    }
+
+    /// Sets the number of release notes, checksum files or listings downloaded at once
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Treats versions with an odd minor number (i.e. `2.81.0`) as development releases
+    ///
+    /// Only some modules number their development series this way, so it is
+    /// enabled by default for a known list of them (glib, gtk, pango, ...)
+    /// and must be turned on for others. Versions of the 40+ scheme
+    /// (i.e. `47.1`) are never affected.
+    pub fn with_odd_minor_development(self, odd_minor_development: bool) -> Self {
+        Self {
+            odd_minor_development,
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+}
+
+/// Returns the project of a URL below a `gnome/sources/` directory, as mirrors lay it out
+pub(crate) fn mirror_project(url: &Url) -> Option<&str> {
+    let path = url
+        .path_segments()?
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<&str>>();
+    let gnome = path
+        .windows(2)
+        .position(|w| w[0].eq_ignore_ascii_case("gnome") && w[1] == "sources")?;
+    path.get(gnome + 2).copied()
+}
+
+/// Parses a `cache.json` body after checking that its format is understood
+///
+/// The format is the first element of the cache array, checked before the rest
+/// is parsed so a changed layout is reported as such.
+fn parse_cache(uri: &str, body: serde_json::Value) -> Result<GnomeCacheResponse, HostError> {
+    let format = body.get(0).and_then(serde_json::Value::as_u64);
+    if format != Some(CACHE_FORMAT) {
+        let found = format.map_or_else(|| "no".to_string(), |f| f.to_string());
+        return Err(HostError::Unsupported(format!(
+            "found {found} cache.json format, expected format {CACHE_FORMAT}"
+        )));
+    }
+    serde_json::from_value(body)
+        .map_err(|e| HostError::parse_error(uri, format!("invalid cache data: {e}")))
+}
+
+/// Whether a GNOME version is a development release
+///
+/// Versions marked `alpha`, `beta` or `rc` (i.e. `47.rc`) always are. With
+/// `odd_minor`, versions of the old scheme (major below 40, i.e. `2.81.0`)
+/// are too when their minor number is odd, while the 40+ scheme uses every
+/// minor number for stable point releases (i.e. `47.1`).
+fn is_unstable(version: &str, odd_minor: bool) -> bool {
+    if is_prerelease(version) {
+        return true;
+    }
+    if !odd_minor {
+        return false;
+    }
+    let mut parts = version.split('.').map(|p| p.parse::<u64>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor))) => major < 40 && minor % 2 == 1,
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl Host for GnomeHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let response = self.fetch_cache(&self.project).await?;
+        let mut versions = self.collect(response)?;
+        if !self.former_names.is_empty() {
+            for name in &self.former_names {
+                match self.fetch_cache(name).await {
+                    Ok(response) => versions.extend(self.collect(response)?),
+                    Err(e) => warn!("Failed to fetch releases of {}: {}", name, e),
                 }
-                if let Some(tarbz2) = files.tarbz2.as_ref() {
-                    downloads.push(VersionedAsset {
-                        url: format!(
-                            "https://download.gnome.org/sources/{}/{}",
-                            self.project, tarbz2
-                        ),
-                        kind: AssetKind::Release,
-                        released_at: None,
-                        updated_at: None,
-                    });
+            }
+            versions = sort_versions(versions);
+        }
+
+        if self.release_notes {
+            self.fill_release_notes(&mut versions).await;
+        }
+        if self.checksum_manifests {
+            self.fill_checksums(&mut versions).await;
+        }
+        if self.dates {
+            self.fill_dates(&mut versions).await;
+        }
+        Ok(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::host::{
+        mock::{MockResponse, MockServer},
+        plain::PlainHost,
+    };
+
+    #[test]
+    fn test_from_url() {
+        for url in [
+            "https://download.gnome.org/sources/NetworkManager/1.50/NetworkManager-1.50.0.tar.xz",
+            "https://ftp.gnome.org/pub/GNOME/sources/NetworkManager/1.50/NetworkManager-1.50.0.tar.xz",
+            "https://download.gnome.org/sources/NetworkManager/",
+            "https://download.gnome.org/sources/NetworkManager/1.50/",
+            "https://mirror.example.org/pub/gnome/sources/NetworkManager/1.50/",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert_eq!(GnomeHost::from_url(&url).unwrap().project, "NetworkManager");
+        }
+
+        // Mirrors are only used to find the project
+        let url = Url::parse("https://mirror.example.org/pub/GNOME/sources/glib/2.82/").unwrap();
+        let host = GnomeHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "glib");
+        assert_eq!(
+            host.file_url(&host.project, "2.82/glib-2.82.0.tar.xz"),
+            "https://download.gnome.org/sources/glib/2.82/glib-2.82.0.tar.xz"
+        );
+    }
+
+    /// Tests that only the component of the requested project becomes versions
+    #[test]
+    fn test_component() {
+        let response = r#"[
+            4,
+            {
+                "networkmanager": {
+                    "1.50.0": {
+                        "tar.xz": "1.50/NetworkManager-1.50.0.tar.xz",
+                        "news": "1.50/NetworkManager-1.50.0.news",
+                        "sha256sum": "1.50/NetworkManager-1.50.0.sha256sum"
+                    },
+                    "1.48.10": {"tar.xz": "1.48/NetworkManager-1.48.10.tar.xz"}
+                },
+                "NetworkManager-bundle": {
+                    "9.9.9": {"tar.xz": "9.9/NetworkManager-bundle-9.9.9.tar.xz"}
                 }
+            },
+            {},
+            {}
+        ]"#;
+        let response: GnomeCacheResponse = serde_json::from_str(response).unwrap();
+        let url = Url::parse("https://download.gnome.org/sources/NetworkManager/").unwrap();
+        let versions = GnomeHost::from_url(&url)
+            .unwrap()
+            .collect(response)
+            .unwrap();
+        let names = versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["1.50.0", "1.48.10"]);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://download.gnome.org/sources/NetworkManager/1.50/NetworkManager-1.50.0.tar.xz"
+        );
+        assert_eq!(versions[0].downloads[1].kind, AssetKind::Checksum);
+        assert_eq!(
+            versions[0].downloads[1].verifies,
+            vec![versions[0].downloads[0].url.clone()]
+        );
+        assert_eq!(versions[0].downloads[2].kind, AssetKind::ReleaseNotes);
+        assert_eq!(versions[1].downloads.len(), 1);
 
-                // TODO: fetch release notes and released_at from additional metadata
-                let version = VersionMetadata {
-                    version: version.clone(),
-                    downloads,
-                    release_notes: None,
-                    released_at: None,
-                };
-                versions_set.push(version);
-            }
+        let response: GnomeCacheResponse =
+            serde_json::from_str(r#"[4, {"glib": {}}, {}, {}]"#).unwrap();
+        assert!(matches!(
+            GnomeHost::from_url(&url).unwrap().collect(response),
+            Err(HostError::ParseError { url, .. }) if url.ends_with("/NetworkManager/cache.json")
+        ));
+    }
+
+    /// Tests that release notes are downloaded and cut off at the size cap
+    #[tokio::test]
+    async fn test_release_notes() {
+        let long = "x".repeat(MAX_FILE_SIZE + 100);
+        let server = MockServer::with_routes(vec![
+            ("/1.50.news", MockResponse::new(200, "* Fixed a crash")),
+            ("/1.48.news", MockResponse::new(200, &long)),
+        ])
+        .await;
+
+        let url = Url::parse("https://download.gnome.org/sources/NetworkManager/").unwrap();
+        let host = GnomeHost::from_url(&url).unwrap().with_release_notes(true);
+        let mut versions = ["1.50", "1.48", "1.46"]
+            .into_iter()
+            .map(|version| {
+                let news = server.url.join(&format!("{version}.news")).unwrap();
+                VersionMetadata::new(
+                    version.to_string(),
+                    vec![VersionedAsset::new(news, AssetKind::ReleaseNotes)],
+                )
+            })
+            .collect::<Vec<_>>();
+        host.fill_release_notes(&mut versions).await;
+
+        assert_eq!(
+            versions[0].release_notes.as_deref(),
+            Some("* Fixed a crash")
+        );
+        assert_eq!(
+            versions[1].release_notes.as_ref().map(|n| n.len()),
+            Some(MAX_FILE_SIZE)
+        );
+        // A missing file leaves the notes unset
+        assert_eq!(versions[2].release_notes, None);
+    }
+
+    /// Tests that checksum files fill in digests of the emitted tarballs only
+    #[tokio::test]
+    async fn test_checksums() {
+        let sums = "\
+            1f2e  foo-1.0.tar.xz\n\
+            3c4d  foo-1.0.tar.bz2\n";
+        let server =
+            MockServer::with_routes(vec![("/foo-1.0.sha256sum", MockResponse::new(200, sums))])
+                .await;
+
+        let url = Url::parse("https://download.gnome.org/sources/foo/").unwrap();
+        let host = GnomeHost::from_url(&url)
+            .unwrap()
+            .with_checksum_manifests(true);
+        let mut versions = vec![VersionMetadata::new(
+            "1.0",
+            vec![
+                VersionedAsset::new("https://example.com/foo-1.0.tar.xz", AssetKind::Release),
+                VersionedAsset::new(
+                    server.url.join("foo-1.0.sha256sum").unwrap(),
+                    AssetKind::Checksum,
+                ),
+            ],
+        )];
+        host.fill_checksums(&mut versions).await;
+
+        assert_eq!(
+            versions[0].downloads[0].checksum.as_deref(),
+            Some("sha256:1f2e")
+        );
+        assert_eq!(versions[0].downloads[1].checksum, None);
+    }
+
+    /// Tests that a configured mirror is used for the cache and the downloads
+    #[tokio::test]
+    async fn test_base_url() {
+        let cache = r#"[4, {"foo": {"1.0": {"tar.xz": "1.0/foo-1.0.tar.xz"}}}, {}, {}]"#;
+        let server = MockServer::with_routes(vec![(
+            "/mirror/foo/cache.json",
+            MockResponse::new(200, cache),
+        )])
+        .await;
+
+        let url = Url::parse("https://download.gnome.org/sources/foo/").unwrap();
+        let versions = GnomeHost::from_url(&url)
+            .unwrap()
+            .with_retry(RetryPolicy::none())
+            .with_base_url(server.url.join("mirror").unwrap())
+            .versions()
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests()[0].path, "/mirror/foo/cache.json");
+        assert_eq!(
+            versions[0].downloads[0].url,
+            server
+                .url
+                .join("mirror/foo/1.0/foo-1.0.tar.xz")
+                .unwrap()
+                .as_str()
+        );
+    }
+
+    /// Tests that a module missing from the mirror is reported as not found
+    #[tokio::test]
+    async fn test_not_found() {
+        let server = MockServer::with_routes(vec![]).await;
+
+        let url = Url::parse("https://download.gnome.org/sources/foo/").unwrap();
+        let error = GnomeHost::from_url(&url)
+            .unwrap()
+            .with_retry(RetryPolicy::none())
+            .with_base_url(server.url.clone())
+            .versions()
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            HostError::NotFound { url } if url.ends_with("/foo/cache.json")
+        ));
+    }
+
+    /// Tests that the cache lists every series, where one series directory does not
+    #[tokio::test]
+    async fn test_all_series() {
+        let cache = r#"[
+            4,
+            {
+                "NetworkManager": {
+                    "1.50.0": {"tar.xz": "1.50/NetworkManager-1.50.0.tar.xz"},
+                    "1.48.10": {"tar.xz": "1.48/NetworkManager-1.48.10.tar.xz"},
+                    "1.46.6": {"tar.xz": "1.46/NetworkManager-1.46.6.tar.xz"}
+                }
+            },
+            {},
+            {}
+        ]"#;
+        let server = MockServer::with_routes(vec![
+            (
+                "/sources/NetworkManager/cache.json",
+                MockResponse::new(200, cache),
+            ),
+            (
+                "/sources/NetworkManager/1.50/",
+                MockResponse::new(
+                    200,
+                    r#"<a href="NetworkManager-1.50.0.tar.xz">NetworkManager-1.50.0.tar.xz</a>"#,
+                ),
+            ),
+        ])
+        .await;
+
+        let url = server
+            .url
+            .join("sources/NetworkManager/1.50/NetworkManager-1.50.0.tar.xz")
+            .unwrap();
+        let series = PlainHost::from_url(&url).versions().await.unwrap();
+        let all = GnomeHost::from_url(&url)
+            .unwrap()
+            .with_retry(RetryPolicy::none())
+            .with_base_url(server.url.join("sources").unwrap())
+            .versions()
+            .await
+            .unwrap();
+
+        assert_eq!(series.len(), 1);
+        assert_eq!(all.len(), 3);
+        assert!(all.iter().any(|v| v.version == series[0].version));
+
+        // Paths outside the sources tree are left to a plain directory listing
+        let url = Url::parse("https://ftp.gnome.org/pub/GNOME/teams/releng/").unwrap();
+        assert!(GnomeHost::from_url(&url).is_err());
+        assert!(crate::host::from_url(&url).is_ok());
+    }
+
+    #[test]
+    fn test_unstable() {
+        // glib style: odd minor numbers are development series
+        assert!(is_unstable("2.81.0", true));
+        assert!(is_unstable("1.51.2", true));
+        assert!(!is_unstable("2.80.4", true));
+        assert!(!is_unstable("1.50.0", true));
+
+        // gnome-shell style: named prereleases, then stable point releases
+        for odd_minor in [true, false] {
+            assert!(is_unstable("47.alpha", odd_minor));
+            assert!(is_unstable("47.beta", odd_minor));
+            assert!(is_unstable("47.rc", odd_minor));
+            assert!(!is_unstable("47.0", odd_minor));
+            assert!(!is_unstable("47.1", odd_minor));
+        }
+
+        // libxml2 style: every minor number is a stable series
+        let host = |project: &str| {
+            let url = format!("https://download.gnome.org/sources/{project}/");
+            GnomeHost::from_url(&Url::parse(&url).unwrap()).unwrap()
+        };
+        let response = |project: &str| {
+            let body = format!(
+                r#"[4, {{"{project}": {{
+                    "2.13.5": {{"tar.xz": "2.13/{project}-2.13.5.tar.xz"}},
+                    "2.14.0": {{"tar.xz": "2.14/{project}-2.14.0.tar.xz"}}
+                }}}}, {{}}, {{}}]"#
+            );
+            serde_json::from_str::<GnomeCacheResponse>(&body).unwrap()
+        };
+        let libxml2 = host("libxml2").collect(response("libxml2")).unwrap();
+        assert_eq!(libxml2.len(), 2);
+        assert!(libxml2.iter().all(|v| !v.prerelease));
+
+        // Known odd/even modules flag the odd series, others can opt in
+        let glib = host("glib").collect(response("glib")).unwrap();
+        assert!(glib.iter().any(|v| v.version == "2.13.5" && v.prerelease));
+        let opted = host("libxml2")
+            .with_odd_minor_development(true)
+            .collect(response("libxml2"))
+            .unwrap();
+        assert!(opted.iter().any(|v| v.version == "2.13.5" && v.prerelease));
+    }
+
+    /// Tests that unknown formats are rejected and GNOME's version order is kept
+    #[test]
+    fn test_parse_cache() {
+        for body in [r#"[5, {}, {}, {}]"#, r#"{"format": 4}"#] {
+            let body = serde_json::from_str(body).unwrap();
+            assert!(matches!(
+                parse_cache("cache.json", body),
+                Err(HostError::Unsupported(message)) if message.contains("expected format 4")
+            ));
         }
 
-        Ok(versions_set)
+        let body = serde_json::from_str(
+            r#"[
+                4,
+                {
+                    "gnome-shell": {
+                        "47.0": {"tar.xz": "47/gnome-shell-47.0.tar.xz"},
+                        "47.rc": {"tar.xz": "47/gnome-shell-47.rc.tar.xz"},
+                        "47.beta": {"tar.xz": "47/gnome-shell-47.beta.tar.xz"},
+                        "47.alpha": {"tar.xz": "47/gnome-shell-47.alpha.tar.xz"}
+                    }
+                },
+                {"gnome-shell": ["47.alpha", "47.beta", "47.rc", "47.0"]},
+                {}
+            ]"#,
+        )
+        .unwrap();
+        let url = Url::parse("https://download.gnome.org/sources/gnome-shell/").unwrap();
+        let versions = GnomeHost::from_url(&url)
+            .unwrap()
+            .collect(parse_cache("cache.json", body).unwrap())
+            .unwrap();
+        let names = versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["47.0", "47.rc", "47.beta", "47.alpha"]);
+
+        // The generic ordering the CLI sorts every host's versions by agrees
+        let mut sorted = versions.clone();
+        crate::versioning::sort_versions(&mut sorted);
+        assert_eq!(sorted, versions);
+    }
+
+    /// Tests that release dates are read from the series directory listings
+    #[tokio::test]
+    async fn test_dates() {
+        let listing = r#"<pre><a href="../">../</a>
+<a href="foo-1.1.tar.xz">foo-1.1.tar.xz</a>    17-Sep-2024 10:03    5533212
+<a href="foo-1.1.news">foo-1.1.news</a>      17-Sep-2024 10:01    1024
+</pre>"#;
+        let server =
+            MockServer::with_routes(vec![("/foo/1.1/", MockResponse::new(200, listing))]).await;
+
+        let url = Url::parse("https://download.gnome.org/sources/foo/").unwrap();
+        let host = GnomeHost::from_url(&url).unwrap().with_dates(true);
+        let file = |path: &str, kind| VersionedAsset::new(server.url.join(path).unwrap(), kind);
+        let mut versions = vec![
+            VersionMetadata::new(
+                "1.1",
+                vec![
+                    file("foo/1.1/foo-1.1.tar.xz", AssetKind::Release),
+                    file("foo/1.1/foo-1.1.news", AssetKind::ReleaseNotes),
+                ],
+            ),
+            VersionMetadata::new(
+                "1.0",
+                vec![file("foo/1.0/foo-1.0.tar.xz", AssetKind::Release)],
+            ),
+        ];
+        host.fill_dates(&mut versions).await;
+
+        assert_eq!(
+            versions[0].downloads[0]
+                .released_at
+                .map(|d| d.to_rfc3339())
+                .as_deref(),
+            Some("2024-09-17T10:03:00+00:00")
+        );
+        assert_eq!(
+            versions[0].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-09-17T10:01:00+00:00")
+        );
+        // A missing listing leaves the dates of its series unset
+        assert_eq!(versions[1].released_at, None);
+    }
+
+    /// Tests that releases under a former name are merged by version
+    #[test]
+    fn test_former_names() {
+        let response = r#"[
+            4,
+            {
+                "endeavour": {
+                    "42.0": {"tar.xz": "42/endeavour-42.0.tar.xz"},
+                    "41.0": {"tar.xz": "41/endeavour-41.0.tar.xz"}
+                },
+                "gnome-todo": {
+                    "41.0": {"tar.xz": "41/gnome-todo-41.0.tar.xz"},
+                    "40.0": {"tar.xz": "40/gnome-todo-40.0.tar.xz"}
+                }
+            },
+            {},
+            {}
+        ]"#;
+        let response: GnomeCacheResponse = serde_json::from_str(response).unwrap();
+        let url = Url::parse("https://download.gnome.org/sources/endeavour/").unwrap();
+        let versions = GnomeHost::from_url(&url)
+            .unwrap()
+            .with_former_names(["gnome-todo"])
+            .collect(response)
+            .unwrap();
+
+        let names = versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["42.0", "41.0", "40.0"]);
+        let urls = versions[1]
+            .downloads
+            .iter()
+            .map(|d| d.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec![
+                "https://download.gnome.org/sources/endeavour/41/endeavour-41.0.tar.xz",
+                "https://download.gnome.org/sources/gnome-todo/41/gnome-todo-41.0.tar.xz",
+            ]
+        );
+    }
+
+    /// Tests that no more files than configured are downloaded at once
+    #[tokio::test]
+    async fn test_concurrency() {
+        let server = MockServer::start(|_| {
+            MockResponse::new(200, "* Fixed a crash").delay(Duration::from_millis(100))
+        })
+        .await;
+
+        let url = Url::parse("https://download.gnome.org/sources/foo/").unwrap();
+        let host = GnomeHost::from_url(&url).unwrap().with_concurrency(2);
+        let mut versions = (0..6)
+            .map(|minor| {
+                let news = server.url.join(&format!("foo-1.{minor}.news")).unwrap();
+                VersionMetadata::new(
+                    format!("1.{minor}"),
+                    vec![VersionedAsset::new(news, AssetKind::ReleaseNotes)],
+                )
+            })
+            .collect::<Vec<_>>();
+        host.fill_release_notes(&mut versions).await;
+
+        assert!(versions.iter().all(|v| v.release_notes.is_some()));
+        assert_eq!(server.requests().len(), 6);
+        assert_eq!(server.max_in_flight(), 2);
+    }
+
+    /// Tests that the raw cache is served typed and serializes deterministically
+    #[tokio::test]
+    async fn test_raw_cache() {
+        let cache = r#"[
+            4,
+            {"foo": {"1.10": {"tar.xz": "1.10/foo-1.10.tar.xz"}, "1.9": {"tar.xz": "1.9/foo-1.9.tar.xz"}}},
+            {"foo": ["1.9", "1.10"]},
+            {}
+        ]"#;
+        let server =
+            MockServer::with_routes(vec![("/foo/cache.json", MockResponse::new(200, cache))]).await;
+        let url = Url::parse("https://download.gnome.org/sources/foo/").unwrap();
+        let cache = GnomeHost::from_url(&url)
+            .unwrap()
+            .with_base_url(server.url.clone())
+            .raw_cache()
+            .await
+            .unwrap();
+
+        assert_eq!(cache.format, 4);
+        assert_eq!(cache.versions["foo"], vec!["1.9", "1.10"]);
+        let json = serde_json::to_string(&cache).unwrap();
+        assert!(json.starts_with("[4,"));
+        assert!(json.find("\"1.10\"").unwrap() < json.find("\"1.9\"").unwrap());
+
+        // The serialized form is the upstream layout, so it parses like a fetched cache
+        let parsed = parse_cache("cache.json", serde_json::to_value(&cache).unwrap()).unwrap();
+        assert_eq!(parsed, cache);
     }
 }
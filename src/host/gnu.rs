@@ -0,0 +1,141 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use tracing::debug;
+use url::Url;
+
+use crate::VersionMetadata;
+
+use super::{listing, retry::RetryPolicy, sort_versions, Host, HostError, DEFAULT_USER_AGENT};
+
+/// A Host implementation for projects published on the GNU FTP server
+///
+/// GNU projects live under `https://ftp.gnu.org/gnu/<project>/`, either as a
+/// flat directory of tarballs or with one subdirectory per version. Each tarball
+/// is usually accompanied by a detached `.sig` signature.
+pub struct GnuHost {
+    /// The project name (i.e. "nano", "gcc", etc)
+    pub project: String,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+impl GnuHost {
+    /// Creates a new GnuHost instance from a URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        if path.first() != Some(&"gnu") {
+            return Err(HostError::invalid_url(url, "invalid URL format"));
+        }
+        let project = path
+            .get(1)
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        Ok(Self {
+            project: project.to_string(),
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Returns the URL of the project's top level directory
+    fn project_url(&self) -> Result<Url, HostError> {
+        let uri = format!(
+            "{}://{}/gnu/{}/",
+            self.url.scheme(),
+            self.url.host_str().unwrap_or("ftp.gnu.org"),
+            self.project
+        );
+        Url::parse(&uri).map_err(|e| HostError::invalid_url(&uri, e))
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for GnuHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries =
+            listing::list_directory_as(&self.project_url()?, &self.user_agent, &self.retry).await?;
+
+        let mut files = vec![];
+        for entry in entries {
+            if listing::is_version_dir(&self.project, &entry) {
+                debug!("Descending into version directory: {}", entry.url);
+                files.extend(
+                    listing::list_directory_as(&entry.url, &self.user_agent, &self.retry).await?,
+                );
+            } else if !entry.is_dir {
+                files.push(entry);
+            }
+        }
+
+        let versions = listing::collect_versions(&self.project, &files);
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetKind;
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://ftp.gnu.org/gnu/nano/nano-8.3.tar.xz").unwrap();
+        let host = GnuHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "nano");
+        assert_eq!(
+            host.project_url().unwrap().as_str(),
+            "https://ftp.gnu.org/gnu/nano/"
+        );
+
+        let url = Url::parse("https://ftp.gnu.org/pub/nano/").unwrap();
+        assert!(GnuHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_collect_signatures() {
+        let base = Url::parse("https://ftp.gnu.org/gnu/gcc/gcc-13.2.0/").unwrap();
+        let body = r#"
+            <a href="gcc-13.2.0.tar.xz">gcc-13.2.0.tar.xz</a>
+            <a href="gcc-13.2.0.tar.xz.sig">gcc-13.2.0.tar.xz.sig</a>
+            <a href="gcc-13.1.0.tar.gz.sig">gcc-13.1.0.tar.gz.sig</a>
+        "#;
+        let entries = listing::parse_listing(&base, body).unwrap();
+        let versions = listing::collect_versions("gcc", &entries);
+
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "13.2.0");
+        let kinds = versions[0]
+            .downloads
+            .iter()
+            .map(|d| d.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec![AssetKind::Release, AssetKind::Signature]);
+    }
+}
@@ -0,0 +1,515 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{stream, StreamExt};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::{
+    versioning::{is_prerelease, Version},
+    AssetKind, VersionMetadata, VersionedAsset,
+};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, with_trailing_slash, Host,
+    HostError, DEFAULT_USER_AGENT,
+};
+
+/// The public Go module proxy
+const PROXY_URL: &str = "https://proxy.golang.org/";
+
+/// Number of `.info` requests made at once by default
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Maximum number of versions whose timestamp is looked up, newest first
+const MAX_INFO_LOOKUPS: usize = 50;
+
+/// A Host implementation for Go modules, resolved through the module proxy
+///
+/// Versions come from `@v/list` and the timestamps of the newest
+/// [`MAX_INFO_LOOKUPS`] from one `@v/<version>.info` request each. A
+/// `pkg.go.dev` URL may name a package below the module root (i.e.
+/// `golang.org/x/text/unicode/norm`), in which case the shorter paths are tried
+/// until the proxy knows one as a module.
+pub struct GoModuleHost {
+    /// The module or package path (i.e. "github.com/spf13/cobra")
+    pub module: String,
+
+    /// The complete URL the host was created from
+    pub url: Url,
+
+    /// The module proxy requests are sent to
+    pub proxy: Url,
+
+    /// Number of `.info` requests made at once
+    pub concurrency: usize,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// Response format for the proxy `.info` and `@latest` endpoints
+#[derive(Deserialize, Debug)]
+pub struct GoModuleInfo {
+    /// The canonical version (i.e. "v1.8.0")
+    #[serde(rename = "Version")]
+    pub version: String,
+    /// When the version was committed or tagged
+    #[serde(rename = "Time")]
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Escapes a module path or version for the proxy protocol
+///
+/// Uppercase letters become `!` followed by the lowercase letter, so paths stay
+/// unique on case-insensitive file systems (i.e. `github.com/BurntSushi/toml`
+/// becomes `github.com/!burnt!sushi/toml`).
+pub fn escape_path(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            escaped.push('!');
+            escaped.push(c.to_ascii_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Returns `path` followed by each of its parents that can still be a module path
+///
+/// Module paths have at least two elements (i.e. `example.com/tool`).
+fn module_candidates(path: &str) -> impl Iterator<Item = &str> {
+    let parents = std::iter::successors(Some(path), |path| {
+        path.rsplit_once('/').map(|(parent, _)| parent)
+    });
+    std::iter::once(path).chain(parents.skip(1).filter(|parent| parent.contains('/')))
+}
+
+/// Reverses [`escape_path`]
+fn unescape_path(path: &str) -> String {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '!' => unescaped.extend(chars.next().map(|c| c.to_ascii_uppercase())),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+impl GoModuleHost {
+    /// Creates a new GoModuleHost instance from a URL
+    ///
+    /// Both `https://pkg.go.dev/<module>[@<version>]` package pages and
+    /// `https://proxy.golang.org/<escaped module>/@v/...` URLs are accepted.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+
+        let module = match url.host_str() {
+            Some("proxy.golang.org") => {
+                let end = path
+                    .iter()
+                    .position(|p| p.starts_with('@'))
+                    .unwrap_or(path.len());
+                unescape_path(&path[..end].join("/"))
+            }
+            _ => {
+                let module = path.join("/");
+                match module.split_once('@') {
+                    Some((module, _)) => module.to_string(),
+                    None => module,
+                }
+            }
+        };
+        if module.is_empty() {
+            return Err(HostError::parse_error(url, "missing module path in URL"));
+        }
+
+        debug!("Created GoModuleHost for {}", module);
+        Ok(Self {
+            module,
+            url: url.clone(),
+            proxy: Url::parse(PROXY_URL).expect("valid proxy URL"),
+            concurrency: DEFAULT_CONCURRENCY,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Sends requests to another module proxy instead of `proxy.golang.org`
+    pub fn with_proxy(self, proxy: Url) -> Self {
+        Self {
+            proxy: with_trailing_slash(proxy),
+            ..self
+        }
+    }
+
+    /// Sets the number of `.info` requests made at once
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Returns the proxy URL of `endpoint` below the `@v` or `@latest` path of `module`
+    fn endpoint(&self, module: &str, endpoint: &str) -> String {
+        format!("{}{}/{}", self.proxy, escape_path(module), endpoint)
+    }
+
+    /// Sends a GET request to a proxy endpoint of `module`
+    ///
+    /// The proxy answers `410 Gone` for unknown modules, reported as [`HostError::NotFound`].
+    async fn get(&self, module: &str, endpoint: &str) -> Result<reqwest::Response, HostError> {
+        let uri = self.endpoint(module, endpoint);
+        debug!("Fetching: {}", uri);
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: format!("failed to fetch {uri}"),
+                source: e,
+            })?;
+        if response.status() == reqwest::StatusCode::GONE {
+            return Err(HostError::NotFound { url: uri });
+        }
+        check_status(response)
+    }
+
+    /// Fetches the version and timestamp served at `endpoint`
+    async fn fetch_info(&self, module: &str, endpoint: &str) -> Result<GoModuleInfo, HostError> {
+        self.get(module, endpoint)
+            .await?
+            .json::<GoModuleInfo>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse {}", self.endpoint(module, endpoint)),
+                source: e,
+            })
+    }
+
+    /// Fetches the tagged versions of `module` listed by the proxy
+    async fn fetch_list(&self, module: &str) -> Result<Vec<String>, HostError> {
+        let body = self
+            .get(module, "@v/list")
+            .await?
+            .text()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to read {}", self.endpoint(module, "@v/list")),
+                source: e,
+            })?;
+        Ok(body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Finds the module containing [`GoModuleHost::module`] and its versions
+    ///
+    /// The path itself is tried first, then each parent path the proxy reports
+    /// as unknown, until one is a module.
+    async fn resolve(&self) -> Result<(&str, Vec<VersionMetadata>), HostError> {
+        let mut error = None;
+        for module in module_candidates(&self.module) {
+            let found = match self.fetch_list(module).await {
+                Ok(list) if list.is_empty() => self.fetch_latest(module).await,
+                Ok(list) => Ok(self.fetch_dates(module, &list).await),
+                Err(e) => Err(e),
+            };
+            match found {
+                Err(e @ HostError::NotFound { .. }) => {
+                    error.get_or_insert(e);
+                }
+                found => return found.map(|versions| (module, versions)),
+            }
+        }
+        Err(error.unwrap_or_else(|| HostError::NotFound {
+            url: self.endpoint(&self.module, "@v/list"),
+        }))
+    }
+
+    /// Fetches the `@latest` pseudo-version of a module without tags
+    async fn fetch_latest(&self, module: &str) -> Result<Vec<VersionMetadata>, HostError> {
+        let latest = self.fetch_info(module, "@latest").await?;
+        Ok(vec![self.collect(module, &latest.version, latest.time)])
+    }
+
+    /// Maps the listed versions of `module` to version metadata with their timestamps
+    ///
+    /// Only the newest [`MAX_INFO_LOOKUPS`] versions are looked up, and a
+    /// failed lookup only leaves the timestamp unset.
+    async fn fetch_dates(&self, module: &str, list: &[String]) -> Vec<VersionMetadata> {
+        let mut newest = list.iter().collect::<Vec<_>>();
+        newest.sort_by_cached_key(|version| std::cmp::Reverse(Version::from(version.as_str())));
+        if newest.len() > MAX_INFO_LOOKUPS {
+            debug!("Dating {} of {} versions", MAX_INFO_LOOKUPS, newest.len());
+        }
+
+        let mut requests = vec![];
+        for (index, version) in newest.into_iter().enumerate() {
+            requests.push(async move {
+                if index >= MAX_INFO_LOOKUPS {
+                    return self.collect(module, version, None);
+                }
+                let endpoint = format!("@v/{}.info", escape_path(version));
+                let time = match self.fetch_info(module, &endpoint).await {
+                    Ok(info) => info.time,
+                    Err(e) => {
+                        warn!("Failed to fetch info for {}: {}", version, e);
+                        None
+                    }
+                };
+                self.collect(module, version, time)
+            });
+        }
+        stream::iter(requests)
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Maps a version of `module` and its timestamp to version metadata
+    fn collect(&self, module: &str, version: &str, time: Option<DateTime<Utc>>) -> VersionMetadata {
+        let zip = self.endpoint(module, &format!("@v/{}.zip", escape_path(version)));
+        VersionMetadata {
+            released_at: time,
+            prerelease: is_prerelease(version),
+            ..VersionMetadata::new(
+                version.strip_prefix('v').unwrap_or(version),
+                vec![VersionedAsset {
+                    released_at: time,
+                    ..VersionedAsset::new(zip, AssetKind::Release)
+                }],
+            )
+        }
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for GoModuleHost {
+    #[tracing::instrument(skip(self), fields(project = %self.module))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let (module, versions) = self.resolve().await?;
+        info!("Found {} versions of {}", versions.len(), module);
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{MockResponse, MockServer};
+
+    #[test]
+    fn test_from_url() {
+        for (url, module) in [
+            (
+                "https://pkg.go.dev/github.com/spf13/cobra",
+                "github.com/spf13/cobra",
+            ),
+            (
+                "https://pkg.go.dev/golang.org/x/text@v0.21.0",
+                "golang.org/x/text",
+            ),
+            (
+                "https://proxy.golang.org/github.com/!burnt!sushi/toml/@v/list",
+                "github.com/BurntSushi/toml",
+            ),
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert_eq!(GoModuleHost::from_url(&url).unwrap().module, module);
+        }
+
+        let url = Url::parse("https://pkg.go.dev/").unwrap();
+        assert!(GoModuleHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_escape_path() {
+        assert_eq!(
+            escape_path("github.com/BurntSushi/toml"),
+            "github.com/!burnt!sushi/toml"
+        );
+        assert_eq!(escape_path("golang.org/x/text"), "golang.org/x/text");
+        assert_eq!(
+            unescape_path("github.com/!burnt!sushi/toml"),
+            "github.com/BurntSushi/toml"
+        );
+    }
+
+    /// Tests that listed versions get timestamps and zip downloads from the proxy
+    #[tokio::test]
+    async fn test_versions() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/github.com/!burnt!sushi/toml/@v/list",
+                MockResponse::new(200, "v1.4.0\nv1.5.0-rc.1\n"),
+            ),
+            (
+                "/github.com/!burnt!sushi/toml/@v/v1.4.0.info",
+                MockResponse::new(200, r#"{"Version":"v1.4.0","Time":"2024-06-06T09:00:00Z"}"#),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://pkg.go.dev/github.com/BurntSushi/toml").unwrap();
+        let versions = GoModuleHost::from_url(&url)
+            .unwrap()
+            .with_proxy(server.url.clone())
+            .versions()
+            .await
+            .unwrap();
+
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, "1.5.0-rc.1");
+        assert!(versions[0].prerelease);
+        // A failed .info request only leaves the timestamp unset
+        assert_eq!(versions[0].released_at, None);
+        assert_eq!(versions[1].version, "1.4.0");
+        assert_eq!(
+            versions[1].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-06-06T09:00:00+00:00")
+        );
+        assert_eq!(
+            versions[1].downloads[0].url,
+            server
+                .url
+                .join("github.com/!burnt!sushi/toml/@v/v1.4.0.zip")
+                .unwrap()
+                .as_str()
+        );
+    }
+
+    /// Tests that modules without tags fall back to their latest pseudo-version
+    #[tokio::test]
+    async fn test_latest() {
+        let server = MockServer::with_routes(vec![
+            ("/example.com/tool/@v/list", MockResponse::new(200, "")),
+            (
+                "/example.com/tool/@latest",
+                MockResponse::new(
+                    200,
+                    r#"{"Version":"v0.0.0-20240101000000-abcdef123456","Time":"2024-01-01T00:00:00Z"}"#,
+                ),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://pkg.go.dev/example.com/tool").unwrap();
+        let versions = GoModuleHost::from_url(&url)
+            .unwrap()
+            .with_proxy(server.url.clone())
+            .versions()
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "0.0.0-20240101000000-abcdef123456");
+    }
+
+    /// Tests that packages below a module root resolve to the module
+    #[tokio::test]
+    async fn test_package_path() {
+        assert_eq!(
+            module_candidates("golang.org/x/text/unicode/norm").collect::<Vec<_>>(),
+            [
+                "golang.org/x/text/unicode/norm",
+                "golang.org/x/text/unicode",
+                "golang.org/x/text",
+                "golang.org/x",
+            ]
+        );
+
+        let server = MockServer::with_routes(vec![
+            (
+                "/golang.org/x/text/unicode/@v/list",
+                MockResponse::new(410, "not found: module golang.org/x/text/unicode"),
+            ),
+            (
+                "/golang.org/x/text/@v/list",
+                MockResponse::new(200, "v0.21.0\n"),
+            ),
+        ])
+        .await;
+
+        let url = Url::parse("https://pkg.go.dev/golang.org/x/text/unicode/norm").unwrap();
+        let versions = GoModuleHost::from_url(&url)
+            .unwrap()
+            .with_proxy(server.url.clone())
+            .versions()
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            server
+                .url
+                .join("golang.org/x/text/@v/v0.21.0.zip")
+                .unwrap()
+                .as_str()
+        );
+
+        let url = Url::parse("https://pkg.go.dev/example.com/missing/pkg").unwrap();
+        let error = GoModuleHost::from_url(&url)
+            .unwrap()
+            .with_proxy(server.url.clone())
+            .versions()
+            .await
+            .unwrap_err();
+        assert!(matches!(error, HostError::NotFound { .. }));
+    }
+
+    /// Tests that only the newest versions are dated
+    #[tokio::test]
+    async fn test_info_limit() {
+        let list = (0..60)
+            .map(|minor| format!("v1.{minor}.0\n"))
+            .collect::<String>();
+        let server = MockServer::start(move |request| match request.path.as_str() {
+            "/example.com/tool/@v/list" => MockResponse::new(200, list.clone()),
+            _ => MockResponse::new(200, r#"{"Version":"v1.0.0","Time":"2024-01-01T00:00:00Z"}"#),
+        })
+        .await;
+
+        let url = Url::parse("https://pkg.go.dev/example.com/tool").unwrap();
+        let versions = GoModuleHost::from_url(&url)
+            .unwrap()
+            .with_proxy(server.url.clone())
+            .versions()
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 60);
+        assert_eq!(server.requests().len(), 1 + MAX_INFO_LOOKUPS);
+        assert!(versions[0].released_at.is_some());
+        assert!(versions[59].released_at.is_none());
+    }
+}
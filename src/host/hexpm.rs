@@ -0,0 +1,197 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, Host, HostError,
+    DEFAULT_USER_AGENT,
+};
+
+/// A Host implementation for Elixir and Erlang packages published on hex.pm
+pub struct HexPmHost {
+    /// The package name (i.e. "phoenix")
+    pub package: String,
+
+    /// The complete URL the host was created from
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// Response format for the hex.pm package API
+#[derive(Deserialize, Debug)]
+pub struct HexPmPackageResponse {
+    /// The package name
+    pub name: String,
+    /// Every published release of the package
+    pub releases: Vec<HexPmRelease>,
+}
+
+/// A single release in a hex.pm package response
+#[derive(Deserialize, Debug)]
+pub struct HexPmRelease {
+    /// The release version
+    pub version: String,
+    /// When the release was published
+    pub inserted_at: Option<DateTime<Utc>>,
+}
+
+impl HexPmHost {
+    /// Creates a new HexPmHost instance from a `https://hex.pm/packages/<name>` URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        if path.first() != Some(&"packages") {
+            return Err(HostError::invalid_url(url, "expected a /packages/ URL"));
+        }
+        let package = path
+            .get(1)
+            .ok_or_else(|| HostError::parse_error(url, "missing package in hex.pm URL"))?;
+
+        debug!("Created HexPmHost for {}", package);
+        Ok(Self {
+            package: package.to_string(),
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Maps the releases of a package response to version metadata
+    fn collect(&self, response: HexPmPackageResponse) -> Vec<VersionMetadata> {
+        response
+            .releases
+            .into_iter()
+            .map(|release| {
+                let url = format!(
+                    "https://repo.hex.pm/tarballs/{}-{}.tar",
+                    response.name, release.version
+                );
+                VersionMetadata {
+                    downloads: vec![VersionedAsset {
+                        released_at: release.inserted_at,
+                        ..VersionedAsset::new(url, AssetKind::Release)
+                    }],
+                    released_at: release.inserted_at,
+                    prerelease: is_prerelease(&release.version),
+                    ..VersionMetadata::new(release.version, vec![])
+                }
+            })
+            .collect()
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for HexPmHost {
+    #[tracing::instrument(skip(self), fields(project = %self.package))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let uri = format!("https://hex.pm/api/packages/{}", self.package);
+        debug!("Fetching package data from: {}", uri);
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch package data".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .json::<HexPmPackageResponse>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse package data from {uri}"),
+                source: e,
+            })?;
+
+        info!(
+            "Found {} versions of {}",
+            response.releases.len(),
+            response.name
+        );
+        Ok(sort_versions(self.collect(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the package name is taken from package and release pages
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://hex.pm/packages/phoenix").unwrap();
+        let host = HexPmHost::from_url(&url).unwrap();
+        assert_eq!(host.package, "phoenix");
+
+        let url = Url::parse("https://hex.pm/packages/phoenix/1.7.14").unwrap();
+        assert_eq!(HexPmHost::from_url(&url).unwrap().package, "phoenix");
+    }
+
+    /// Tests that releases use repository tarballs and keep their insertion dates
+    #[test]
+    fn test_releases() {
+        let url = Url::parse("https://hex.pm/packages/phoenix").unwrap();
+        let host = HexPmHost::from_url(&url).unwrap();
+        let response: HexPmPackageResponse = serde_json::from_str(
+            r#"{
+                "name": "phoenix",
+                "releases": [
+                    {
+                        "version": "1.7.14",
+                        "url": "https://hex.pm/api/packages/phoenix/releases/1.7.14",
+                        "has_docs": true,
+                        "inserted_at": "2024-06-18T16:02:17.404930Z"
+                    },
+                    {
+                        "version": "1.7.0-rc.3",
+                        "url": "https://hex.pm/api/packages/phoenix/releases/1.7.0-rc.3",
+                        "has_docs": true,
+                        "inserted_at": "2023-02-13T20:31:02.130466Z"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let versions = host.collect(response);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://repo.hex.pm/tarballs/phoenix-1.7.14.tar"
+        );
+        assert_eq!(
+            versions[0].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-06-18T16:02:17.404930+00:00")
+        );
+        assert_eq!(
+            versions[1].downloads[0].url,
+            "https://repo.hex.pm/tarballs/phoenix-1.7.0-rc.3.tar"
+        );
+    }
+}
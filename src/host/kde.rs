@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use tracing::debug;
+use url::Url;
+
+use crate::{versioning::VersionExtractor, VersionMetadata};
+
+use super::{listing, retry::RetryPolicy, sort_versions, Host, HostError, DEFAULT_USER_AGENT};
+
+/// A Host implementation for projects published on download.kde.org
+///
+/// KDE products live under `https://download.kde.org/<channel>/<product>/` with
+/// one subdirectory per version, where `<channel>` is either `stable` or `unstable`.
+/// Components of larger products (i.e. Plasma or Frameworks) share the product
+/// directory, so the component is taken from the tarball name.
+pub struct KdeHost {
+    /// Release channel, either "stable" or "unstable"
+    pub channel: String,
+
+    /// The product directory (i.e. "plasma", "frameworks", "kdevelop")
+    pub product: String,
+
+    /// The component name used to match tarballs (i.e. "kwin")
+    pub project: String,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+impl KdeHost {
+    /// Creates a new KdeHost instance from a URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        let channel_index = path
+            .iter()
+            .position(|p| *p == "stable" || *p == "unstable")
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let product = path
+            .get(channel_index + 1)
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+
+        let project = path
+            .last()
+            .and_then(|file| VersionExtractor::new().extract(file).ok())
+            .filter(|_| path.len() > channel_index + 2)
+            .map(|m| m.name)
+            .unwrap_or_else(|| product.to_string());
+
+        Ok(Self {
+            channel: path[channel_index].to_string(),
+            product: product.to_string(),
+            project,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Returns the URL of the product's top level directory
+    fn product_url(&self) -> Result<Url, HostError> {
+        let uri = format!(
+            "https://download.kde.org/{}/{}/",
+            self.channel, self.product
+        );
+        Url::parse(&uri).map_err(|e| HostError::invalid_url(&uri, e))
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for KdeHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries =
+            listing::list_directory_as(&self.product_url()?, &self.user_agent, &self.retry).await?;
+
+        let version_dirs = entries
+            .iter()
+            .filter(|e| e.is_dir && e.name.starts_with(|c: char| c.is_ascii_digit()))
+            .collect::<Vec<_>>();
+
+        // Products without version subdirectories publish everything in one flat index
+        let mut files = vec![];
+        if version_dirs.is_empty() {
+            files.extend(entries.iter().filter(|e| !e.is_dir).cloned());
+        }
+        for dir in version_dirs {
+            debug!("Descending into version directory: {}", dir.url);
+            for entry in listing::list_directory_as(&dir.url, &self.user_agent, &self.retry).await?
+            {
+                if entry.is_dir && entry.name == "src" {
+                    files.extend(
+                        listing::list_directory_as(&entry.url, &self.user_agent, &self.retry)
+                            .await?,
+                    );
+                } else if !entry.is_dir {
+                    files.push(entry);
+                }
+            }
+        }
+
+        let prerelease = self.channel == "unstable";
+        Ok(sort_versions(
+            listing::collect_versions(&self.project, &files)
+                .into_iter()
+                .map(|v| VersionMetadata { prerelease, ..v }),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let url =
+            Url::parse("https://download.kde.org/stable/plasma/6.1.0/kwin-6.1.0.tar.xz").unwrap();
+        let host = KdeHost::from_url(&url).unwrap();
+        assert_eq!(host.channel, "stable");
+        assert_eq!(host.product, "plasma");
+        assert_eq!(host.project, "kwin");
+
+        let url = Url::parse("https://download.kde.org/unstable/kdevelop/").unwrap();
+        let host = KdeHost::from_url(&url).unwrap();
+        assert_eq!(host.channel, "unstable");
+        assert_eq!(host.project, "kdevelop");
+
+        let url = Url::parse("https://download.kde.org/Attic/").unwrap();
+        assert!(KdeHost::from_url(&url).is_err());
+    }
+}
@@ -0,0 +1,491 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Helpers for hosts that publish releases through plain HTTP directory
+//! listings (Apache/nginx autoindex pages and similar).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::LazyLock,
+};
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use tracing::debug;
+use url::Url;
+
+use crate::{versioning::VersionExtractor, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, classify_asset, http_client, retry::RetryPolicy, HostError, CHECKSUM_SUFFIXES,
+    DEFAULT_USER_AGENT, SIGNATURE_SUFFIXES,
+};
+
+/// A single link discovered in a directory listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListingEntry {
+    /// The file or directory name, without any trailing slash
+    pub name: String,
+
+    /// The absolute URL of the entry
+    pub url: Url,
+
+    /// Whether the entry is a subdirectory
+    pub is_dir: bool,
+}
+
+/// Fetches the directory listing at `url` and returns its entries
+///
+/// Only links pointing directly inside the listed directory are returned,
+/// so parent links, sort links and links to other sites are dropped.
+pub async fn list_directory(url: &Url) -> Result<Vec<ListingEntry>, HostError> {
+    list_directory_as(url, DEFAULT_USER_AGENT, &RetryPolicy::default()).await
+}
+
+/// Like [`list_directory`], identifying as `user_agent` and retrying according to `retry`
+pub async fn list_directory_as(
+    url: &Url,
+    user_agent: &str,
+    retry: &RetryPolicy,
+) -> Result<Vec<ListingEntry>, HostError> {
+    let body = fetch_text_as(url, user_agent, retry).await?;
+    parse_listing(url, &body)
+}
+
+/// Fetches a text document such as a directory listing or checksum file
+pub async fn fetch_text(url: &Url) -> Result<String, HostError> {
+    fetch_text_as(url, DEFAULT_USER_AGENT, &RetryPolicy::default()).await
+}
+
+/// Like [`fetch_text`], identifying as `user_agent` and retrying according to `retry`
+pub async fn fetch_text_as(
+    url: &Url,
+    user_agent: &str,
+    retry: &RetryPolicy,
+) -> Result<String, HostError> {
+    debug!("Fetching: {}", url);
+    retry
+        .send(http_client(user_agent).get(url.as_str()))
+        .await
+        .map_err(|e| HostError::ApiRequest {
+            context: format!("failed to fetch {url}"),
+            source: e,
+        })
+        .and_then(check_status)?
+        .text()
+        .await
+        .map_err(|e| HostError::ApiRequest {
+            context: format!("failed to read {url}"),
+            source: e,
+        })
+}
+
+/// Parses the HTML body of a directory listing located at `base`
+pub fn parse_listing(base: &Url, body: &str) -> Result<Vec<ListingEntry>, HostError> {
+    let doc = scraper::Html::parse_document(body);
+    let selector = scraper::Selector::parse("a").map_err(|e| HostError::parse_error(base, e))?;
+
+    let mut entries = vec![];
+    for element in doc.select(&selector) {
+        let Some(href) = element.value().attr("href") else {
+            continue;
+        };
+        if href.starts_with('?') || href.starts_with('#') {
+            continue;
+        }
+        let Ok(url) = base.join(href) else {
+            continue;
+        };
+        if url.query().is_some() || url.host_str() != base.host_str() {
+            continue;
+        }
+
+        // Only keep direct children of the listed directory
+        let Some(relative) = url.path().strip_prefix(base.path()) else {
+            continue;
+        };
+        let is_dir = relative.ends_with('/');
+        let name = relative.trim_end_matches('/');
+        if name.is_empty() || name.contains('/') {
+            continue;
+        }
+
+        let entry = ListingEntry {
+            name: name.to_string(),
+            url,
+            is_dir,
+        };
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// A link of a listing and the text up to the next link, where its date is shown
+static LISTING_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<a\s[^>]*href="([^"?#]+)"[^>]*>.*?</a>((?:[^<]|</?t[dh][^>]*>)*)"#).unwrap()
+});
+
+/// A date and time as shown by Apache and nginx indexes
+static LISTING_DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(\d{4}-\d{2}-\d{2}|\d{1,2}-[A-Za-z]{3}-\d{4}|\d{4}-[A-Za-z]{3}-\d{1,2})[ T](\d{1,2}:\d{2}(?::\d{2})?)",
+    )
+    .unwrap()
+});
+
+/// Reads the modification date shown next to each link of a directory listing
+///
+/// Both Apache's fancy index, with the date in the table cell after the link,
+/// and the pre-formatted variant used by plain Apache and nginx indexes
+/// (`05-Sep-2024 14:12`) are understood. Dates are assumed to be UTC and are
+/// keyed by the file name the link points to.
+pub fn parse_listing_dates(body: &str) -> BTreeMap<String, DateTime<Utc>> {
+    LISTING_LINK
+        .captures_iter(body)
+        .filter_map(|caps| {
+            let name = caps[1].trim_end_matches('/').rsplit('/').next()?;
+            let found = LISTING_DATE.captures(&caps[2])?;
+            let stamp = format!("{} {}", &found[1], &found[2]);
+            let parsed = ["%Y-%m-%d", "%d-%b-%Y", "%Y-%b-%d"]
+                .iter()
+                .flat_map(|day| [format!("{day} %H:%M"), format!("{day} %H:%M:%S")])
+                .find_map(|format| NaiveDateTime::parse_from_str(&stamp, &format).ok())?;
+            Some((name.to_string(), Utc.from_utc_datetime(&parsed)))
+        })
+        .collect()
+}
+
+/// Whether `entry` is a subdirectory holding a single version of `project`
+///
+/// Matches both bare version directories (`1.2/`) and ones repeating the
+/// project name (`gcc-13.2.0/`).
+pub fn is_version_dir(project: &str, entry: &ListingEntry) -> bool {
+    let name = entry
+        .name
+        .strip_prefix(&format!("{project}-"))
+        .unwrap_or(&entry.name);
+    entry.is_dir && name.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Splits a companion file name into the artifact it belongs to and its kind
+///
+/// `foo-1.0.tar.xz.sig` yields `("foo-1.0.tar.xz", Signature)`, while plain
+/// archives are returned unchanged as `Release`.
+pub fn split_companion(name: &str) -> (&str, AssetKind) {
+    for suffix in SIGNATURE_SUFFIXES {
+        if let Some(artifact) = name.strip_suffix(suffix) {
+            return (artifact, AssetKind::Signature);
+        }
+    }
+    for suffix in CHECKSUM_SUFFIXES {
+        if let Some(artifact) = name.strip_suffix(suffix) {
+            return (artifact, AssetKind::Checksum);
+        }
+    }
+    (name, AssetKind::Release)
+}
+
+/// Groups the release files of `project` found in a listing by version
+///
+/// Archives are matched against the project name using the [`VersionExtractor`],
+/// and signature/checksum files are only kept when the archive they belong to
+/// is also present in the listing.
+pub fn collect_versions(project: &str, entries: &[ListingEntry]) -> Vec<VersionMetadata> {
+    let matcher = VersionExtractor::new();
+    let names = entries
+        .iter()
+        .filter(|e| !e.is_dir)
+        .map(|e| e.name.as_str())
+        .collect::<BTreeSet<_>>();
+
+    let mut versions: BTreeMap<String, BTreeSet<VersionedAsset>> = BTreeMap::new();
+    for entry in entries.iter().filter(|e| !e.is_dir) {
+        let (artifact, kind) = split_companion(&entry.name);
+        if kind != AssetKind::Release && !names.contains(artifact) {
+            continue;
+        }
+        let Ok(m) = matcher.extract(artifact) else {
+            continue;
+        };
+        if m.name != project {
+            continue;
+        }
+
+        versions
+            .entry(m.version)
+            .or_default()
+            .insert(VersionedAsset::new(entry.url.to_string(), kind));
+    }
+
+    versions
+        .into_iter()
+        .map(|(version, downloads)| VersionMetadata::new(version, downloads.into_iter().collect()))
+        .collect()
+}
+
+/// Classifies a file published for `project` in a release directory
+///
+/// Source archives stay [`AssetKind::Release`], as they are what these hosts
+/// release, while prebuilt binaries, installers and companion files get their
+/// own kind. The project name itself is skipped, so that `linux-6.1.tar.xz`
+/// is not taken for a platform specific build.
+pub fn classify_release_file(project: &str, name: &str) -> AssetKind {
+    let rest = match name.get(..project.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(project) => &name[project.len()..],
+        _ => name,
+    };
+    match classify_asset(rest, None) {
+        AssetKind::SourceArchive => AssetKind::Release,
+        kind => kind,
+    }
+}
+
+/// Parses a checksum manifest in GNU coreutils (`<hash>  <filename>`) or
+/// BSD (`SHA256 (<filename>) = <hash>`) format
+///
+/// Returns a map from file name to `<algorithm>:<hex>` digest. BSD lines name
+/// their algorithm, while GNU lines are assumed to use `algorithm`. Binary
+/// mode markers (`*file`) and directory components are stripped from the file names.
+pub fn parse_checksums(body: &str, algorithm: &str) -> BTreeMap<String, String> {
+    body.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (named, hash, file) = match parse_bsd_checksum(line) {
+                Some((named, hash, file)) => (named.to_lowercase(), hash, file),
+                None => {
+                    let (hash, file) = line.split_once(char::is_whitespace)?;
+                    let file = file.trim_start().trim_start_matches('*');
+                    (algorithm.to_string(), hash, file)
+                }
+            };
+            let file = file.rsplit('/').next().unwrap_or(file);
+            if hash.is_empty() || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            Some((file.to_string(), format!("{named}:{}", hash.to_lowercase())))
+        })
+        .collect()
+}
+
+/// Splits a BSD style `<ALGORITHM> (<filename>) = <hash>` line into algorithm, hash and file name
+fn parse_bsd_checksum(line: &str) -> Option<(&str, &str, &str)> {
+    let (algorithm, rest) = line.split_once(" (")?;
+    let (file, hash) = rest.rsplit_once(") = ")?;
+    Some((algorithm.trim(), hash.trim(), file))
+}
+
+/// Whether a file name is a SHA256 checksum manifest (i.e. `SHA256SUMS`, `sha256sum.txt`)
+pub fn is_sha256_manifest(name: &str) -> bool {
+    let stem = name.strip_suffix(".txt").unwrap_or(name);
+    stem.eq_ignore_ascii_case("SHA256SUMS") || stem.eq_ignore_ascii_case("SHA256SUM")
+}
+
+/// Attaches digests from a parsed checksum manifest to the matching downloads
+///
+/// Assets are matched by the file name at the end of their URL. Downloads that
+/// already carry a checksum, such as a digest reported by the host, keep it.
+pub fn attach_checksums(versions: &mut [VersionMetadata], sums: &BTreeMap<String, String>) {
+    for asset in versions
+        .iter_mut()
+        .flat_map(|v| v.downloads.iter_mut())
+        .filter(|asset| asset.checksum.is_none())
+    {
+        let file = asset.url.rsplit('/').next().unwrap_or_default();
+        if let Some(checksum) = sums.get(file) {
+            asset.checksum = Some(checksum.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::host::mock::{MockResponse, MockServer};
+
+    /// Tests that a transient failure of a listing server is retried
+    #[tokio::test]
+    async fn test_fetch_retry() {
+        let failed = AtomicBool::new(false);
+        let server = MockServer::start(move |_| match failed.swap(true, Ordering::SeqCst) {
+            false => MockResponse::new(502, "Bad Gateway"),
+            true => MockResponse::new(200, "SHA256SUMS"),
+        })
+        .await;
+
+        let body = fetch_text_as(
+            &server.url,
+            DEFAULT_USER_AGENT,
+            &RetryPolicy::new(2, Duration::ZERO),
+        )
+        .await
+        .unwrap();
+        assert_eq!(body, "SHA256SUMS");
+        assert_eq!(server.requests().len(), 2);
+
+        let server = MockServer::start(|_| MockResponse::new(502, "Bad Gateway")).await;
+        let result = fetch_text_as(&server.url, DEFAULT_USER_AGENT, &RetryPolicy::none()).await;
+        assert!(matches!(result, Err(HostError::ApiRequest { .. })));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_classify_release_file() {
+        for (project, name, kind) in [
+            (
+                "nvidia-persistenced",
+                "nvidia-persistenced-570.tar.bz2",
+                AssetKind::Release,
+            ),
+            (
+                "NVIDIA-Linux-x86_64",
+                "NVIDIA-Linux-x86_64-570.run",
+                AssetKind::Binary,
+            ),
+            ("linux", "linux-6.12.1.tar.xz", AssetKind::Release),
+            ("linux", "linux-6.12.1.tar.sign", AssetKind::Signature),
+            ("foo", "foo-1.0-x86_64.tar.gz", AssetKind::Binary),
+            ("foo", "foo-1.0.x86_64.rpm", AssetKind::Installer),
+        ] {
+            assert_eq!(classify_release_file(project, name), kind, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_split_companion() {
+        for (name, artifact, kind) in [
+            ("foo-1.0.tar.xz.sig", "foo-1.0.tar.xz", AssetKind::Signature),
+            (
+                "foo-1.0.tar.xz.minisig",
+                "foo-1.0.tar.xz",
+                AssetKind::Signature,
+            ),
+            (
+                "foo-1.0.tar.xz.sha256sum",
+                "foo-1.0.tar.xz",
+                AssetKind::Checksum,
+            ),
+            ("foo-1.0.tar.xz.md5", "foo-1.0.tar.xz", AssetKind::Checksum),
+            ("foo-1.0.tar.xz", "foo-1.0.tar.xz", AssetKind::Release),
+        ] {
+            assert_eq!(split_companion(name), (artifact, kind), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_checksums() {
+        let sums = parse_checksums(
+            "0eb4b4b9420a0f51db142ba3f9c64b333f826532dc0f48c6410ae51f4799b664 *dist/foo-1.0.tar.gz\n\
+             SHA256 (foo 1.1.tar.gz) = C4D91BE36FC8E54DEAE7575241E03F4211EB102AFB3FC0775FBBC1B740016705\n\
+             SHA512 (foo-1.2.tar.gz) = 0a1b\n\
+             MD5 (foo-1.3.tar.gz) = 9e8f\n\
+             not a checksum line\n",
+            "sha256",
+        );
+        assert_eq!(sums.len(), 4);
+        assert_eq!(
+            sums.get("foo-1.0.tar.gz").map(String::as_str),
+            Some("sha256:0eb4b4b9420a0f51db142ba3f9c64b333f826532dc0f48c6410ae51f4799b664")
+        );
+        assert_eq!(
+            sums.get("foo 1.1.tar.gz").map(String::as_str),
+            Some("sha256:c4d91be36fc8e54deae7575241e03f4211eb102afb3fc0775fbbc1b740016705")
+        );
+        // BSD lines name their own algorithm
+        assert_eq!(
+            sums.get("foo-1.2.tar.gz").map(String::as_str),
+            Some("sha512:0a1b")
+        );
+        assert_eq!(
+            sums.get("foo-1.3.tar.gz").map(String::as_str),
+            Some("md5:9e8f")
+        );
+
+        assert!(is_sha256_manifest("SHA256SUMS.txt"));
+        assert!(is_sha256_manifest("sha256sum.txt"));
+        assert!(!is_sha256_manifest("SHA512SUMS"));
+    }
+
+    #[test]
+    fn test_parse_listing_dates() {
+        let fancy = r#"<table>
+            <tr><th><a href="?C=N;O=D">Name</a></th><th><a href="?C=M;O=A">Last modified</a></th></tr>
+            <tr><td valign="top"><img src="/icons/back.gif" alt="[PARENTDIR]"></td><td><a href="/sources/glib/">Parent Directory</a></td><td>&nbsp;</td></tr>
+            <tr><td valign="top"><img src="/icons/compressed.gif" alt="[   ]"></td><td><a href="glib-2.82.0.tar.xz">glib-2.82.0.tar.xz</a></td><td align="right">2024-09-05 14:12  </td><td align="right">5.3M</td></tr>
+            <tr><td valign="top"><img src="/icons/text.gif" alt="[TXT]"></td><td><a href="glib-2.82.0.news">glib-2.82.0.news</a></td><td align="right">2024-09-05 14:12:31  </td><td align="right">1.1K</td></tr>
+            </table>"#;
+        let dates = parse_listing_dates(fancy);
+        assert_eq!(
+            dates
+                .get("glib-2.82.0.tar.xz")
+                .map(|d| d.to_rfc3339())
+                .as_deref(),
+            Some("2024-09-05T14:12:00+00:00")
+        );
+        assert_eq!(
+            dates
+                .get("glib-2.82.0.news")
+                .map(|d| d.to_rfc3339())
+                .as_deref(),
+            Some("2024-09-05T14:12:31+00:00")
+        );
+        assert!(!dates.contains_key("glib"));
+
+        let plain = r#"<pre><a href="../">../</a>
+<a href="glib-2.82.1.tar.xz">glib-2.82.1.tar.xz</a>                 17-Sep-2024 10:03             5533212
+<a href="glib-2.82.1.sha256sum">glib-2.82.1.sha256sum</a>              2024-Sep-17 10:03                 176
+</pre>"#;
+        let dates = parse_listing_dates(plain);
+        assert_eq!(dates.len(), 2);
+        assert_eq!(
+            dates
+                .get("glib-2.82.1.tar.xz")
+                .map(|d| d.to_rfc3339())
+                .as_deref(),
+            Some("2024-09-17T10:03:00+00:00")
+        );
+        assert_eq!(
+            dates.get("glib-2.82.1.sha256sum"),
+            dates.get("glib-2.82.1.tar.xz")
+        );
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let base = Url::parse("https://ftp.gnu.org/gnu/nano/").unwrap();
+        let body = r#"
+            <a href="?C=N;O=D">Name</a>
+            <a href="/gnu/">Parent Directory</a>
+            <a href="nano-8.3.tar.xz">nano-8.3.tar.xz</a>
+            <a href="nano-8.3.tar.xz.sig">nano-8.3.tar.xz.sig</a>
+            <a href="old/">old/</a>
+            <a href="https://www.gnu.org/">GNU</a>
+        "#;
+
+        let entries = parse_listing(&base, body).unwrap();
+        let names = entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_dir))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("nano-8.3.tar.xz", false),
+                ("nano-8.3.tar.xz.sig", false),
+                ("old", true),
+            ]
+        );
+        assert_eq!(
+            entries[0].url.as_str(),
+            "https://ftp.gnu.org/gnu/nano/nano-8.3.tar.xz"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Minimal HTTP server used to exercise hosts against canned responses in tests.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use url::Url;
+
+/// A request received by the [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// The HTTP method (i.e. "GET")
+    pub method: String,
+    /// The request path, including any query string
+    pub path: String,
+    /// Request headers, with lowercased names
+    pub headers: Vec<(String, String)>,
+    /// The request body, empty for requests without one
+    pub body: String,
+}
+
+impl MockRequest {
+    /// Returns the value of the named header, if it was sent
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A canned response returned by the [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// How long the server waits before answering
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    /// A response with the given status and body
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.into(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Delays the response, i.e. to observe requests overlapping
+    pub fn delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+
+    /// Adds a response header
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+/// A local HTTP/1.1 server answering every request through a handler
+pub struct MockServer {
+    /// Base URL of the server, with a trailing slash
+    pub url: Url,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    /// Starts a server answering requests using `handler`
+    pub async fn start(
+        handler: impl Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let seen = requests.clone();
+        let highest = peak.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let seen = seen.clone();
+                let handler = handler.clone();
+                let in_flight = in_flight.clone();
+                let highest = highest.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![];
+                    let mut chunk = [0u8; 1024];
+                    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match stream.read(&mut chunk).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                    }
+
+                    let end = buf.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+                    let text = String::from_utf8_lossy(&buf[..end]).to_string();
+                    let mut lines = text.lines();
+                    let mut request_line = lines.next().unwrap_or_default().split(' ');
+                    let mut request = MockRequest {
+                        method: request_line.next().unwrap_or_default().to_string(),
+                        path: request_line.next().unwrap_or_default().to_string(),
+                        headers: lines
+                            .take_while(|l| !l.is_empty())
+                            .filter_map(|l| l.split_once(':'))
+                            .map(|(n, v)| (n.trim().to_lowercase(), v.trim().to_string()))
+                            .collect(),
+                        body: String::new(),
+                    };
+
+                    let length = request
+                        .header("content-length")
+                        .and_then(|l| l.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    while buf.len() < end + length {
+                        match stream.read(&mut chunk).await {
+                            Ok(0) | Err(_) => break,
+                            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                        }
+                    }
+                    request.body = String::from_utf8_lossy(&buf[end..]).to_string();
+
+                    let response = handler(&request);
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    highest.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(response.delay).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let mut out = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                        response.status,
+                        response.body.len()
+                    );
+                    for (name, value) in &response.headers {
+                        out.push_str(&format!("{name}: {value}\r\n"));
+                    }
+                    out.push_str("\r\n");
+                    if request.method != "HEAD" {
+                        out.push_str(&response.body);
+                    }
+                    seen.lock().unwrap().push(request);
+                    let _ = stream.write_all(out.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            peak,
+        }
+    }
+
+    /// Starts a server serving fixed responses keyed by request path
+    ///
+    /// Unknown paths are answered with a 404.
+    pub async fn with_routes(routes: Vec<(&str, MockResponse)>) -> Self {
+        let routes = routes
+            .into_iter()
+            .map(|(path, response)| (path.to_string(), response))
+            .collect::<Vec<_>>();
+        Self::start(move |request| {
+            routes
+                .iter()
+                .find(|(path, _)| *path == request.path)
+                .map(|(_, response)| response.clone())
+                .unwrap_or_else(|| MockResponse::new(404, "Not Found"))
+        })
+        .await
+    }
+
+    /// Returns all requests received so far
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the most requests that were being answered at the same time
+    pub fn max_in_flight(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
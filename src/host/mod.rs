@@ -2,6 +2,11 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    sync::{Arc, LazyLock, RwLock},
+};
+
 use github::GithubHost;
 use plain::PlainHost;
 use thiserror::Error;
@@ -9,32 +14,157 @@ use url::Url;
 
 use async_trait::async_trait;
 
-use crate::VersionMetadata;
+use crate::{
+    versioning::{self, Version},
+    AssetKind, UpdateReport, VersionMetadata, VersionedAsset,
+};
 
+pub mod anaconda;
+pub mod apache;
+pub mod bitbucket;
+pub mod cache;
+pub mod ctan;
+pub mod eclipse;
+pub mod forgejo;
+pub mod freedesktop;
+pub mod gitee;
 pub mod github;
 pub mod gnome;
+pub mod gnu;
+pub mod gomod;
+pub mod hexpm;
+pub mod kde;
+pub mod listing;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod nuget;
+pub mod obs;
+pub mod pecl;
 pub mod plain;
+pub mod retry;
+pub mod savannah;
+pub mod snapshot;
+pub mod sourcehut;
+pub mod xiph;
+
+/// The `User-Agent` sent by hosts unless configured otherwise
+pub const DEFAULT_USER_AGENT: &str = concat!("upstreams-rs/", env!("CARGO_PKG_VERSION"));
+
+/// Words that may precede the version in a tag without being part of it
+///
+/// Prerelease markers are deliberately absent, so `beta-2.0` stays apart from
+/// the stable `2.0` instead of merging with it.
+const TAG_WORD_PREFIXES: &[&str] = &["release", "rel"];
+
+/// Strips a leading project name, `release-` and a `v` from `tag`
+///
+/// Used by forges whose tags name versions (i.e. `v1.2.3` or `foo-1.2.3`).
+/// The project name is matched case-insensitively and may itself contain
+/// separators (i.e. `cosmic-applets-1.0`). Tags are kept whole unless a
+/// version starting with a digit remains.
+pub(crate) fn strip_tag_prefixes<'a>(project: &str, tag: &'a str) -> &'a str {
+    let mut rest = tag;
+    if let Some(head) = rest.get(..project.len()) {
+        if head.eq_ignore_ascii_case(project) {
+            rest = rest[project.len()..]
+                .strip_prefix(['-', '_'])
+                .unwrap_or(rest);
+        }
+    }
+    if let Some((word, tail)) = rest.split_once(['-', '_']) {
+        if TAG_WORD_PREFIXES
+            .iter()
+            .any(|prefix| word.eq_ignore_ascii_case(prefix))
+        {
+            rest = tail;
+        }
+    }
+    let rest = rest.strip_prefix(['v', 'V']).unwrap_or(rest);
+    if rest.starts_with(|c: char| c.is_ascii_digit()) {
+        rest
+    } else {
+        tag
+    }
+}
+
+/// Ends the path of `url` with a slash, as relative joins would otherwise
+/// replace its last segment
+pub(crate) fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    url
+}
+
+/// Builds the HTTP client hosts send their requests with, identifying as `user_agent`
+///
+/// A `user_agent` that is not a valid header value is reported and left out.
+pub(crate) fn http_client(user_agent: &str) -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(user_agent)
+        .build()
+        .unwrap_or_else(|e| {
+            tracing::warn!("Ignoring User-Agent {:?}: {}", user_agent, e);
+            reqwest::Client::new()
+        })
+}
 
 /// Common trait implemented by all repository host types
 #[async_trait]
 pub trait Host {
-    /// Fetches all available versions for this repository
+    /// Fetches all available versions for this repository, newest first
     async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError>;
+
+    /// Fetches a single version of this repository, if it exists
+    ///
+    /// The default implementation searches the result of [`Host::versions`];
+    /// hosts with a cheaper lookup should override it.
+    async fn version(&self, version: &str) -> Result<Option<VersionMetadata>, HostError> {
+        Ok(self
+            .versions()
+            .await?
+            .into_iter()
+            .find(|v| v.version == version))
+    }
+
+    /// Fetches the newest stable version, or the newest prerelease if there is none
+    ///
+    /// The default implementation picks from the result of [`Host::versions`];
+    /// hosts that can ask for the latest release directly should override it.
+    async fn latest(&self) -> Result<Option<VersionMetadata>, HostError> {
+        Ok(newest(sort_versions(self.versions().await?)))
+    }
+
+    /// Compares `current` against the versions available upstream
+    ///
+    /// Versions are compared with [`Version`] ordering, so `current` may use a
+    /// different spelling (i.e. `v1.2.0` against `1.2.0`).
+    async fn check_update(&self, current: &str) -> Result<UpdateReport, HostError> {
+        Ok(update_report(current, self.versions().await?))
+    }
+
+    /// Returns the new location of a project that was found to have moved
+    ///
+    /// Hosts that learn about renames while fetching versions report them here;
+    /// by default nothing is known to have moved.
+    fn moved_to(&self) -> Option<Url> {
+        None
+    }
 }
 
 /// Errors that can occur when interacting with repository hosts
 #[derive(Error, Debug)]
 pub enum HostError {
     /// The URL provided was not in a valid format for this host
-    #[error("invalid URL format: {0}")]
-    InvalidUrl(String),
+    #[error("invalid URL {url}: {reason}")]
+    InvalidUrl { url: String, reason: String },
 
-    /// Failed to parse repository information from a valid URL
-    #[error("failed to parse repository info: {0}")]
-    ParseError(String),
+    /// Failed to parse repository information from a URL, or data fetched from it
+    #[error("failed to parse {url}: {reason}")]
+    ParseError { url: String, reason: String },
 
     /// Failed to fetch data from the host's API
-    #[error("API request failed: {context}")]
+    #[error("API request failed: {}", with_request_url(context, source))]
     ApiRequest {
         context: String,
         #[source]
@@ -42,7 +172,7 @@ pub enum HostError {
     },
 
     /// Failed to parse data received from the API
-    #[error("failed to parse API response: {context}")]
+    #[error("failed to parse API response: {}", with_request_url(context, source))]
     ApiResponse {
         context: String,
         #[source]
@@ -52,12 +182,979 @@ pub enum HostError {
     /// The requested operation is not supported by this host
     #[error("operation not supported: {0}")]
     Unsupported(String),
+
+    /// The requested repository, project or file does not exist, or is private
+    /// and hidden from the current credentials
+    #[error("not found: {url}")]
+    NotFound { url: String },
+
+    /// Access was denied for a reason other than rate limiting, i.e. the
+    /// repository is private or credentials are invalid
+    #[error("access denied: {url}")]
+    Unauthorized { url: String },
+
+    /// The API rate limit was exhausted; requests may resume at `reset_at`
+    #[error("rate limited until {reset_at} ({remaining} requests remaining)")]
+    RateLimited {
+        reset_at: chrono::DateTime<chrono::Utc>,
+        remaining: u32,
+    },
+
+    /// The API rejected the configured credentials as malformed or expired
+    #[error("invalid or expired token for {url}")]
+    InvalidToken { url: String },
+
+    /// Failed to read or write a local file
+    #[error("I/O error: {context}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl HostError {
+    /// Creates an [`HostError::InvalidUrl`] for `url`
+    pub fn invalid_url(url: impl ToString, reason: impl ToString) -> Self {
+        Self::InvalidUrl {
+            url: url.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Creates an [`HostError::ParseError`] for data from `url`
+    pub fn parse_error(url: impl ToString, reason: impl ToString) -> Self {
+        Self::ParseError {
+            url: url.to_string(),
+            reason: reason.to_string(),
+        }
+    }
 }
 
+/// Appends the URL a failed request was sent to, unless `context` already names it
+fn with_request_url(context: &str, source: &reqwest::Error) -> String {
+    match source.url() {
+        Some(url) if !context.contains(url.as_str()) => format!("{context} ({url})"),
+        _ => context.to_string(),
+    }
+}
+
+/// Checks the status of a response before its body is consumed
+///
+/// A 404/410 maps to [`HostError::NotFound`] and a 401/403 to [`HostError::Unauthorized`]
+/// so callers can tell a missing or private project apart from other failures,
+/// while any other error status becomes an [`HostError::ApiRequest`] instead of
+/// an opaque parse error later on.
+pub(crate) fn check_status(response: reqwest::Response) -> Result<reqwest::Response, HostError> {
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE => {
+            return Err(HostError::NotFound {
+                url: response.url().to_string(),
+            })
+        }
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            return Err(HostError::Unauthorized {
+                url: response.url().to_string(),
+            })
+        }
+        _ => {}
+    }
+    let status = response.status();
+    response
+        .error_for_status()
+        .map_err(|e| HostError::ApiRequest {
+            context: format!("unexpected response status {status}"),
+            source: e,
+        })
+}
+
+/// Name suffixes of detached signatures
+pub(crate) const SIGNATURE_SUFFIXES: &[&str] = &[".asc", ".sig", ".sign", ".minisig"];
+
+/// Name suffixes of checksum files
+pub(crate) const CHECKSUM_SUFFIXES: &[&str] =
+    &[".sha256", ".sha512", ".sha1", ".md5", ".sha256sum"];
+
+/// Name suffixes of software bills of materials
+const SBOM_SUFFIXES: &[&str] = &[".spdx", ".spdx.json", ".cdx.json", ".bom.json", ".sbom"];
+
+/// Name suffixes of installers and distribution packages
+const INSTALLER_SUFFIXES: &[&str] = &[
+    ".msi",
+    ".exe",
+    ".dmg",
+    ".pkg",
+    ".deb",
+    ".rpm",
+    ".apk",
+    ".appimage",
+    ".flatpak",
+    ".snap",
+];
+
+/// Name suffixes of debug symbols and source maps
+const DEBUG_SUFFIXES: &[&str] = &[".debug", ".dbg", ".pdb", ".map", ".dsym.zip"];
+
+/// Name suffixes of archives, which hold sources unless they name a platform
+const ARCHIVE_SUFFIXES: &[&str] = &[
+    ".tar.gz",
+    ".tgz",
+    ".tar.xz",
+    ".txz",
+    ".tar.bz2",
+    ".tar.zst",
+    ".tar.lz",
+    ".tar.lzma",
+    ".zip",
+];
+
+/// Name fragments identifying a platform specific build
+const PLATFORM_MARKERS: &[&str] = &[
+    "linux",
+    "darwin",
+    "macos",
+    "windows",
+    "win32",
+    "win64",
+    "freebsd",
+    "android",
+    "amd64",
+    "x86_64",
+    "x64",
+    "arm64",
+    "aarch64",
+    "armv",
+    "i386",
+    "i686",
+    "386",
+    "universal",
+];
+
+/// Classifies a release file by its content type, when the host reports one,
+/// and otherwise by its name
+///
+/// Archives are treated as sources unless their name mentions a platform, so
+/// callers that know the project name should strip it first (i.e. for `linux`).
+pub(crate) fn classify_asset(name: &str, content_type: Option<&str>) -> AssetKind {
+    match content_type {
+        Some("application/pgp-signature") => return AssetKind::Signature,
+        Some(
+            "application/x-debian-package"
+            | "application/x-rpm"
+            | "application/x-redhat-package-manager"
+            | "application/x-msi"
+            | "application/x-msdownload"
+            | "application/x-apple-diskimage",
+        ) => return AssetKind::Installer,
+        _ => {}
+    }
+
+    let name = name.to_lowercase();
+    let has_suffix = |suffixes: &[&str]| suffixes.iter().any(|s| name.ends_with(s));
+
+    if has_suffix(SIGNATURE_SUFFIXES) {
+        AssetKind::Signature
+    } else if has_suffix(CHECKSUM_SUFFIXES)
+        || name.contains("checksums")
+        || name.contains("sha256sums")
+        || name.contains("sha512sums")
+    {
+        AssetKind::Checksum
+    } else if has_suffix(SBOM_SUFFIXES) || name.contains("sbom") {
+        AssetKind::Sbom
+    } else if has_suffix(INSTALLER_SUFFIXES) {
+        AssetKind::Installer
+    } else if has_suffix(DEBUG_SUFFIXES) {
+        AssetKind::Debug
+    } else if has_suffix(ARCHIVE_SUFFIXES) && !PLATFORM_MARKERS.iter().any(|m| name.contains(m)) {
+        AssetKind::SourceArchive
+    } else {
+        AssetKind::Binary
+    }
+}
+
+/// Ranks asset kinds by how much they tell us about an asset
+fn specificity(kind: &AssetKind) -> u8 {
+    match kind {
+        AssetKind::Autogenerated => 0,
+        AssetKind::Binary => 1,
+        AssetKind::Release
+        | AssetKind::Signature
+        | AssetKind::Checksum
+        | AssetKind::SourceArchive
+        | AssetKind::Sbom
+        | AssetKind::Installer
+        | AssetKind::Debug
+        | AssetKind::ReleaseNotes => 2,
+    }
+}
+
+/// Merges assets sharing the same URL into one entry per URL
+///
+/// The most specific kind wins (Release > Binary > Autogenerated), and any
+/// timestamps, checksum, size or download count missing on the kept asset are taken from the duplicate.
+pub(crate) fn merge_assets(
+    assets: impl IntoIterator<Item = VersionedAsset>,
+) -> Vec<VersionedAsset> {
+    let mut merged: BTreeMap<String, VersionedAsset> = BTreeMap::new();
+    for asset in assets {
+        match merged.entry(asset.url.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(asset);
+            }
+            Entry::Occupied(mut entry) => {
+                let existing = entry.get_mut();
+                let (mut keep, other) = if specificity(&asset.kind) > specificity(&existing.kind) {
+                    (asset, existing.clone())
+                } else {
+                    (existing.clone(), asset)
+                };
+                keep.released_at = keep.released_at.or(other.released_at);
+                keep.updated_at = keep.updated_at.or(other.updated_at);
+                keep.checksum = keep.checksum.or(other.checksum);
+                keep.size = keep.size.or(other.size);
+                keep.download_count = keep.download_count.or(other.download_count);
+                *existing = keep;
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+/// Links signature and checksum assets to the assets they verify
+///
+/// A companion named after another asset (i.e. `foo.tar.gz.asc` or
+/// `foo.tar.gz.sha256`) verifies that asset. Checksum files covering several
+/// files, such as `SHA256SUMS` or `checksums.txt`, verify every uploaded
+/// artifact; generated source tarballs are never listed in them.
+pub(crate) fn link_companions(assets: &mut [VersionedAsset]) {
+    let file_name = |url: &str| url.rsplit('/').next().unwrap_or(url).to_string();
+    let files = assets
+        .iter()
+        .map(|a| (file_name(&a.url), a.url.clone(), a.kind.clone()))
+        .collect::<Vec<_>>();
+
+    for asset in assets.iter_mut() {
+        if !matches!(asset.kind, AssetKind::Signature | AssetKind::Checksum) {
+            continue;
+        }
+        let name = file_name(&asset.url);
+        let lower = name.to_lowercase();
+        let parent = SIGNATURE_SUFFIXES
+            .iter()
+            .chain(CHECKSUM_SUFFIXES)
+            .find(|suffix| lower.ends_with(*suffix))
+            .map(|suffix| &name[..name.len() - suffix.len()])
+            .and_then(|stem| files.iter().find(|(file, _, _)| file == stem));
+
+        asset.verifies = match parent {
+            Some((_, url, _)) => vec![url.clone()],
+            None if asset.kind == AssetKind::Checksum => files
+                .iter()
+                .filter(|(_, _, kind)| {
+                    matches!(
+                        kind,
+                        AssetKind::Binary
+                            | AssetKind::SourceArchive
+                            | AssetKind::Installer
+                            | AssetKind::Sbom
+                            | AssetKind::Debug
+                    )
+                })
+                .map(|(_, url, _)| url.clone())
+                .collect(),
+            None => vec![],
+        };
+    }
+}
+
+/// Sorts versions newest first, merging entries that share a version string
+///
+/// Hosts assemble versions from hash maps or from several endpoints, so every
+/// host passes its result through here to return a deterministic order. The
+/// assets of duplicates are combined with [`merge_assets`].
+pub(crate) fn sort_versions(
+    versions: impl IntoIterator<Item = VersionMetadata>,
+) -> Vec<VersionMetadata> {
+    let mut merged: BTreeMap<String, VersionMetadata> = BTreeMap::new();
+    for version in versions {
+        match merged.entry(version.version.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(version);
+            }
+            Entry::Occupied(mut entry) => {
+                let existing = entry.get_mut();
+                let downloads = std::mem::take(&mut existing.downloads);
+                existing.downloads = merge_assets(downloads.into_iter().chain(version.downloads));
+                existing.release_notes = existing.release_notes.take().or(version.release_notes);
+                existing.released_at = match (existing.released_at, version.released_at) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                existing.prerelease |= version.prerelease;
+                existing.source_ref = existing.source_ref.take().or(version.source_ref);
+            }
+        }
+    }
+
+    let mut sorted = merged.into_values().collect::<Vec<_>>();
+    versioning::sort_versions(&mut sorted);
+    sorted
+}
+
+/// Picks the newest stable version, as chosen by [`versioning::latest`]
+///
+/// Falls back to the newest prerelease when every version is a prerelease.
+pub(crate) fn newest(versions: Vec<VersionMetadata>) -> Option<VersionMetadata> {
+    versioning::latest(&versions).cloned()
+}
+
+/// Builds an [`UpdateReport`] comparing `current` against `versions`
+pub(crate) fn update_report(current: &str, versions: Vec<VersionMetadata>) -> UpdateReport {
+    let known = Version::from(current);
+    let versions = sort_versions(versions);
+    let newer = versions
+        .iter()
+        .filter(|v| Version::from(v.version.as_str()) > known)
+        .cloned()
+        .collect::<Vec<_>>();
+    let latest = newest(versions);
+    UpdateReport {
+        current: current.to_string(),
+        up_to_date: latest
+            .as_ref()
+            .is_none_or(|v| Version::from(v.version.as_str()) <= known),
+        latest,
+        newer,
+    }
+}
+
+/// A matcher inspects a URL and, if it belongs to a host it knows about,
+/// returns the constructed host (or the error from constructing it)
+pub type HostMatcher = Arc<dyn Fn(&Url) -> Option<Result<Box<dyn Host>, HostError>> + Send + Sync>;
+
+/// Ordered list of matchers used to resolve URLs to hosts
+///
+/// The default registry knows about all built-in hosts and falls back to
+/// [`PlainHost`] for HTTP(S) URLs. Downstream tools can
+/// [`register`](HostRegistry::register) their own matchers, which take
+/// precedence over the built-in ones. The registry is cheap to clone, so it
+/// can be built once and shared.
+#[derive(Clone)]
+pub struct HostRegistry {
+    matchers: Vec<HostMatcher>,
+}
+
+impl HostRegistry {
+    /// Creates an empty registry without any matchers
+    pub fn new() -> Self {
+        Self { matchers: vec![] }
+    }
+
+    /// Registers a custom matcher, tried before all previously registered ones
+    pub fn register(
+        &mut self,
+        matcher: impl Fn(&Url) -> Option<Result<Box<dyn Host>, HostError>> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.matchers.insert(0, Arc::new(matcher));
+        self
+    }
+
+    /// Registers a host built by `constructor` for every URL accepted by `matcher`
+    ///
+    /// Like [`register`](HostRegistry::register), the new entry is tried before
+    /// all previously registered ones.
+    pub fn register_host(
+        &mut self,
+        matcher: impl Fn(&Url) -> bool + Send + Sync + 'static,
+        constructor: impl Fn(&Url) -> Result<Box<dyn Host>, HostError> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.register(move |url| matcher(url).then(|| constructor(url)))
+    }
+
+    /// Routes repositories on a GitHub Enterprise Server instance to [`GithubHost`]
+    ///
+    /// Without this hint such URLs fall through to [`PlainHost`].
+    pub fn register_github_enterprise(&mut self, hostname: impl Into<String>) -> &mut Self {
+        let hostname = hostname.into();
+        self.register(move |url| {
+            (url.host_str() == Some(hostname.as_str()))
+                .then(|| boxed(GithubHost::from_enterprise_url(url)))
+        })
+    }
+
+    /// Resolves a URL to the first host whose matcher accepts it
+    pub fn resolve(&self, url: &Url) -> Result<Box<dyn Host>, HostError> {
+        self.matchers
+            .iter()
+            .find_map(|matcher| matcher(url))
+            .unwrap_or_else(|| Err(HostError::Unsupported(format!("no host matches {url}"))))
+    }
+
+    fn push(&mut self, matcher: HostMatcher) {
+        self.matchers.push(matcher);
+    }
+}
+
+impl Default for HostRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.push(Arc::new(|url: &Url| {
+            (url.scheme() == "file").then(|| boxed(snapshot::SnapshotHost::from_url(url)))
+        }));
+        registry.push(host_matcher(
+            &["github.com", "codeload.github.com", "api.github.com"],
+            GithubHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["bitbucket.org"],
+            bitbucket::BitbucketHost::from_url,
+        ));
+        registry.push(host_matcher(&["gitee.com"], gitee::GiteeHost::from_url));
+        registry.push(host_matcher(
+            &["codeberg.org"],
+            forgejo::ForgejoHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["git.sr.ht"],
+            sourcehut::SourceHutHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["download.gnome.org", "ftp.gnome.org"],
+            gnome::GnomeHost::from_url,
+        ));
+        registry.push(Arc::new(|url: &Url| {
+            gnome::mirror_project(url).map(|_| boxed(gnome::GnomeHost::from_url(url)))
+        }));
+        registry.push(host_matcher(
+            &["anaconda.org", "conda.anaconda.org"],
+            anaconda::AnacondaHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["ftp.gnu.org", "ftpmirror.gnu.org"],
+            gnu::GnuHost::from_url,
+        ));
+        registry.push(host_matcher(&["hex.pm"], hexpm::HexPmHost::from_url));
+        registry.push(host_matcher(&["pecl.php.net"], pecl::PeclHost::from_url));
+        registry.push(host_matcher(
+            &["pkg.go.dev", "proxy.golang.org"],
+            gomod::GoModuleHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["ctan.org", "www.ctan.org"],
+            ctan::CtanHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["www.nuget.org", "nuget.org", "api.nuget.org"],
+            nuget::NuGetHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["download.opensuse.org"],
+            obs::ObsHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["downloads.xiph.org"],
+            xiph::XiphHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &[
+                "www.freedesktop.org",
+                "freedesktop.org",
+                "xorg.freedesktop.org",
+                "www.x.org",
+            ],
+            freedesktop::FreedesktopHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &["downloads.apache.org", "archive.apache.org"],
+            apache::ApacheHost::from_url,
+        ));
+        registry.push(host_matcher(&["download.kde.org"], kde::KdeHost::from_url));
+        registry.push(host_matcher(
+            &["download.eclipse.org"],
+            eclipse::EclipseHost::from_url,
+        ));
+        registry.push(host_matcher(
+            &[
+                "savannah.gnu.org",
+                "savannah.nongnu.org",
+                "download.savannah.gnu.org",
+                "download.savannah.nongnu.org",
+                "download-mirror.savannah.gnu.org",
+                "download-mirror.savannah.nongnu.org",
+            ],
+            savannah::SavannahHost::from_url,
+        ));
+        registry.push(Arc::new(|url: &Url| match url.scheme() {
+            "http" | "https" => Some(Ok(Box::new(PlainHost::from_url(url)) as Box<dyn Host>)),
+            scheme => Some(Err(HostError::Unsupported(format!(
+                "unsupported URL scheme: {scheme}"
+            )))),
+        }));
+        registry
+    }
+}
+
+/// Boxes the result of a host constructor for use in a [`HostMatcher`]
+fn boxed<H: Host + 'static>(host: Result<H, HostError>) -> Result<Box<dyn Host>, HostError> {
+    host.map(|h| Box::new(h) as Box<dyn Host>)
+}
+
+/// Creates a matcher constructing a host for any of the given host names
+///
+/// URLs the constructor rejects, such as paths outside the layout the host
+/// understands, are left to the matchers after it, so they end up with
+/// [`PlainHost`] rather than failing.
+fn host_matcher<H: Host + 'static>(
+    hosts: &'static [&'static str],
+    constructor: fn(&Url) -> Result<H, HostError>,
+) -> HostMatcher {
+    Arc::new(move |url: &Url| {
+        let host = url.host_str()?;
+        if !hosts.contains(&host) {
+            return None;
+        }
+        match constructor(url) {
+            Ok(host) => Some(Ok(Box::new(host) as Box<dyn Host>)),
+            Err(e) => {
+                tracing::debug!("Falling through for {}: {}", url, e);
+                None
+            }
+        }
+    })
+}
+
+/// The registry consulted by [`from_url`], starting out as the default one
+static REGISTRY: LazyLock<RwLock<HostRegistry>> =
+    LazyLock::new(|| RwLock::new(HostRegistry::default()));
+
+/// Registers a custom host with the registry used by [`from_url`]
+///
+/// URLs accepted by `matcher` are handed to `constructor` instead of any
+/// built-in host, which lets downstream tools add hosts without building
+/// and passing around their own [`HostRegistry`].
+pub fn register_host(
+    matcher: impl Fn(&Url) -> bool + Send + Sync + 'static,
+    constructor: impl Fn(&Url) -> Result<Box<dyn Host>, HostError> + Send + Sync + 'static,
+) {
+    REGISTRY
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register_host(matcher, constructor);
+}
+
+/// Resolves a URL to a host using the built-in hosts and any registered with [`register_host`]
 pub fn from_url(url: &Url) -> Result<Box<dyn Host>, HostError> {
-    match url.host_str() {
-        Some("github.com") => Ok(Box::new(GithubHost::from_url(url)?)),
-        Some("download.gnome.org") => Ok(Box::new(gnome::GnomeHost::from_url(url)?)),
-        _ => Ok(Box::new(PlainHost::from_url(url))),
+    let registry = REGISTRY.read().unwrap_or_else(|e| e.into_inner()).clone();
+    registry.resolve(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{MockResponse, MockServer};
+
+    /// Tests that response statuses map to the matching host errors
+    #[tokio::test]
+    async fn test_check_status() {
+        let server = MockServer::with_routes(vec![
+            ("/ok", MockResponse::new(200, "[]")),
+            ("/broken", MockResponse::new(502, "Bad Gateway")),
+            ("/private", MockResponse::new(403, "Forbidden")),
+            ("/gone", MockResponse::new(410, "Gone")),
+        ])
+        .await;
+
+        let get = |path: &str| reqwest::get(server.url.join(path).unwrap());
+
+        assert!(check_status(get("ok").await.unwrap()).is_ok());
+        assert!(matches!(
+            check_status(get("missing").await.unwrap()),
+            Err(HostError::NotFound { url }) if url.ends_with("/missing")
+        ));
+        assert!(matches!(
+            check_status(get("gone").await.unwrap()),
+            Err(HostError::NotFound { url }) if url.ends_with("/gone")
+        ));
+        assert!(matches!(
+            check_status(get("private").await.unwrap()),
+            Err(HostError::Unauthorized { .. })
+        ));
+        assert!(matches!(
+            check_status(get("broken").await.unwrap()),
+            Err(HostError::ApiRequest { .. })
+        ));
+    }
+
+    /// Tests that error messages name the URL they are about
+    #[tokio::test]
+    async fn test_error_messages() {
+        let url = Url::parse("https://www.nuget.org/profiles/JamesNK").unwrap();
+        let error = nuget::NuGetHost::from_url(&url).err().unwrap();
+        assert!(matches!(error, HostError::InvalidUrl { .. }));
+        assert_eq!(
+            error.to_string(),
+            "invalid URL https://www.nuget.org/profiles/JamesNK: expected a /packages/ URL"
+        );
+
+        let url = Url::parse("https://ctan.org/pkg/").unwrap();
+        let error = ctan::CtanHost::from_url(&url).err().unwrap();
+        assert!(matches!(error, HostError::ParseError { .. }));
+        assert!(
+            error.to_string().contains("https://ctan.org/pkg/"),
+            "{error}"
+        );
+
+        let server =
+            MockServer::with_routes(vec![("/broken", MockResponse::new(502, "Bad Gateway"))]).await;
+        let broken = server.url.join("broken").unwrap();
+        let error = check_status(reqwest::get(broken.clone()).await.unwrap()).unwrap_err();
+        assert!(error.to_string().contains(broken.as_str()), "{error}");
+
+        // A context naming the URL already does not get it appended again
+        let unreachable = "http://127.0.0.1:1/";
+        let error = reqwest::get(unreachable).await.unwrap_err();
+        let error = HostError::ApiRequest {
+            context: format!("failed to fetch {unreachable}"),
+            source: error,
+        };
+        assert_eq!(error.to_string().matches(unreachable).count(), 1, "{error}");
+    }
+
+    struct FixedHost;
+
+    #[async_trait]
+    impl Host for FixedHost {
+        async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+            Ok(vec![VersionMetadata::new("1.0", vec![])])
+        }
+    }
+
+    /// Tests that custom matchers take precedence over the built-in hosts
+    #[tokio::test]
+    async fn test_registry() {
+        let mut registry = HostRegistry::default();
+        registry.register(|url| {
+            (url.host_str() == Some("git.example.com"))
+                .then(|| Ok(Box::new(FixedHost) as Box<dyn Host>))
+        });
+        let shared = registry.clone();
+
+        let url = Url::parse("https://git.example.com/foo/bar").unwrap();
+        let versions = shared.resolve(&url).unwrap().versions().await.unwrap();
+        assert_eq!(versions[0].version, "1.0");
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        assert!(HostRegistry::new().resolve(&url).is_err());
+
+        // Only HTTP(S) URLs fall back to a plain directory listing
+        let url = Url::parse("rsync://rsync.example.org/pub/foo/foo-1.0.tar.gz").unwrap();
+        assert!(matches!(
+            shared.resolve(&url),
+            Err(HostError::Unsupported(message)) if message.contains("rsync")
+        ));
+        let host = PlainHost::from_url(&url);
+        assert!(matches!(
+            host.versions().await,
+            Err(HostError::Unsupported(_))
+        ));
+
+        let url = Url::parse("https://github.example.com/team/tool").unwrap();
+        assert!(HostRegistry::new()
+            .register_github_enterprise("github.example.com")
+            .resolve(&url)
+            .is_ok());
+        assert!(HostRegistry::new().resolve(&url).is_err());
+    }
+
+    /// Tests which URLs each built-in host claims and which fall through to
+    /// a plain directory listing
+    #[test]
+    fn test_routing() {
+        let registry = HostRegistry::default();
+        let mut specific = registry.clone();
+        // Drop the plain fallback so fall-through URLs fail to resolve
+        specific.matchers.pop();
+
+        // A host's constructor, the URLs it claims and those it leaves to the fallback
+        type Routes = (
+            fn(&Url) -> bool,
+            &'static [&'static str],
+            &'static [&'static str],
+        );
+        let hosts: [Routes; 10] = [
+            (
+                |url| GithubHost::from_url(url).is_ok(),
+                &["https://github.com/cli/cli"],
+                &["https://github.com/"],
+            ),
+            (
+                |url| gitee::GiteeHost::from_url(url).is_ok(),
+                &["https://gitee.com/openharmony/docs"],
+                &["https://gitee.com/openharmony"],
+            ),
+            (
+                |url| forgejo::ForgejoHost::from_url(url).is_ok(),
+                &["https://codeberg.org/dnkl/foot"],
+                &["https://codeberg.org/dnkl"],
+            ),
+            (
+                |url| gnome::GnomeHost::from_url(url).is_ok(),
+                &[
+                    "https://download.gnome.org/sources/NetworkManager/",
+                    "https://mirror.example.org/pub/GNOME/sources/glib/2.82/",
+                ],
+                &[
+                    "https://download.gnome.org/teams/releng/",
+                    "https://download.gnome.org/sources/",
+                    "https://mirror.example.org/pub/sources/NetworkManager/",
+                ],
+            ),
+            (
+                |url| gnu::GnuHost::from_url(url).is_ok(),
+                &["https://ftp.gnu.org/gnu/nano/"],
+                &["https://ftpmirror.gnu.org/pub/foo-1.0.tar.gz"],
+            ),
+            (
+                |url| hexpm::HexPmHost::from_url(url).is_ok(),
+                &[
+                    "https://hex.pm/packages/phoenix",
+                    "https://hex.pm/packages/phoenix/1.7.14",
+                ],
+                &["https://hex.pm/packages", "https://hex.pm/users/chrismccord"],
+            ),
+            (
+                |url| pecl::PeclHost::from_url(url).is_ok(),
+                &[
+                    "https://pecl.php.net/package/redis",
+                    "https://pecl.php.net/package/imagick/3.7.0",
+                ],
+                &[
+                    "https://pecl.php.net/package/",
+                    "https://pecl.php.net/user/mkoppanen",
+                ],
+            ),
+            (
+                |url| ctan::CtanHost::from_url(url).is_ok(),
+                &[
+                    "https://ctan.org/pkg/pgf",
+                    "https://www.ctan.org/pkg/pgf?lang=en",
+                ],
+                &[
+                    "https://ctan.org/pkg/",
+                    "https://ctan.org/author/tantau",
+                    "https://ctan.org/tex-archive/macros/latex/contrib/geometry/",
+                    "https://mirrors.ctan.org/macros/latex/contrib/geometry.zip",
+                ],
+            ),
+            (
+                |url| nuget::NuGetHost::from_url(url).is_ok(),
+                &[
+                    "https://www.nuget.org/packages/Newtonsoft.Json",
+                    "https://www.nuget.org/packages/Newtonsoft.Json/13.0.3",
+                    "https://api.nuget.org/v3-flatcontainer/newtonsoft.json/13.0.3/newtonsoft.json.13.0.3.nupkg",
+                ],
+                &[
+                    "https://www.nuget.org/packages/",
+                    "https://www.nuget.org/profiles/JamesNK",
+                ],
+            ),
+            (
+                |url| freedesktop::FreedesktopHost::from_url(url).is_ok(),
+                &[
+                    "https://www.freedesktop.org/software/libinput/releases/",
+                    "https://www.x.org/pub/individual/xserver/xwayland-24.1.4.tar.xz",
+                ],
+                &[
+                    "https://www.x.org/pub/individual/xserver/",
+                    "https://www.x.org/wiki/Releases/",
+                ],
+            ),
+        ];
+
+        for (parses, claimed, rejected) in hosts {
+            for url in claimed {
+                let url = Url::parse(url).unwrap();
+                assert!(parses(&url), "{url}");
+                assert!(specific.resolve(&url).is_ok(), "{url}");
+            }
+            for url in rejected {
+                let url = Url::parse(url).unwrap();
+                assert!(!parses(&url), "{url}");
+                assert!(specific.resolve(&url).is_err(), "{url}");
+                assert!(registry.resolve(&url).is_ok(), "{url}");
+            }
+        }
+    }
+
+    /// Tests that hosts registered globally are used by `from_url`
+    #[tokio::test]
+    async fn test_register_host() {
+        let url = Url::parse("https://forge.internal.example/team/tool").unwrap();
+        register_host(
+            |url| url.host_str() == Some("forge.internal.example"),
+            |_| Ok(Box::new(FixedHost)),
+        );
+        let versions = from_url(&url).unwrap().versions().await.unwrap();
+        assert_eq!(versions[0].version, "1.0");
+
+        let url = Url::parse("https://github.com/cli/cli").unwrap();
+        assert!(from_url(&url).is_ok());
+    }
+
+    /// Tests asset classification against files published by real projects
+    #[test]
+    fn test_classify_asset() {
+        let assets = [
+            ("curl-8.11.1.tar.xz", None, AssetKind::SourceArchive),
+            ("curl-8.11.1.tar.xz.asc", None, AssetKind::Signature),
+            ("gnupg-2.4.7.tar.bz2.sig", None, AssetKind::Signature),
+            ("linux-6.12.1.tar.sign", None, AssetKind::Signature),
+            ("zig-0.13.0.tar.xz.minisig", None, AssetKind::Signature),
+            ("glib-2.82.4.sha256sum", None, AssetKind::Checksum),
+            ("openssl-3.4.0.tar.gz.sha256", None, AssetKind::Checksum),
+            (
+                "ripgrep-14.1.1-x86_64-unknown-linux-musl.tar.gz.sha256",
+                None,
+                AssetKind::Checksum,
+            ),
+            ("SHA256SUMS", None, AssetKind::Checksum),
+            ("SHA512SUMS.txt", None, AssetKind::Checksum),
+            ("gh_2.63.2_checksums.txt", None, AssetKind::Checksum),
+            ("zlib-1.3.1.zip", None, AssetKind::SourceArchive),
+            ("zstd-1.5.6.tar.gz", None, AssetKind::SourceArchive),
+            (
+                "ripgrep-14.1.1-x86_64-unknown-linux-musl.tar.gz",
+                None,
+                AssetKind::Binary,
+            ),
+            ("helix-24.07-aarch64-macos.tar.xz", None, AssetKind::Binary),
+            (
+                "bat-v0.24.0-x86_64-pc-windows-msvc.zip",
+                None,
+                AssetKind::Binary,
+            ),
+            ("hyperfine", None, AssetKind::Binary),
+            ("fd_10.2.0_amd64.deb", None, AssetKind::Installer),
+            ("Obsidian-1.7.7.AppImage", None, AssetKind::Installer),
+            ("syft_1.18.1_windows_amd64.msi", None, AssetKind::Installer),
+            ("wezterm-20240203.sbom.spdx.json", None, AssetKind::Sbom),
+            ("deno-x86_64-pc-windows-msvc.pdb", None, AssetKind::Debug),
+            ("bun-linux-x64-profile.debug", None, AssetKind::Debug),
+            // Content types reported by the host take precedence over the name
+            (
+                "KEYS.gpg",
+                Some("application/pgp-signature"),
+                AssetKind::Signature,
+            ),
+            (
+                "setup.bin",
+                Some("application/x-msdownload"),
+                AssetKind::Installer,
+            ),
+            (
+                "zstd-1.5.6.tar.gz",
+                Some("application/gzip"),
+                AssetKind::SourceArchive,
+            ),
+        ];
+        for (name, content_type, kind) in assets {
+            assert_eq!(classify_asset(name, content_type), kind, "{name}");
+        }
+    }
+
+    /// Tests that shuffled and duplicated versions come out in one order, newest first
+    #[test]
+    fn test_sort_versions() {
+        let asset = |url: &str| VersionedAsset::new(url, AssetKind::Release);
+        let versions = vec![
+            VersionMetadata::new("1.9", vec![asset("https://example.com/foo-1.9.tar.gz")]),
+            VersionMetadata::new("2.0-rc1", vec![]),
+            VersionMetadata::new("1.10", vec![asset("https://example.com/foo-1.10.tar.xz")]),
+            VersionMetadata::new("2.0", vec![]),
+            VersionMetadata::new("1.10", vec![asset("https://example.com/foo-1.10.tar.gz")]),
+            VersionMetadata::new("1.2", vec![]),
+        ];
+        let expected = ["2.0", "2.0-rc1", "1.10", "1.9", "1.2"];
+
+        let mut shuffled = versions.clone();
+        shuffled.reverse();
+        shuffled.swap(0, 3);
+        for input in [versions, shuffled] {
+            let sorted = sort_versions(input);
+            let order = sorted
+                .iter()
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(order, expected);
+            assert_eq!(sorted[2].downloads.len(), 2);
+        }
+    }
+
+    /// Tests that the latest version skips prereleases unless there is nothing else
+    #[test]
+    fn test_newest() {
+        let prerelease = VersionMetadata {
+            prerelease: true,
+            ..VersionMetadata::new("2.0-rc1", vec![])
+        };
+        let stable = VersionMetadata::new("1.9", vec![]);
+        let latest = newest(vec![prerelease.clone(), stable.clone()]);
+        assert_eq!(latest.map(|v| v.version).as_deref(), Some("1.9"));
+        let latest = newest(vec![prerelease]);
+        assert_eq!(latest.map(|v| v.version).as_deref(), Some("2.0-rc1"));
+        assert!(newest(vec![]).is_none());
+    }
+
+    /// Tests update reports for up-to-date and outdated versions
+    #[tokio::test]
+    async fn test_check_update() {
+        let report = FixedHost.check_update("1.0").await.unwrap();
+        assert!(report.up_to_date);
+        assert!(report.newer.is_empty());
+        assert_eq!(report.latest.map(|v| v.version).as_deref(), Some("1.0"));
+
+        let report = update_report(
+            "v1.2.0",
+            vec![
+                VersionMetadata::new("1.1.0", vec![]),
+                VersionMetadata::new("1.2.0", vec![]),
+                VersionMetadata::new("1.3.0", vec![]),
+                VersionMetadata {
+                    prerelease: true,
+                    ..VersionMetadata::new("1.4.0-rc1", vec![])
+                },
+            ],
+        );
+        assert!(!report.up_to_date);
+        assert_eq!(report.latest.map(|v| v.version).as_deref(), Some("1.3.0"));
+        let newer = report
+            .newer
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(newer, vec!["1.4.0-rc1", "1.3.0"]);
+
+        // A newer prerelease alone does not make the current version outdated
+        let report = update_report(
+            "1.3.0",
+            vec![
+                VersionMetadata::new("1.3.0", vec![]),
+                VersionMetadata {
+                    prerelease: true,
+                    ..VersionMetadata::new("1.4.0-rc1", vec![])
+                },
+            ],
+        );
+        assert!(report.up_to_date);
+        assert_eq!(report.newer.len(), 1);
+    }
+
+    /// Tests that the default single version lookup searches all versions
+    #[tokio::test]
+    async fn test_version_fallback() {
+        let found = FixedHost.version("1.0").await.unwrap();
+        assert_eq!(found.map(|v| v.version).as_deref(), Some("1.0"));
+        assert!(FixedHost.version("2.0").await.unwrap().is_none());
     }
 }
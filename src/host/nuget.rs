@@ -0,0 +1,186 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, Host, HostError,
+    DEFAULT_USER_AGENT,
+};
+
+/// Base URL of the NuGet flat container API
+const FLAT_CONTAINER_URL: &str = "https://api.nuget.org/v3-flatcontainer";
+
+/// A Host implementation for .NET packages published on nuget.org
+///
+/// Versions are listed through the flat container API, which addresses
+/// packages by their lowercased id.
+pub struct NuGetHost {
+    /// The package id as given in the URL (i.e. "Newtonsoft.Json")
+    pub id: String,
+
+    /// The complete URL the host was created from
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// Response format for the flat container `index.json` endpoint
+#[derive(Deserialize, Debug)]
+pub struct NuGetIndexResponse {
+    /// Every published version of the package, oldest first
+    pub versions: Vec<String>,
+}
+
+impl NuGetHost {
+    /// Creates a new NuGetHost instance from a URL
+    ///
+    /// Both `https://www.nuget.org/packages/<id>[/<version>]` package pages and
+    /// `https://api.nuget.org/v3-flatcontainer/<id>/...` download URLs are accepted.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        let prefix = match url.host_str() {
+            Some("api.nuget.org") => "v3-flatcontainer",
+            _ => "packages",
+        };
+        if path.first() != Some(&prefix) {
+            return Err(HostError::invalid_url(
+                url,
+                format!("expected a /{prefix}/ URL"),
+            ));
+        }
+        let id = path
+            .get(1)
+            .ok_or_else(|| HostError::parse_error(url, "missing package id in NuGet URL"))?;
+
+        debug!("Created NuGetHost for {}", id);
+        Ok(Self {
+            id: id.to_string(),
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Returns the download URL of the `.nupkg` for `version`
+    fn package_url(&self, version: &str) -> String {
+        let id = self.id.to_lowercase();
+        let version = version.to_lowercase();
+        format!("{FLAT_CONTAINER_URL}/{id}/{version}/{id}.{version}.nupkg")
+    }
+
+    /// Maps the versions of an index response to version metadata
+    fn collect(&self, index: NuGetIndexResponse) -> Vec<VersionMetadata> {
+        index
+            .versions
+            .into_iter()
+            .map(|version| VersionMetadata {
+                prerelease: is_prerelease(&version),
+                ..VersionMetadata::new(
+                    version.clone(),
+                    vec![VersionedAsset::new(
+                        self.package_url(&version),
+                        AssetKind::Release,
+                    )],
+                )
+            })
+            .collect()
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for NuGetHost {
+    #[tracing::instrument(skip(self), fields(project = %self.id))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let uri = format!("{FLAT_CONTAINER_URL}/{}/index.json", self.id.to_lowercase());
+        debug!("Fetching package index from: {}", uri);
+        let response = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch package index".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .json::<NuGetIndexResponse>()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to parse package index from {uri}"),
+                source: e,
+            })?;
+
+        info!("Found {} versions of {}", response.versions.len(), self.id);
+        Ok(sort_versions(self.collect(response)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the package ID is taken from gallery and flat container URLs
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://www.nuget.org/packages/Newtonsoft.Json").unwrap();
+        let host = NuGetHost::from_url(&url).unwrap();
+        assert_eq!(host.id, "Newtonsoft.Json");
+
+        let url = Url::parse("https://www.nuget.org/packages/Newtonsoft.Json/13.0.3").unwrap();
+        assert_eq!(NuGetHost::from_url(&url).unwrap().id, "Newtonsoft.Json");
+
+        let url = Url::parse(
+            "https://api.nuget.org/v3-flatcontainer/newtonsoft.json/13.0.3/newtonsoft.json.13.0.3.nupkg",
+        )
+        .unwrap();
+        assert_eq!(NuGetHost::from_url(&url).unwrap().id, "newtonsoft.json");
+    }
+
+    /// Tests that downloads use the lowercased ID and version of the flat container
+    #[test]
+    fn test_flat_container() {
+        let url = Url::parse("https://www.nuget.org/packages/Newtonsoft.Json").unwrap();
+        let host = NuGetHost::from_url(&url).unwrap();
+        let index: NuGetIndexResponse =
+            serde_json::from_str(r#"{"versions": ["13.0.3", "13.0.4-Beta1"]}"#).unwrap();
+
+        let versions = host.collect(index);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://api.nuget.org/v3-flatcontainer/newtonsoft.json/13.0.3/newtonsoft.json.13.0.3.nupkg"
+        );
+        assert_eq!(versions[1].version, "13.0.4-Beta1");
+        assert_eq!(
+            versions[1].downloads[0].url,
+            "https://api.nuget.org/v3-flatcontainer/newtonsoft.json/13.0.4-beta1/newtonsoft.json.13.0.4-beta1.nupkg"
+        );
+        assert!(!versions[0].prerelease);
+        assert!(versions[1].prerelease);
+    }
+}
@@ -0,0 +1,362 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use flate2::write::GzDecoder;
+use regex::Regex;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, Host, HostError,
+    DEFAULT_USER_AGENT,
+};
+
+/// A Host implementation for packages published through the openSUSE Build Service
+///
+/// OBS repositories on `download.opensuse.org/repositories/...` carry rpm-md
+/// metadata: `repodata/repomd.xml` points at a gzip compressed `primary.xml.gz`
+/// listing every package in the repository.
+pub struct ObsHost {
+    /// The root URL of the repository (the directory holding `repodata/`)
+    pub repository: Url,
+
+    /// The package name to look up
+    pub package: String,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// A package entry parsed from `primary.xml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObsPackage {
+    /// The package name
+    pub name: String,
+    /// The package architecture ("src" for source RPMs)
+    pub arch: String,
+    /// The upstream version, without the distro release suffix
+    pub version: String,
+    /// The distro release (i.e. "1.1")
+    pub release: String,
+    /// Location of the rpm relative to the repository root
+    pub location: String,
+    /// Build time as a unix timestamp
+    pub build_time: Option<i64>,
+}
+
+impl ObsHost {
+    /// Creates a new ObsHost for `package` in the repository at `repository`
+    pub fn new(repository: Url, package: impl Into<String>) -> Self {
+        Self {
+            repository,
+            package: package.into(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Creates a new ObsHost instance from the URL of an rpm in an OBS repository
+    ///
+    /// The repository root is the parent of the architecture directory holding
+    /// the rpm, and the package name is taken from the rpm file name.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let mut segments = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<&str>>();
+        let file = segments
+            .pop()
+            .filter(|f| f.ends_with(".rpm"))
+            .ok_or_else(|| HostError::parse_error(url, "expected an rpm URL"))?;
+        let package = package_from_filename(file)
+            .ok_or_else(|| HostError::parse_error(url, format!("invalid rpm file name: {file}")))?;
+        // Drop the architecture directory
+        segments.pop();
+
+        let mut repository = url.clone();
+        repository.set_path(&format!("{}/", segments.join("/")));
+        repository.set_query(None);
+        repository.set_fragment(None);
+        Ok(Self::new(repository, package))
+    }
+
+    /// Fetches `repomd.xml` and returns the location of the primary metadata
+    async fn primary_location(&self) -> Result<Url, HostError> {
+        let repomd = self.join("repodata/repomd.xml")?;
+        debug!("Fetching repository metadata from: {}", repomd);
+        let body = self
+            .retry
+            .send(http_client(&self.user_agent).get(repomd.as_str()))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch repomd.xml".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to read {repomd}"),
+                source: e,
+            })?;
+
+        let location = parse_primary_location(&body)
+            .ok_or_else(|| HostError::parse_error(&repomd, "no primary metadata in repomd.xml"))?;
+        self.join(&location)
+    }
+
+    /// Streams and decompresses `primary.xml.gz`, keeping only entries for our package
+    async fn fetch_packages(&self, primary: &Url) -> Result<Vec<ObsPackage>, HostError> {
+        debug!("Fetching primary metadata from: {}", primary);
+        let mut response = self
+            .retry
+            .send(http_client(&self.user_agent).get(primary.as_str()))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch primary metadata".into(),
+                source: e,
+            })
+            .and_then(check_status)?;
+
+        let mut decoder = GzDecoder::new(PackageScanner::new(&self.package));
+        while let Some(chunk) = response.chunk().await.map_err(|e| HostError::ApiResponse {
+            context: format!("failed to read primary metadata from {primary}"),
+            source: e,
+        })? {
+            decoder
+                .write_all(&chunk)
+                .map_err(|e| HostError::parse_error(primary, e))?;
+        }
+        let scanner = decoder
+            .finish()
+            .map_err(|e| HostError::parse_error(primary, e))?;
+
+        Ok(scanner.packages)
+    }
+
+    fn join(&self, path: &str) -> Result<Url, HostError> {
+        self.repository
+            .join(path)
+            .map_err(|e| HostError::invalid_url(format!("{}{path}", self.repository), e))
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// Derives the package name from an rpm file name (`<name>-<version>-<release>.<arch>.rpm`)
+fn package_from_filename(file: &str) -> Option<String> {
+    let mut parts = file.rsplitn(3, '-');
+    let _release = parts.next()?;
+    let _version = parts.next()?;
+    parts.next().map(String::from)
+}
+
+/// Finds the location of the primary metadata in `repomd.xml`
+fn parse_primary_location(repomd: &str) -> Option<String> {
+    let data = Regex::new(r#"(?s)<data\s+type="primary">(.*?)</data>"#).ok()?;
+    let location = Regex::new(r#"<location\s+href="([^"]+)""#).ok()?;
+    let primary = data.captures(repomd)?.get(1)?.as_str();
+    Some(location.captures(primary)?.get(1)?.as_str().to_string())
+}
+
+/// Incremental `primary.xml` scanner fed by the gzip decoder
+///
+/// Decompressed bytes are buffered only until a complete `<package>` element is
+/// available, so the full metadata never needs to be held in memory.
+struct PackageScanner {
+    name: String,
+    buffer: Vec<u8>,
+    packages: Vec<ObsPackage>,
+    name_re: Regex,
+    arch_re: Regex,
+    version_re: Regex,
+    location_re: Regex,
+    time_re: Regex,
+}
+
+impl PackageScanner {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            buffer: vec![],
+            packages: vec![],
+            name_re: Regex::new(r"<name>([^<]+)</name>").unwrap(),
+            arch_re: Regex::new(r"<arch>([^<]+)</arch>").unwrap(),
+            version_re: Regex::new(r#"<version\s[^>]*ver="([^"]+)"\s+rel="([^"]+)""#).unwrap(),
+            location_re: Regex::new(r#"<location\s[^>]*href="([^"]+)""#).unwrap(),
+            time_re: Regex::new(r#"<time\s[^>]*build="(\d+)""#).unwrap(),
+        }
+    }
+
+    /// Parses and removes every complete package element from the buffer
+    fn drain(&mut self) {
+        const END: &[u8] = b"</package>";
+        while let Some(pos) = self.buffer.windows(END.len()).position(|w| w == END) {
+            let element = self.buffer.drain(..pos + END.len()).collect::<Vec<u8>>();
+            let element = String::from_utf8_lossy(&element);
+            if let Some(package) = self.parse(&element) {
+                self.packages.push(package);
+            }
+        }
+    }
+
+    fn parse(&self, element: &str) -> Option<ObsPackage> {
+        let capture = |re: &Regex, i: usize| {
+            re.captures(element)
+                .and_then(|c| c.get(i))
+                .map(|m| m.as_str().to_string())
+        };
+
+        let name = capture(&self.name_re, 1)?;
+        if name != self.name {
+            return None;
+        }
+        Some(ObsPackage {
+            name,
+            arch: capture(&self.arch_re, 1)?,
+            version: capture(&self.version_re, 1)?,
+            release: capture(&self.version_re, 2)?,
+            location: capture(&self.location_re, 1)?,
+            build_time: capture(&self.time_re, 1).and_then(|t| t.parse().ok()),
+        })
+    }
+}
+
+impl Write for PackageScanner {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        self.drain();
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Host for ObsHost {
+    #[tracing::instrument(skip(self), fields(project = %self.package))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let primary = self.primary_location().await?;
+        let packages = self.fetch_packages(&primary).await?;
+
+        // Prefer source RPMs, but fall back to binaries for repositories without them
+        let sources = packages.iter().any(|p| p.arch == "src");
+        let mut versions: BTreeMap<String, VersionMetadata> = BTreeMap::new();
+        for package in packages.iter().filter(|p| !sources || p.arch == "src") {
+            let released_at = package
+                .build_time
+                .and_then(|t| DateTime::from_timestamp(t, 0));
+            let metadata = versions
+                .entry(package.version.clone())
+                .or_insert_with(|| VersionMetadata::new(package.version.clone(), vec![]));
+            metadata.downloads.push(VersionedAsset {
+                url: self.join(&package.location)?.to_string(),
+                kind: AssetKind::Release,
+                released_at,
+                updated_at: None,
+                checksum: None,
+                size: None,
+                download_count: None,
+                verifies: vec![],
+            });
+            metadata.released_at = metadata.released_at.max(released_at);
+        }
+
+        info!("Found {} versions of {}", versions.len(), self.package);
+        Ok(sort_versions(versions.into_values()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse(
+            "https://download.opensuse.org/repositories/home:foo/openSUSE_Tumbleweed/src/bar-1.2.3-1.1.src.rpm",
+        )
+        .unwrap();
+        let host = ObsHost::from_url(&url).unwrap();
+        assert_eq!(host.package, "bar");
+        assert_eq!(
+            host.repository.as_str(),
+            "https://download.opensuse.org/repositories/home:foo/openSUSE_Tumbleweed/"
+        );
+
+        let url = Url::parse("https://download.opensuse.org/repositories/home:foo/").unwrap();
+        assert!(ObsHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_parse_primary_location() {
+        let repomd = r#"<repomd>
+            <data type="filelists"><location href="repodata/abc-filelists.xml.gz"/></data>
+            <data type="primary"><location href="repodata/def-primary.xml.gz"/></data>
+        </repomd>"#;
+        assert_eq!(
+            parse_primary_location(repomd).as_deref(),
+            Some("repodata/def-primary.xml.gz")
+        );
+    }
+
+    #[test]
+    fn test_scan_compressed() {
+        let primary = r#"<metadata packages="3">
+            <package type="rpm"><name>bar</name><arch>src</arch>
+              <version epoch="0" ver="1.2.3" rel="1.1"/><time file="1" build="1700000000"/>
+              <location href="src/bar-1.2.3-1.1.src.rpm"/></package>
+            <package type="rpm"><name>bar-devel</name><arch>x86_64</arch>
+              <version epoch="0" ver="1.2.3" rel="1.1"/>
+              <location href="x86_64/bar-devel-1.2.3-1.1.x86_64.rpm"/></package>
+            <package type="rpm"><name>bar</name><arch>x86_64</arch>
+              <version epoch="0" ver="1.2.3" rel="1.1"/>
+              <location href="x86_64/bar-1.2.3-1.1.x86_64.rpm"/></package>
+        </metadata>"#;
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(primary.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        // Feed the decoder in small chunks like a network stream would
+        let mut decoder = GzDecoder::new(PackageScanner::new("bar"));
+        for chunk in compressed.chunks(16) {
+            decoder.write_all(chunk).unwrap();
+        }
+        let packages = decoder.finish().unwrap().packages;
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].arch, "src");
+        assert_eq!(packages[0].version, "1.2.3");
+        assert_eq!(packages[0].release, "1.1");
+        assert_eq!(packages[0].build_time, Some(1700000000));
+    }
+}
@@ -0,0 +1,213 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use regex::Regex;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, Host, HostError,
+    DEFAULT_USER_AGENT,
+};
+
+/// Base URL of the PECL website, REST API and downloads
+const PECL_URL: &str = "https://pecl.php.net";
+
+/// A Host implementation for PHP extensions published on PECL
+///
+/// Versions come from the `allreleases.xml` REST document, which lists each
+/// release with its stability but no release date.
+pub struct PeclHost {
+    /// The extension name (i.e. "redis")
+    pub package: String,
+
+    /// The complete URL the host was created from
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+/// A release listed in `allreleases.xml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeclRelease {
+    /// The release version (i.e. "6.1.0RC2")
+    pub version: String,
+    /// The release stability: "stable", "beta", "alpha", "devel" or "snapshot"
+    pub stability: String,
+}
+
+impl PeclHost {
+    /// Creates a new PeclHost instance from a `https://pecl.php.net/package/<name>` URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        if path.first() != Some(&"package") {
+            return Err(HostError::invalid_url(url, "expected a /package/ URL"));
+        }
+        let package = path
+            .get(1)
+            .ok_or_else(|| HostError::parse_error(url, "missing extension in PECL URL"))?;
+
+        debug!("Created PeclHost for {}", package);
+        Ok(Self {
+            package: package.to_string(),
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Maps the releases of an `allreleases.xml` document to version metadata
+    ///
+    /// Any stability other than "stable" marks a prerelease.
+    fn collect(&self, releases: Vec<PeclRelease>) -> Vec<VersionMetadata> {
+        releases
+            .into_iter()
+            .map(|release| {
+                let url = format!("{PECL_URL}/get/{}-{}.tgz", self.package, release.version);
+                VersionMetadata {
+                    prerelease: !release.stability.eq_ignore_ascii_case("stable")
+                        || is_prerelease(&release.version),
+                    ..VersionMetadata::new(
+                        release.version,
+                        vec![VersionedAsset::new(url, AssetKind::Release)],
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// An `<r>` release entry of an `allreleases.xml` document
+static RELEASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<r>(.*?)</r>").unwrap());
+
+/// The `<v>` version of a release entry
+static VERSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<v>\s*(.*?)\s*</v>").unwrap());
+
+/// The `<s>` stability of a release entry
+static STABILITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<s>\s*(.*?)\s*</s>").unwrap());
+
+/// Parses the `<r>` entries of an `allreleases.xml` document
+fn parse_releases(body: &str) -> Vec<PeclRelease> {
+    RELEASE
+        .captures_iter(body)
+        .filter_map(|release| {
+            let release = release.get(1)?.as_str();
+            let version = VERSION.captures(release)?.get(1)?.as_str();
+            let stability = STABILITY
+                .captures(release)
+                .and_then(|c| c.get(1))
+                .map_or("stable", |s| s.as_str());
+            Some(PeclRelease {
+                version: version.to_string(),
+                stability: stability.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Host for PeclHost {
+    #[tracing::instrument(skip(self), fields(project = %self.package))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let uri = format!(
+            "{PECL_URL}/rest/r/{}/allreleases.xml",
+            self.package.to_lowercase()
+        );
+        debug!("Fetching releases from: {}", uri);
+        let body = self
+            .retry
+            .send(http_client(&self.user_agent).get(&uri))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch releases".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to read {uri}"),
+                source: e,
+            })?;
+
+        let releases = parse_releases(&body);
+        info!("Found {} versions of {}", releases.len(), self.package);
+        Ok(sort_versions(self.collect(releases)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that the package name is taken from package and release pages
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://pecl.php.net/package/redis").unwrap();
+        assert_eq!(PeclHost::from_url(&url).unwrap().package, "redis");
+
+        let url = Url::parse("https://pecl.php.net/package/imagick/3.7.0").unwrap();
+        assert_eq!(PeclHost::from_url(&url).unwrap().package, "imagick");
+    }
+
+    /// Tests that the REST release list is parsed and its stability marks prereleases
+    #[test]
+    fn test_stability() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <a xmlns="http://pear.php.net/dtd/rest.allreleases">
+             <p>redis</p>
+             <c>pecl.php.net</c>
+             <r><v>6.1.0</v><s>stable</s></r>
+             <r><v>6.1.0RC2</v><s>beta</s></r>
+             <r><v>2.2.0</v><s>alpha</s></r>
+            </a>"#;
+        let releases = parse_releases(body);
+        assert_eq!(releases.len(), 3);
+        assert_eq!(
+            releases[1],
+            PeclRelease {
+                version: "6.1.0RC2".to_string(),
+                stability: "beta".to_string(),
+            }
+        );
+
+        let url = Url::parse("https://pecl.php.net/package/redis").unwrap();
+        let versions = PeclHost::from_url(&url).unwrap().collect(releases);
+        assert_eq!(versions[0].version, "6.1.0");
+        assert!(!versions[0].prerelease);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://pecl.php.net/get/redis-6.1.0.tgz"
+        );
+        assert!(versions[1].prerelease);
+        // Stability marks prereleases whose version does not look like one
+        assert!(versions[2].prerelease);
+    }
+}
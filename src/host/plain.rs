@@ -2,14 +2,19 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use async_trait::async_trait;
-use url::Url;
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+use url::{Position, Url};
 
-use crate::{versioning::VersionExtractor, AssetKind, VersionMetadata, VersionedAsset};
+use crate::{versioning::VersionExtractor, VersionMetadata, VersionedAsset};
 
-use super::{Host, HostError};
+use super::{
+    check_status, http_client, listing, retry::RetryPolicy, sort_versions, Host, HostError,
+    DEFAULT_USER_AGENT,
+};
 
 /// Fallback host implementation for plain URLs. Used when no other host implementation
 /// matches the provided URL format. Simply stores the raw URL and path information
@@ -21,7 +26,20 @@ pub struct PlainHost {
     /// The complete URL of the repository
     pub url: Url,
 
+    /// The directory holding the file, or the URL itself when it ends in `/`
     pub directory: String,
+
+    /// Whether to also scan the parent directory and its version subdirectories
+    pub scan_parent: bool,
+
+    /// Whether to send a `HEAD` request per asset to read its size and modification time
+    pub asset_metadata: bool,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
 }
 
 impl PlainHost {
@@ -35,101 +53,494 @@ impl PlainHost {
     pub fn from_url(url: &Url) -> Self {
         let path = url.path();
         let path = path.trim_start_matches('/');
-        // collect the path but not the last element
-        let mut segments: Vec<String> = url
-            .path_segments()
-            .map(|segments| segments.map(String::from).collect())
-            .unwrap_or_default();
-        segments.pop();
-        let directory = segments.join("/");
+        // A trailing slash means the URL already names a directory
+        let directory = if path.ends_with('/') || path.is_empty() {
+            path.trim_end_matches('/')
+        } else {
+            path.rsplit_once('/')
+                .map(|(dir, _)| dir)
+                .unwrap_or_default()
+        };
 
         Self {
             path: path.to_string(),
             url: url.clone(),
-            directory,
+            directory: directory.to_string(),
+            scan_parent: false,
+            asset_metadata: false,
+            retry: RetryPolicy::default(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
         }
     }
-}
 
-#[async_trait]
-impl Host for PlainHost {
-    /// Not implemented for PlainHost - returns unimplemented error
-    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+    /// Also scans the parent directory, descending into its version subdirectories
+    ///
+    /// Useful for GNOME-style mirrors where `/sources/proj/46/` sits next to
+    /// other series directories under `/sources/proj/`.
+    pub fn with_scan_parent(self, scan_parent: bool) -> Self {
+        Self {
+            scan_parent,
+            ..self
+        }
+    }
+
+    /// Reads `Content-Length` and `Last-Modified` of every asset with a `HEAD` request
+    ///
+    /// Directory listings carry no dates, so this fills in `updated_at` and `size`
+    /// at the cost of one extra request per asset.
+    pub fn with_asset_metadata(self, asset_metadata: bool) -> Self {
+        Self {
+            asset_metadata,
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Fills in the size and modification time of `asset` from a `HEAD` request
+    async fn enrich(&self, client: &reqwest::Client, asset: &mut VersionedAsset) {
+        let response = client
+            .head(&asset.url)
+            .send()
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: format!("failed to fetch {}", asset.url),
+                source: e,
+            })
+            .and_then(check_status);
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to read asset metadata: {}", e);
+                return;
+            }
+        };
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+        };
+        asset.size = header(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse().ok());
+        asset.updated_at = header(reqwest::header::LAST_MODIFIED)
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            .map(|d| d.with_timezone(&Utc));
+    }
+
+    /// Returns the URL of a directory on this host
+    fn directory_url(&self, directory: &str) -> Result<Url, HostError> {
+        let directory = if directory.is_empty() {
+            String::new()
+        } else {
+            format!("{directory}/")
+        };
         let url = format!(
-            "{}://{}/{}/",
+            "{}://{}/{}",
             self.url.scheme(),
-            self.url.host().map(|s| s.to_string()).unwrap_or_default(),
-            self.directory
+            &self.url[Position::BeforeHost..Position::AfterPort],
+            directory
         );
-        let client = reqwest::Client::new()
-            .get(&url)
-            .header("User-Agent", "upstreams-rs".to_string())
-            .send()
+        Url::parse(&url).map_err(|e| HostError::invalid_url(&url, e))
+    }
+
+    /// Returns the project name to match release files against
+    ///
+    /// Directory URLs carry no file name, so the innermost directory that does
+    /// not look like a version is used instead.
+    fn project_name(&self, matcher: &VersionExtractor) -> Result<String, HostError> {
+        if let Ok(m) = matcher.extract(self.url.as_ref()) {
+            return Ok(m.name);
+        }
+        self.directory
+            .rsplit('/')
+            .find(|s| !s.is_empty() && !s.starts_with(|c: char| c.is_ascii_digit()))
+            .map(String::from)
+            .ok_or_else(|| HostError::parse_error(&self.url, "no project name"))
+    }
+
+    /// Fetches and parses the checksum manifest at `url`
+    async fn fetch_manifest(&self, url: &Url) -> Result<BTreeMap<String, String>, HostError> {
+        debug!("Fetching checksum manifest: {}", url);
+        let request = http_client(&self.user_agent).get(url.as_str());
+        let body = self
+            .retry
+            .send(request)
             .await
             .map_err(|e| HostError::ApiRequest {
-                context: "failed to fetch directory listing".into(),
+                context: format!("failed to fetch {url}"),
+                source: e,
+            })
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: format!("failed to read {url}"),
                 source: e,
             })?;
+        Ok(listing::parse_checksums(&body, "sha256"))
+    }
 
-        let body = client.text().await.map_err(|e| HostError::ApiRequest {
-            context: "failed to read directory listing".into(),
-            source: e,
-        })?;
+    /// Fetches the listing at `url` and adds the files of `project` to `versions`
+    ///
+    /// Checksum manifests found in the listing are added to `manifests`.
+    async fn scan(
+        &self,
+        url: &Url,
+        project: &str,
+        matcher: &VersionExtractor,
+        versions: &mut HashMap<String, BTreeSet<VersionedAsset>>,
+        manifests: &mut Vec<Url>,
+    ) -> Result<(), HostError> {
+        let request = http_client(&self.user_agent).get(url.as_str());
+        let body = self
+            .retry
+            .send(request)
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch directory listing".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: format!("failed to read {url}"),
+                source: e,
+            })?;
 
         let doc = scraper::Html::parse_document(&body);
-        let selector =
-            scraper::Selector::parse("a").map_err(|e| HostError::ParseError(e.to_string()))?;
+        let selector = scraper::Selector::parse("a").map_err(|e| HostError::parse_error(url, e))?;
 
+        for element in doc.select(&selector) {
+            let href = element.value().attr("href").unwrap_or_default();
+            let file = href.rsplit('/').next().unwrap_or_default();
+            if listing::is_sha256_manifest(file) {
+                if let Ok(manifest) = url.join(href) {
+                    manifests.push(manifest);
+                }
+                continue;
+            }
+            // Signatures and checksums share the version of the file they belong to
+            let (artifact, _) = listing::split_companion(href);
+            let Ok(m) = matcher.extract(artifact) else {
+                continue;
+            };
+            if m.name != project {
+                continue;
+            }
+            let Ok(full_url) = url.join(href) else {
+                continue;
+            };
+
+            versions
+                .entry(m.version)
+                .or_default()
+                .insert(VersionedAsset::new(
+                    full_url.to_string(),
+                    listing::classify_release_file(project, file),
+                ));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Host for PlainHost {
+    /// Lists the directory holding the URL and collects files of the same project
+    #[tracing::instrument(skip(self), fields(url = %self.url))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        if !matches!(self.url.scheme(), "http" | "https") {
+            return Err(HostError::Unsupported(format!(
+                "unsupported URL scheme: {}",
+                self.url.scheme()
+            )));
+        }
         let matcher = VersionExtractor::new();
-        let match_us = matcher
-            .extract(self.url.as_ref())
-            .map_err(|e| HostError::ParseError(e.to_string()))?;
+        let project = self.project_name(&matcher)?;
+        let directory = self.directory_url(&self.directory)?;
 
         let mut versions = HashMap::new();
-        for element in doc.select(&selector) {
-            let href = element.value().attr("href").unwrap_or_default();
+        let mut manifests = vec![];
+        self.scan(
+            &directory,
+            &project,
+            &matcher,
+            &mut versions,
+            &mut manifests,
+        )
+        .await?;
 
-            let mut downloads = BTreeSet::new();
-            if let Ok(m) = matcher.extract(href) {
-                if m.name == match_us.name {
-                    // Construct full URL
-                    let full_url = if href.starts_with("http") {
-                        href.to_string()
-                    } else {
-                        format!(
-                            "{}/{}/{}",
-                            self.url.host().map(|s| s.to_string()).unwrap_or_default(),
-                            self.directory,
-                            href.trim_start_matches('.')
+        if self.scan_parent {
+            if let Some((parent, _)) = self.directory.rsplit_once('/') {
+                let parent = self.directory_url(parent)?;
+                for entry in
+                    listing::list_directory_as(&parent, &self.user_agent, &self.retry).await?
+                {
+                    if entry.url != directory && listing::is_version_dir(&project, &entry) {
+                        debug!("Scanning sibling directory: {}", entry.url);
+                        self.scan(
+                            &entry.url,
+                            &project,
+                            &matcher,
+                            &mut versions,
+                            &mut manifests,
                         )
-                    };
-
-                    downloads.insert(VersionedAsset {
-                        url: full_url,
-                        kind: AssetKind::Release,
-                        released_at: None,
-                        updated_at: None,
-                    });
-
-                    versions
-                        .entry(m.version)
-                        .or_insert_with(BTreeSet::new)
-                        .extend(downloads);
+                        .await?;
+                    }
                 }
             }
         }
 
+        let client = http_client(&self.user_agent);
         let mut versions_set = vec![];
         for (version, downloads) in versions.iter() {
+            let mut downloads = downloads.iter().cloned().collect::<Vec<_>>();
+            if self.asset_metadata {
+                for asset in downloads.iter_mut() {
+                    self.enrich(&client, asset).await;
+                }
+            }
             let metadata = VersionMetadata {
                 version: version.to_string(),
-                downloads: downloads.iter().cloned().collect(),
+                downloads,
                 release_notes: None,
                 released_at: None,
+                prerelease: false,
+                source_ref: None,
+                tag: None,
             };
             versions_set.push(metadata);
         }
 
-        Ok(versions_set)
+        for manifest in manifests {
+            match self.fetch_manifest(&manifest).await {
+                Ok(sums) => listing::attach_checksums(&mut versions_set, &sums),
+                Err(e) => warn!("Failed to fetch checksum manifest {}: {}", manifest, e),
+            }
+        }
+
+        Ok(sort_versions(versions_set))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        host::mock::{MockResponse, MockServer},
+        AssetKind,
+    };
+
+    /// Tests that a missing directory is reported as not found
+    #[tokio::test]
+    async fn test_not_found() {
+        let server = MockServer::with_routes(vec![(
+            "/exists/",
+            MockResponse::new(200, r#"<a href="foo-1.0.tar.gz">foo-1.0.tar.gz</a>"#),
+        )])
+        .await;
+
+        let url = server.url.join("exists/foo-1.0.tar.gz").unwrap();
+        let versions = PlainHost::from_url(&url).versions().await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(
+            server.requests()[0].header("user-agent"),
+            Some(DEFAULT_USER_AGENT)
+        );
+
+        let host = PlainHost::from_url(&server.url.join("exists/foo-1.0.tar.gz").unwrap())
+            .with_user_agent("distro-bot/1.0");
+        host.versions().await.unwrap();
+        assert_eq!(
+            server.requests()[1].header("user-agent"),
+            Some("distro-bot/1.0")
+        );
+
+        let url = server.url.join("missing/foo-1.0.tar.gz").unwrap();
+        let err = PlainHost::from_url(&url).versions().await.unwrap_err();
+        assert!(matches!(err, HostError::NotFound { url } if url.ends_with("/missing/")));
+    }
+
+    #[test]
+    fn test_directory() {
+        let url =
+            Url::parse("https://download.gnome.org/sources/proj/46/proj-46.1.tar.xz").unwrap();
+        assert_eq!(PlainHost::from_url(&url).directory, "sources/proj/46");
+
+        let url = Url::parse("https://download.gnome.org/sources/proj/46/").unwrap();
+        let host = PlainHost::from_url(&url);
+        assert_eq!(host.directory, "sources/proj/46");
+        assert_eq!(host.project_name(&VersionExtractor::new()).unwrap(), "proj");
+    }
+
+    /// Tests that sibling series directories are scanned through the parent
+    #[tokio::test]
+    async fn test_scan_parent() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/sources/proj/",
+                MockResponse::new(
+                    200,
+                    r#"<a href="../">..</a><a href="46/">46/</a><a href="47/">47/</a>"#,
+                ),
+            ),
+            (
+                "/sources/proj/46/",
+                MockResponse::new(200, r#"<a href="proj-46.1.tar.xz">proj-46.1.tar.xz</a>"#),
+            ),
+            (
+                "/sources/proj/47/",
+                MockResponse::new(200, r#"<a href="proj-47.0.tar.xz">proj-47.0.tar.xz</a>"#),
+            ),
+        ])
+        .await;
+
+        let url = server.url.join("sources/proj/46/").unwrap();
+        let host = PlainHost::from_url(&url);
+        assert_eq!(host.versions().await.unwrap().len(), 1);
+
+        let mut versions = host.with_scan_parent(true).versions().await.unwrap();
+        versions.sort();
+        let found = versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.downloads[0].url.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "46.1",
+                    server
+                        .url
+                        .join("sources/proj/46/proj-46.1.tar.xz")
+                        .unwrap()
+                        .as_str()
+                ),
+                (
+                    "47.0",
+                    server
+                        .url
+                        .join("sources/proj/47/proj-47.0.tar.xz")
+                        .unwrap()
+                        .as_str()
+                ),
+            ]
+        );
+    }
+
+    /// Tests that signatures are listed with their tarball instead of as versions
+    #[tokio::test]
+    async fn test_companions() {
+        let server = MockServer::with_routes(vec![(
+            "/dist/",
+            MockResponse::new(
+                200,
+                r#"<a href="foo-1.2.tar.gz">foo-1.2.tar.gz</a>
+                   <a href="foo-1.2.tar.gz.sig">foo-1.2.tar.gz.sig</a>"#,
+            ),
+        )])
+        .await;
+
+        let url = server.url.join("dist/foo-1.2.tar.gz").unwrap();
+        let versions = PlainHost::from_url(&url).versions().await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, "1.2");
+        let kinds = versions[0]
+            .downloads
+            .iter()
+            .map(|d| d.kind.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [AssetKind::Release, AssetKind::Signature]);
+    }
+
+    /// Tests that HEAD responses fill in asset sizes and modification times
+    #[tokio::test]
+    async fn test_asset_metadata() {
+        let server = MockServer::with_routes(vec![
+            (
+                "/dist/",
+                MockResponse::new(200, r#"<a href="foo-1.0.tar.gz">foo-1.0.tar.gz</a>"#),
+            ),
+            (
+                "/dist/foo-1.0.tar.gz",
+                MockResponse::new(200, "x".repeat(1234))
+                    .header("Last-Modified", "Tue, 03 Dec 2024 10:00:00 GMT"),
+            ),
+        ])
+        .await;
+
+        let url = server.url.join("dist/foo-1.0.tar.gz").unwrap();
+        let versions = PlainHost::from_url(&url).versions().await.unwrap();
+        assert_eq!(versions[0].downloads[0].size, None);
+
+        let versions = PlainHost::from_url(&url)
+            .with_asset_metadata(true)
+            .versions()
+            .await
+            .unwrap();
+        let asset = &versions[0].downloads[0];
+        assert_eq!(asset.size, Some(1234));
+        assert_eq!(
+            asset.updated_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-12-03T10:00:00+00:00")
+        );
+        assert!(server.requests().iter().any(|r| r.method == "HEAD"));
+    }
+
+    /// Tests that checksums from SHA256SUMS manifests are attached, in both formats
+    #[tokio::test]
+    async fn test_checksum_manifests() {
+        let gnu =
+            "0eb4b4b9420a0f51db142ba3f9c64b333f826532dc0f48c6410ae51f4799b664  foo-1.0.tar.gz\n";
+        let bsd = "SHA256 (foo-1.1.tar.gz) = C4D91BE36FC8E54DEAE7575241E03F4211EB102AFB3FC0775FBBC1B740016705\n";
+        let server = MockServer::with_routes(vec![
+            (
+                "/gnu/",
+                MockResponse::new(
+                    200,
+                    r#"<a href="foo-1.0.tar.gz">foo-1.0.tar.gz</a>
+                       <a href="SHA256SUMS">SHA256SUMS</a>"#,
+                ),
+            ),
+            ("/gnu/SHA256SUMS", MockResponse::new(200, gnu)),
+            (
+                "/bsd/",
+                MockResponse::new(
+                    200,
+                    r#"<a href="foo-1.1.tar.gz">foo-1.1.tar.gz</a>
+                       <a href="sha256sum.txt">sha256sum.txt</a>"#,
+                ),
+            ),
+            ("/bsd/sha256sum.txt", MockResponse::new(200, bsd)),
+        ])
+        .await;
+
+        let url = server.url.join("gnu/foo-1.0.tar.gz").unwrap();
+        let versions = PlainHost::from_url(&url).versions().await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(
+            versions[0].downloads[0].checksum.as_deref(),
+            Some("sha256:0eb4b4b9420a0f51db142ba3f9c64b333f826532dc0f48c6410ae51f4799b664")
+        );
+
+        let url = server.url.join("bsd/foo-1.1.tar.gz").unwrap();
+        let versions = PlainHost::from_url(&url).versions().await.unwrap();
+        assert_eq!(
+            versions[0].downloads[0].checksum.as_deref(),
+            Some("sha256:c4d91be36fc8e54deae7575241e03f4211eb102afb3fc0775fbbc1b740016705")
+        );
     }
 }
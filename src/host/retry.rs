@@ -0,0 +1,205 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Retrying of requests that failed for transient reasons, such as a `502`
+//! from an overloaded API or a dropped connection.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+/// Default number of attempts made for a request
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Default delay before the first retry
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Default upper bound on any single delay, including one requested by the server
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Decides how often and how long to wait when retrying a failed request
+///
+/// Connection errors, timeouts, `5xx` responses and `429 Too Many Requests` are
+/// retried with exponential backoff (`base_delay`, then twice that, and so on).
+/// A `Retry-After` header on a 429 replaces the computed delay. Other client
+/// errors such as `401` or `404` are never retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first request
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled for each further retry
+    pub base_delay: Duration,
+
+    /// Longest delay waited for; a `Retry-After` beyond this is not retried
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy making up to `max_attempts` attempts, starting at `base_delay`
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            ..Self::default()
+        }
+    }
+
+    /// A policy sending every request exactly once
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// Sends `request`, retrying transient failures according to this policy
+    ///
+    /// The last response is returned as-is once the attempts are used up, so
+    /// its status can still be reported by the caller.
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let mut attempt = 1;
+        loop {
+            let Some(retry) = request.try_clone().filter(|_| attempt < self.max_attempts) else {
+                return request.send().await;
+            };
+            let delay = match retry.send().await {
+                Ok(response) => match self.delay(attempt, &response) {
+                    Some(delay) => {
+                        warn!(
+                            "Retrying {} after status {} in {:?}",
+                            response.url(),
+                            response.status(),
+                            delay
+                        );
+                        delay
+                    }
+                    None => return Ok(response),
+                },
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    warn!("Retrying after request error: {}", e);
+                    self.backoff(attempt)
+                }
+                Err(e) => return Err(e),
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Returns how long to wait before retrying `response`, or `None` to keep it
+    fn delay(&self, attempt: u32, response: &reqwest::Response) -> Option<Duration> {
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return match retry_after(response) {
+                Some(delay) => (delay <= self.max_delay).then_some(delay),
+                None => Some(self.backoff(attempt)),
+            };
+        }
+        status.is_server_error().then(|| self.backoff(attempt))
+    }
+
+    /// The exponential backoff delay before retry number `attempt`
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay)
+    }
+}
+
+/// Reads a `Retry-After` header given in seconds or as an HTTP date
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((at - Utc::now()).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::host::mock::{MockResponse, MockServer};
+
+    /// Starts a server answering with each of `statuses` in turn, then with a 200
+    async fn flaky(statuses: Vec<MockResponse>) -> MockServer {
+        let count = Arc::new(AtomicUsize::new(0));
+        MockServer::start(move |_| {
+            let n = count.fetch_add(1, Ordering::SeqCst);
+            statuses
+                .get(n)
+                .cloned()
+                .unwrap_or_else(|| MockResponse::new(200, "ok"))
+        })
+        .await
+    }
+
+    /// Tests that transient failures are retried and other errors are not
+    #[tokio::test]
+    async fn test_retry() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+        let client = reqwest::Client::new();
+
+        let server = flaky(vec![
+            MockResponse::new(502, "Bad Gateway"),
+            MockResponse::new(429, "Slow down").header("Retry-After", "0"),
+        ])
+        .await;
+        let response = policy.send(client.get(server.url.clone())).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(server.requests().len(), 3);
+
+        let server = flaky(vec![MockResponse::new(404, "Not Found")]).await;
+        let response = policy.send(client.get(server.url.clone())).await.unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(server.requests().len(), 1);
+
+        // The last failure is returned once the attempts are used up
+        let server = flaky(vec![MockResponse::new(503, "Unavailable"); 3]).await;
+        let response = policy.send(client.get(server.url.clone())).await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(server.requests().len(), 3);
+
+        // A server asking for a longer wait than allowed is not retried
+        let server = flaky(vec![
+            MockResponse::new(429, "Slow down").header("Retry-After", "3600")
+        ])
+        .await;
+        let response = policy.send(client.get(server.url.clone())).await.unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(20), policy.max_delay);
+    }
+}
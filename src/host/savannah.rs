@@ -0,0 +1,169 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use tracing::debug;
+use url::Url;
+
+use crate::VersionMetadata;
+
+use super::{listing, retry::RetryPolicy, sort_versions, Host, HostError, DEFAULT_USER_AGENT};
+
+/// A Host implementation for projects hosted on GNU Savannah
+///
+/// Both `savannah.gnu.org` and `savannah.nongnu.org` publish releases under
+/// `https://download.savannah.<domain>/releases/<project>/`, either flat or with
+/// one subdirectory per version. The listings link to the `download-mirror`
+/// redirector, which is rewritten to the stable canonical download host.
+pub struct SavannahHost {
+    /// The Savannah domain, either "gnu.org" or "nongnu.org"
+    pub domain: String,
+
+    /// The project name (i.e. "man-db", "acl", etc)
+    pub project: String,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+impl SavannahHost {
+    /// Creates a new SavannahHost instance from a URL
+    ///
+    /// Accepts release directory and file URLs on the download hosts, as well as
+    /// `https://savannah.nongnu.org/projects/<project>` project pages.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let domain = if host.ends_with("nongnu.org") {
+            "nongnu.org"
+        } else {
+            "gnu.org"
+        };
+
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        let section = path.first().copied().unwrap_or_default();
+        if section != "releases" && section != "projects" {
+            return Err(HostError::invalid_url(url, "invalid URL format"));
+        }
+        let project = path
+            .get(1)
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+
+        Ok(Self {
+            domain: domain.to_string(),
+            project: project.to_string(),
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Returns the URL of the project's release directory
+    fn releases_url(&self) -> Result<Url, HostError> {
+        let uri = format!(
+            "https://download.savannah.{}/releases/{}/",
+            self.domain, self.project
+        );
+        Url::parse(&uri).map_err(|e| HostError::invalid_url(&uri, e))
+    }
+
+    /// Lists a release directory, resolving mirror redirector links to the canonical host
+    async fn list(&self, url: &Url) -> Result<Vec<listing::ListingEntry>, HostError> {
+        let body = listing::fetch_text_as(url, &self.user_agent, &self.retry).await?;
+        listing::parse_listing(url, &canonicalize_links(&body))
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// Rewrites `download-mirror.savannah.*` links to the canonical `download.savannah.*` host
+fn canonicalize_links(body: &str) -> String {
+    body.replace("//download-mirror.savannah.", "//download.savannah.")
+}
+
+#[async_trait]
+impl Host for SavannahHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries = self.list(&self.releases_url()?).await?;
+
+        let mut files = vec![];
+        for entry in entries {
+            if listing::is_version_dir(&self.project, &entry) {
+                debug!("Descending into version directory: {}", entry.url);
+                files.extend(self.list(&entry.url).await?);
+            } else if !entry.is_dir {
+                files.push(entry);
+            }
+        }
+
+        let versions = listing::collect_versions(&self.project, &files);
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetKind;
+
+    #[test]
+    fn test_from_url() {
+        let url =
+            Url::parse("https://download.savannah.nongnu.org/releases/man-db/man-db-2.13.0.tar.xz")
+                .unwrap();
+        let host = SavannahHost::from_url(&url).unwrap();
+        assert_eq!(host.domain, "nongnu.org");
+        assert_eq!(host.project, "man-db");
+
+        let url = Url::parse("https://savannah.gnu.org/projects/acl").unwrap();
+        let host = SavannahHost::from_url(&url).unwrap();
+        assert_eq!(
+            host.releases_url().unwrap().as_str(),
+            "https://download.savannah.gnu.org/releases/acl/"
+        );
+
+        let url = Url::parse("https://savannah.gnu.org/bugs/?group=acl").unwrap();
+        assert!(SavannahHost::from_url(&url).is_err());
+    }
+
+    #[test]
+    fn test_mirror_links() {
+        let base = Url::parse("https://download.savannah.nongnu.org/releases/man-db/").unwrap();
+        let body = r#"
+            <a href="https://download-mirror.savannah.nongnu.org/releases/man-db/man-db-2.13.0.tar.xz">man-db-2.13.0.tar.xz</a>
+            <a href="https://download-mirror.savannah.nongnu.org/releases/man-db/man-db-2.13.0.tar.xz.asc">man-db-2.13.0.tar.xz.asc</a>
+        "#;
+        let entries = listing::parse_listing(&base, &canonicalize_links(body)).unwrap();
+        let versions = listing::collect_versions("man-db", &entries);
+
+        assert_eq!(versions.len(), 1);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            "https://download.savannah.nongnu.org/releases/man-db/man-db-2.13.0.tar.xz"
+        );
+        assert_eq!(versions[0].downloads[1].kind, AssetKind::Signature);
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tracing::debug;
+use url::Url;
+
+use crate::VersionMetadata;
+
+use super::{Host, HostError};
+
+/// Offline host replaying versions from a local JSON snapshot
+///
+/// Snapshots are the JSON serialisation of `Vec<VersionMetadata>` written with
+/// [`write_snapshot`], or the `{"moved_to", "versions"}` object printed by the
+/// CLI, so API responses can be captured once and resolved again without
+/// network access.
+pub struct SnapshotHost {
+    /// The snapshot file the versions were read from
+    pub path: PathBuf,
+
+    /// The versions contained in the snapshot
+    pub versions: Vec<VersionMetadata>,
+}
+
+impl SnapshotHost {
+    /// Reads a snapshot from `path`
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, HostError> {
+        let path = path.as_ref();
+        debug!("Reading snapshot from: {}", path.display());
+        let data = std::fs::read(path).map_err(|e| HostError::Io {
+            context: format!("failed to read snapshot {}", path.display()),
+            source: e,
+        })?;
+        let snapshot = serde_json::from_slice(&data).map_err(|e| {
+            HostError::parse_error(path.display(), format!("invalid snapshot: {e}"))
+        })?;
+        let (Snapshot::Versions(versions) | Snapshot::Output { versions }) = snapshot;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            versions,
+        })
+    }
+
+    /// Reads a snapshot from a `file://` URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let path = url
+            .to_file_path()
+            .map_err(|_| HostError::invalid_url(url, "not a local file"))?;
+        Self::from_file(path)
+    }
+}
+
+/// The accepted snapshot layouts
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Snapshot {
+    /// A bare list, as written by [`write_snapshot`]
+    Versions(Vec<VersionMetadata>),
+
+    /// The object printed by the CLI, whose `moved_to` is not needed to replay it
+    Output { versions: Vec<VersionMetadata> },
+}
+
+/// Writes `versions` to `path` as a snapshot readable by [`SnapshotHost`]
+pub fn write_snapshot(
+    path: impl AsRef<Path>,
+    versions: &[VersionMetadata],
+) -> Result<(), HostError> {
+    let path = path.as_ref();
+    let data = serde_json::to_vec_pretty(versions)
+        .map_err(|e| HostError::parse_error(path.display(), format!("failed to serialize: {e}")))?;
+    std::fs::write(path, data).map_err(|e| HostError::Io {
+        context: format!("failed to write snapshot {}", path.display()),
+        source: e,
+    })
+}
+
+#[async_trait]
+impl Host for SnapshotHost {
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        Ok(self.versions.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::{AssetKind, VersionedAsset};
+
+    /// Tests that a written snapshot reads back unchanged
+    #[tokio::test]
+    async fn test_round_trip() {
+        let versions = vec![VersionMetadata {
+            version: "2.63.2".to_string(),
+            downloads: vec![VersionedAsset {
+                url: "https://github.com/cli/cli/archive/refs/tags/v2.63.2.tar.gz".to_string(),
+                kind: AssetKind::Autogenerated,
+                released_at: Some(Utc.with_ymd_and_hms(2024, 12, 3, 10, 0, 0).unwrap()),
+                updated_at: None,
+                checksum: None,
+                size: None,
+                download_count: None,
+                verifies: vec![],
+            }],
+            release_notes: Some("Bug fixes".to_string()),
+            released_at: Some(Utc.with_ymd_and_hms(2024, 12, 3, 10, 0, 0).unwrap()),
+            prerelease: false,
+            source_ref: None,
+            tag: Some("v2.63.2".to_string()),
+        }];
+
+        let path =
+            std::env::temp_dir().join(format!("upstreams-snapshot-{}.json", std::process::id()));
+        write_snapshot(&path, &versions).unwrap();
+
+        let url = Url::from_file_path(&path).unwrap();
+        let host = crate::host::from_url(&url).unwrap();
+        let read = host.versions().await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, versions);
+
+        // The CLI output is a snapshot too
+        let output = serde_json::json!({"moved_to": null, "versions": versions});
+        std::fs::write(&path, output.to_string()).unwrap();
+        let read = SnapshotHost::from_file(&path).unwrap().versions;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, versions);
+
+        std::fs::write(&path, r#"{"moved_to": null}"#).unwrap();
+        assert!(matches!(
+            SnapshotHost::from_file(&path),
+            Err(HostError::ParseError { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::{versioning::is_prerelease, AssetKind, VersionMetadata, VersionedAsset};
+
+use super::{
+    check_status, http_client, retry::RetryPolicy, sort_versions, strip_tag_prefixes, Host,
+    HostError, DEFAULT_USER_AGENT,
+};
+
+/// SourceHut host implementation for repositories on `git.sr.ht`.
+///
+/// Tags are enumerated through the public `refs/rss.xml` feed, which unlike the
+/// REST API does not require an OAuth token.
+pub struct SourceHutHost {
+    /// The owner of the repository, without the leading `~`.
+    pub user: String,
+    /// The name of the repository.
+    pub repo: String,
+    /// The URL of the repository.
+    pub url: Url,
+    /// The `User-Agent` sent with every request.
+    pub user_agent: String,
+    /// Policy for retrying requests that failed for transient reasons.
+    pub retry: RetryPolicy,
+}
+
+/// A tag parsed from the refs feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceHutTag {
+    /// The name of the tag
+    pub name: String,
+    /// When the tag was created
+    pub date: Option<DateTime<Utc>>,
+}
+
+impl SourceHutHost {
+    /// Creates a new SourceHutHost instance from a `git.sr.ht/~user/repo` URL.
+    ///
+    /// The `~` may also be percent-encoded (`%7Euser`), as some tools escape it.
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        debug!("Creating SourceHutHost from URL: {}", url);
+        let mut parts = url.path().split('/').filter(|x| !x.is_empty());
+        let owner = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing user in SourceHut URL"))?;
+        let user = owner
+            .strip_prefix('~')
+            .or_else(|| owner.strip_prefix("%7E"))
+            .or_else(|| owner.strip_prefix("%7e"))
+            .filter(|user| !user.is_empty())
+            .ok_or_else(|| {
+                HostError::parse_error(url, format!("expected a ~user path segment, got {owner}"))
+            })?
+            .to_string();
+        let repo = parts
+            .next()
+            .ok_or_else(|| HostError::parse_error(url, "missing repository name in SourceHut URL"))?
+            .trim_end_matches(".git")
+            .to_string();
+
+        let mut url = url.clone();
+        url.set_path(&format!("/~{user}/{repo}"));
+        url.set_query(None);
+        url.set_fragment(None);
+        Ok(Self {
+            user,
+            repo,
+            url,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Returns `path` resolved below the repository URL
+    fn join(&self, path: &str) -> Result<Url, HostError> {
+        let url = format!("{}/{path}", self.url);
+        Url::parse(&url).map_err(|e| HostError::invalid_url(&url, e))
+    }
+
+    /// Fetches the tags listed in the refs feed.
+    #[tracing::instrument(skip(self), fields(owner = %self.user, repo = %self.repo))]
+    async fn fetch_tags(&self) -> Result<Vec<SourceHutTag>, HostError> {
+        let feed = self.join("refs/rss.xml")?;
+        debug!("Fetching tags from: {}", feed);
+        let body = self
+            .retry
+            .send(http_client(&self.user_agent).get(feed.as_str()))
+            .await
+            .map_err(|e| HostError::ApiRequest {
+                context: "failed to fetch refs feed".into(),
+                source: e,
+            })
+            .and_then(check_status)?
+            .text()
+            .await
+            .map_err(|e| HostError::ApiResponse {
+                context: format!("failed to read {feed}"),
+                source: e,
+            })?;
+
+        let tags = parse_feed(&body);
+        info!("Successfully fetched {} tags", tags.len());
+        Ok(tags)
+    }
+
+    /// Sets the `User-Agent` sent with every request.
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons.
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+/// An `<item>` of the refs feed
+static ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<item>(.*?)</item>").unwrap());
+
+/// The `<title>` of a feed item, which is the tag name
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<title>\s*(.*?)\s*</title>").unwrap());
+
+/// The `<pubDate>` of a feed item
+static PUB_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<pubDate>\s*(.*?)\s*</pubDate>").unwrap());
+
+/// Parses the `<item>` entries of a SourceHut refs feed
+fn parse_feed(feed: &str) -> Vec<SourceHutTag> {
+    ITEM.captures_iter(feed)
+        .filter_map(|item| {
+            let item = item.get(1)?.as_str();
+            let name = TITLE.captures(item)?.get(1)?.as_str();
+            let date = PUB_DATE
+                .captures(item)
+                .and_then(|c| DateTime::parse_from_rfc2822(c.get(1)?.as_str()).ok())
+                .map(|d| d.with_timezone(&Utc));
+            Some(SourceHutTag {
+                name: name.to_string(),
+                date,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Host for SourceHutHost {
+    #[tracing::instrument(skip(self), fields(owner = %self.user, repo = %self.repo))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let tags = self.fetch_tags().await?;
+
+        let versions = tags
+            .into_iter()
+            .map(|tag| {
+                let archive = self.join(&format!("archive/{}.tar.gz", tag.name))?;
+                let downloads = vec![VersionedAsset {
+                    released_at: tag.date,
+                    ..VersionedAsset::new(archive, AssetKind::Autogenerated)
+                }];
+                Ok(VersionMetadata {
+                    released_at: tag.date,
+                    prerelease: is_prerelease(&tag.name),
+                    tag: Some(tag.name.clone()),
+                    ..VersionMetadata::new(strip_tag_prefixes(&self.repo, &tag.name), downloads)
+                })
+            })
+            .collect::<Result<Vec<_>, HostError>>()?;
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::mock::{MockResponse, MockServer};
+
+    #[test]
+    fn test_from_url() {
+        let url = Url::parse("https://git.sr.ht/~sircmpwn/scdoc/refs").unwrap();
+        let host = SourceHutHost::from_url(&url).unwrap();
+        assert_eq!(host.user, "sircmpwn");
+        assert_eq!(host.repo, "scdoc");
+        assert_eq!(host.url.as_str(), "https://git.sr.ht/~sircmpwn/scdoc");
+
+        let url = Url::parse("https://git.sr.ht/%7Esircmpwn/scdoc.git").unwrap();
+        let host = SourceHutHost::from_url(&url).unwrap();
+        assert_eq!(host.user, "sircmpwn");
+        assert_eq!(host.repo, "scdoc");
+
+        for invalid in [
+            "https://git.sr.ht/sircmpwn/scdoc",
+            "https://git.sr.ht/~/scdoc",
+            "https://git.sr.ht/~sircmpwn",
+        ] {
+            let url = Url::parse(invalid).unwrap();
+            assert!(SourceHutHost::from_url(&url).is_err(), "{invalid}");
+        }
+    }
+
+    /// Tests that tags from the refs feed become versions with archive downloads
+    #[tokio::test]
+    async fn test_versions() {
+        let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0"><channel>
+              <title>~sircmpwn/scdoc refs</title>
+              <item>
+                <title>v1.11.3</title>
+                <link>https://git.sr.ht/~sircmpwn/scdoc/refs/1.11.3</link>
+                <pubDate>Sun, 31 Mar 2024 10:00:00 +0000</pubDate>
+              </item>
+              <item>
+                <title>1.11.2</title>
+                <link>https://git.sr.ht/~sircmpwn/scdoc/refs/1.11.2</link>
+              </item>
+              <item>
+                <title>1.11.2-rc1</title>
+                <link>https://git.sr.ht/~sircmpwn/scdoc/refs/1.11.2-rc1</link>
+              </item>
+            </channel></rss>"#;
+        let server = MockServer::with_routes(vec![(
+            "/~sircmpwn/scdoc/refs/rss.xml",
+            MockResponse::new(200, feed),
+        )])
+        .await;
+
+        let url = server.url.join("~sircmpwn/scdoc").unwrap();
+        let versions = SourceHutHost::from_url(&url)
+            .unwrap()
+            .versions()
+            .await
+            .unwrap();
+
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[0].version, "1.11.3");
+        assert_eq!(versions[0].tag.as_deref(), Some("v1.11.3"));
+        assert!(!versions[0].prerelease);
+        assert_eq!(
+            versions[0].released_at.map(|d| d.to_rfc3339()).as_deref(),
+            Some("2024-03-31T10:00:00+00:00")
+        );
+        assert_eq!(versions[0].downloads[0].kind, AssetKind::Autogenerated);
+        assert_eq!(
+            versions[0].downloads[0].url,
+            server
+                .url
+                .join("~sircmpwn/scdoc/archive/v1.11.3.tar.gz")
+                .unwrap()
+                .as_str()
+        );
+        assert_eq!(versions[1].released_at, None);
+        assert!(versions[2].prerelease);
+    }
+}
@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: Copyright © 2025 AerynOS Developers
+//
+// SPDX-License-Identifier: MPL-2.0
+
+use async_trait::async_trait;
+use tracing::debug;
+use url::Url;
+
+use crate::{versioning::VersionExtractor, VersionMetadata};
+
+use super::{listing, retry::RetryPolicy, sort_versions, Host, HostError, DEFAULT_USER_AGENT};
+
+/// A Host implementation for projects published on downloads.xiph.org
+///
+/// Xiph.Org projects (Ogg, Vorbis, Opus, FLAC, ...) publish every release into
+/// one flat directory under `https://downloads.xiph.org/releases/<project>/`,
+/// usually in several compression formats, next to a `SHA256SUMS.txt` manifest.
+pub struct XiphHost {
+    /// The release directory name (i.e. "ogg", "opus", "flac")
+    pub directory: String,
+
+    /// The project name used to match tarballs (i.e. "libogg")
+    pub project: String,
+
+    /// The complete URL to the project's download location
+    pub url: Url,
+
+    /// The `User-Agent` sent with every request
+    pub user_agent: String,
+
+    /// Policy for retrying requests that failed for transient reasons
+    pub retry: RetryPolicy,
+}
+
+impl XiphHost {
+    /// Creates a new XiphHost instance from a URL
+    pub fn from_url(url: &Url) -> Result<Self, HostError> {
+        let parts = url
+            .path_segments()
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+        let path = parts.filter(|p| !p.is_empty()).collect::<Vec<&str>>();
+        if path.first() != Some(&"releases") {
+            return Err(HostError::invalid_url(url, "invalid URL format"));
+        }
+        let directory = path
+            .get(1)
+            .ok_or_else(|| HostError::invalid_url(url, "invalid URL format"))?;
+
+        // Tarballs are usually named after the library rather than the directory
+        let project = path
+            .get(2)
+            .and_then(|file| VersionExtractor::new().extract(file).ok())
+            .map(|m| m.name)
+            .unwrap_or_else(|| directory.to_string());
+
+        Ok(Self {
+            directory: directory.to_string(),
+            project,
+            url: url.clone(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    /// Returns the URL of the project's release directory
+    fn releases_url(&self) -> Result<Url, HostError> {
+        let uri = format!("https://downloads.xiph.org/releases/{}/", self.directory);
+        Url::parse(&uri).map_err(|e| HostError::invalid_url(&uri, e))
+    }
+
+    /// Sets the `User-Agent` sent with every request
+    pub fn with_user_agent(self, user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..self
+        }
+    }
+
+    /// Sets the policy for retrying requests that failed for transient reasons
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+}
+
+#[async_trait]
+impl Host for XiphHost {
+    #[tracing::instrument(skip(self), fields(project = %self.project))]
+    async fn versions(&self) -> Result<Vec<VersionMetadata>, HostError> {
+        let entries =
+            listing::list_directory_as(&self.releases_url()?, &self.user_agent, &self.retry)
+                .await?;
+        let mut versions = listing::collect_versions(&self.project, &entries);
+
+        if let Some(manifest) = entries
+            .iter()
+            .find(|e| listing::is_sha256_manifest(&e.name))
+        {
+            debug!("Attaching checksums from: {}", manifest.url);
+            let sums = listing::parse_checksums(
+                &listing::fetch_text_as(&manifest.url, &self.user_agent, &self.retry).await?,
+                "sha256",
+            );
+            listing::attach_checksums(&mut versions, &sums);
+        }
+
+        Ok(sort_versions(versions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_url() {
+        let url =
+            Url::parse("https://downloads.xiph.org/releases/ogg/libogg-1.3.5.tar.xz").unwrap();
+        let host = XiphHost::from_url(&url).unwrap();
+        assert_eq!(host.directory, "ogg");
+        assert_eq!(host.project, "libogg");
+
+        let url = Url::parse("https://downloads.xiph.org/releases/flac/").unwrap();
+        let host = XiphHost::from_url(&url).unwrap();
+        assert_eq!(host.project, "flac");
+    }
+
+    #[test]
+    fn test_group_and_checksums() {
+        let base = Url::parse("https://downloads.xiph.org/releases/ogg/").unwrap();
+        let body = r#"
+            <a href="libogg-1.3.5.tar.gz">libogg-1.3.5.tar.gz</a>
+            <a href="libogg-1.3.5.tar.xz">libogg-1.3.5.tar.xz</a>
+            <a href="libogg-1.3.5.zip">libogg-1.3.5.zip</a>
+            <a href="libogg-1.3.4.tar.gz">libogg-1.3.4.tar.gz</a>
+            <a href="SHA256SUMS.txt">SHA256SUMS.txt</a>
+        "#;
+        let entries = listing::parse_listing(&base, body).unwrap();
+        let mut versions = listing::collect_versions("libogg", &entries);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[1].version, "1.3.5");
+        assert_eq!(versions[1].downloads.len(), 3);
+
+        let sums = listing::parse_checksums(
+            "0eb4b4b9420a0f51db142ba3f9c64b333f826532dc0f48c6410ae51f4799b664  libogg-1.3.5.tar.gz\n\
+             c4d91be36fc8e54deae7575241e03f4211eb102afb3fc0775fbbc1b740016705 *libogg-1.3.5.tar.xz\n",
+            "sha256",
+        );
+        listing::attach_checksums(&mut versions, &sums);
+        let checksums = versions[1]
+            .downloads
+            .iter()
+            .map(|d| d.checksum.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            checksums,
+            vec![
+                Some("sha256:0eb4b4b9420a0f51db142ba3f9c64b333f826532dc0f48c6410ae51f4799b664"),
+                Some("sha256:c4d91be36fc8e54deae7575241e03f4211eb102afb3fc0775fbbc1b740016705"),
+                None,
+            ]
+        );
+        assert!(listing::is_sha256_manifest("SHA256SUMS"));
+    }
+}
@@ -3,13 +3,13 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 pub mod host;
 pub mod versioning;
 
 /// Metadata about a specific version of the software
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct VersionMetadata {
     /// The version string (e.g. "1.0.0")
     pub version: String,
@@ -19,12 +19,54 @@ pub struct VersionMetadata {
     pub release_notes: Option<String>,
     /// Timestamp when this version was released
     pub released_at: Option<DateTime<Utc>>,
+    /// Whether this version is a development or prerelease version
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Commit the version's tag points to, for pinning sources (e.g. a git SHA)
+    #[serde(default)]
+    pub source_ref: Option<String>,
+    /// The tag the version string was derived from (e.g. "v1.0.0")
+    ///
+    /// Hosts versioning tags set it for every version, even when the tag is
+    /// spelled like the version itself.
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl VersionMetadata {
+    /// Creates version metadata with the given downloads and no further details
+    pub fn new(version: impl Into<String>, downloads: Vec<VersionedAsset>) -> Self {
+        Self {
+            version: version.into(),
+            downloads,
+            release_notes: None,
+            released_at: None,
+            prerelease: false,
+            source_ref: None,
+            tag: None,
+        }
+    }
+}
+
+/// Summary of how a known version compares to the versions available upstream
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct UpdateReport {
+    /// The version that is currently packaged
+    pub current: String,
+    /// The newest stable version upstream, or the newest prerelease if there is none
+    pub latest: Option<VersionMetadata>,
+    /// Every upstream version strictly newer than `current`, newest first
+    pub newer: Vec<VersionMetadata>,
+    /// Whether `latest` is no newer than `current`
+    ///
+    /// Newer prereleases are listed in `newer` but do not make `current` outdated.
+    pub up_to_date: bool,
 }
 
 /// Represents a downloadable asset associated with a specific software version.
 /// The discovery of a version is usually bound to encountering
 /// a release asset, so we store the version string here.
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct VersionedAsset {
     /// The URL where this asset can be downloaded from
     pub url: String,
@@ -37,10 +79,45 @@ pub struct VersionedAsset {
 
     /// Timestamp when this asset was last modified
     pub updated_at: Option<DateTime<Utc>>,
+
+    /// Digest of the asset as `<algorithm>:<hex>` (e.g. "sha256:ab12...")
+    pub checksum: Option<String>,
+
+    /// Size of the asset in bytes, when known
+    #[serde(default)]
+    pub size: Option<u64>,
+
+    /// Number of times the asset was downloaded, when the host counts them
+    #[serde(default)]
+    pub download_count: Option<u64>,
+
+    /// URLs of the assets verified by this signature or checksum file
+    #[serde(default)]
+    pub verifies: Vec<String>,
+}
+
+impl VersionedAsset {
+    /// Creates an asset of the given kind without any timestamps
+    pub fn new(url: impl Into<String>, kind: AssetKind) -> Self {
+        Self {
+            url: url.into(),
+            kind,
+            released_at: None,
+            updated_at: None,
+            checksum: None,
+            size: None,
+            download_count: None,
+            verifies: vec![],
+        }
+    }
 }
 
 /// Categorizes different types of release assets
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+///
+/// Serialized as stable snake_case names (e.g. `"release"`) so that persisted
+/// metadata stays readable across versions of this crate.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AssetKind {
     /// Automatically generated release asset, such as a GitHub release from a tag
     Autogenerated,
@@ -53,4 +130,49 @@ pub enum AssetKind {
 
     /// File containing checksums/hashes for verification
     Checksum,
+
+    /// Prebuilt binary artifact
+    Binary,
+
+    /// Archive of the project's source code uploaded by the maintainers
+    SourceArchive,
+
+    /// Software bill of materials (e.g. SPDX or CycloneDX document)
+    Sbom,
+
+    /// Installer or distribution package (e.g. `.msi`, `.deb`, `.AppImage`)
+    Installer,
+
+    /// Debug symbols or source maps (e.g. `.pdb`, `.debug`, `.map`)
+    Debug,
+
+    /// Release notes for the version (e.g. GNOME's `.news` files)
+    ReleaseNotes,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests the exact serialized names of each asset kind, which are part of the stored format
+    #[test]
+    fn test_asset_kind_tokens() {
+        let kinds = [
+            (AssetKind::Autogenerated, "\"autogenerated\""),
+            (AssetKind::Release, "\"release\""),
+            (AssetKind::Signature, "\"signature\""),
+            (AssetKind::Checksum, "\"checksum\""),
+            (AssetKind::Binary, "\"binary\""),
+            (AssetKind::SourceArchive, "\"source_archive\""),
+            (AssetKind::Sbom, "\"sbom\""),
+            (AssetKind::Installer, "\"installer\""),
+            (AssetKind::Debug, "\"debug\""),
+            (AssetKind::ReleaseNotes, "\"release_notes\""),
+        ];
+
+        for (kind, token) in kinds {
+            assert_eq!(serde_json::to_string(&kind).unwrap(), token);
+            assert_eq!(serde_json::from_str::<AssetKind>(token).unwrap(), kind);
+        }
+    }
 }
@@ -2,15 +2,33 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+use std::{path::PathBuf, sync::Arc};
+
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{
-    fmt::format::Format, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter,
+    fmt::{format::FmtSpan, format::Format},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    EnvFilter,
+};
+use upstreams_rs::{
+    host::{
+        self,
+        cache::{CachedResponse, EtagCache},
+        github::{GithubHost, TagFilter},
+        Host,
+    },
+    versioning::{self, VersionExtractor},
 };
-use upstreams_rs::{host, versioning::VersionExtractor};
+
+/// Exit code used when a repository or project does not exist, so scripts can
+/// tell it apart from transient failures
+const EXIT_NOT_FOUND: i32 = 2;
 
 /// Configures the tracing infrastructure with appropriate formatting and filtering
 ///
 /// Sets up tracing with ANSI colors, uptime timer, and target information.
+/// Span close events are logged so each host request reports its elapsed time.
 /// Uses environment variables for filtering or defaults to trace level.
 fn configure_tracing() -> color_eyre::Result<()> {
     let f = Format::default()
@@ -24,27 +42,169 @@ fn configure_tracing() -> color_eyre::Result<()> {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
     tracing_subscriber::registry()
         .with(filter)
-        .with(tracing_subscriber::fmt::layer().event_format(f))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .event_format(f)
+                .with_span_events(FmtSpan::CLOSE),
+        )
         .with(ErrorLayer::default())
         .init();
 
     Ok(())
 }
 
+/// Options collected from the command line
+#[derive(Default)]
+struct Options {
+    /// The URLs to look up
+    urls: Vec<String>,
+    /// Filter applied to GitHub tags
+    tag_filter: TagFilter,
+    /// Whether prereleases are left out of the output
+    stable: bool,
+    /// Directory persisting API responses between runs
+    cache_dir: Option<PathBuf>,
+    /// Number of newest versions listed per URL
+    max_versions: Option<usize>,
+    /// Pattern file whose patterns are used next to the defaults
+    patterns: Option<PathBuf>,
+}
+
+/// An [`EtagCache`] storing one JSON file per URL in a directory
+struct DirCache {
+    dir: PathBuf,
+}
+
+impl DirCache {
+    /// Returns the file holding the cached response for `url`
+    fn path(&self, url: &str) -> PathBuf {
+        let name = url
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        self.dir.join(format!("{name}.json"))
+    }
+}
+
+impl EtagCache for DirCache {
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        let data = std::fs::read(self.path(url)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    fn put(&self, url: &str, response: CachedResponse) {
+        let written = std::fs::create_dir_all(&self.dir).and_then(|_| {
+            let data = serde_json::to_vec(&response).map_err(std::io::Error::other)?;
+            std::fs::write(self.path(url), data)
+        });
+        if let Err(e) = written {
+            tracing::warn!("Failed to write cache entry for {}: {}", url, e);
+        }
+    }
+}
+
+/// Splits command line arguments into URLs and the options they request
+///
+/// `--tag-filter <regex>` keeps only matching tags, or drops them when the
+/// pattern starts with `!`. `--tag-strip-prefix <prefix>` removes a prefix
+/// such as `release/` from the emitted version strings, `--stable` drops
+/// prereleases and `--cache-dir <dir>` keeps GitHub responses between runs.
+/// `--max-versions <n>` lists only the newest `n` versions, which also stops
+/// GitHub pagination early. `--patterns <file>` adds the version patterns of a
+/// TOML or JSON pattern file to the default ones.
+fn parse_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Options, Box<dyn std::error::Error>> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--tag-filter" => {
+                let pattern = value()?;
+                let filter = std::mem::take(&mut options.tag_filter);
+                options.tag_filter = match pattern.strip_prefix('!') {
+                    Some(pattern) => filter.exclude(regex::Regex::new(pattern)?),
+                    None => filter.include(regex::Regex::new(&pattern)?),
+                };
+            }
+            "--tag-strip-prefix" => {
+                let prefix = value()?;
+                options.tag_filter = std::mem::take(&mut options.tag_filter).strip_prefix(prefix);
+            }
+            "--stable" => options.stable = true,
+            "--cache-dir" => options.cache_dir = Some(PathBuf::from(value()?)),
+            "--max-versions" => options.max_versions = Some(value()?.parse()?),
+            "--patterns" => options.patterns = Some(PathBuf::from(value()?)),
+            _ => options.urls.push(arg),
+        }
+    }
+    Ok(options)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     configure_tracing()?;
-    let args: Vec<String> = std::env::args().skip(1).collect();
-    let ext = VersionExtractor::new();
-    for arg in args {
+    let options = parse_args(std::env::args().skip(1))?;
+    let ext = match &options.patterns {
+        Some(path) => VersionExtractor::from_config(path)?,
+        None => VersionExtractor::new(),
+    };
+    let cache = options
+        .cache_dir
+        .map(|dir| Arc::new(DirCache { dir }) as Arc<dyn EtagCache>);
+    for arg in options.urls {
         let version = ext.extract(&arg)?;
         eprintln!("name = {}, version = {}", version.name, version.version);
 
         let url = url::Url::parse(&arg)?;
-        let host = host::from_url(&url)?;
-        let versions = host.versions().await?;
+        let host: Box<dyn Host> = match url.host_str() {
+            Some("github.com" | "api.github.com") => {
+                let mut github = GithubHost::from_url(&url)?
+                    .with_tag_filter(options.tag_filter.clone())
+                    .with_stable(options.stable);
+                if let Some(max) = options.max_versions {
+                    github = github.with_max_versions(max);
+                }
+                match &cache {
+                    Some(cache) => Box::new(github.with_cache(cache.clone())),
+                    None => Box::new(github),
+                }
+            }
+            _ => host::from_url(&url)?,
+        };
+        let mut versions = match host.versions().await {
+            Err(host::HostError::RateLimited { reset_at, .. }) => {
+                let local = reset_at.with_timezone(&chrono::Local);
+                let wait = (reset_at - chrono::Utc::now()).num_minutes().max(0);
+                eprintln!(
+                    "API rate limit exceeded for {arg}; it resets at {} (in {wait} minutes)",
+                    local.format("%Y-%m-%d %H:%M:%S %Z")
+                );
+                std::process::exit(1);
+            }
+            Err(e @ host::HostError::NotFound { .. }) => {
+                eprintln!("{e}");
+                std::process::exit(EXIT_NOT_FOUND);
+            }
+            result => result?,
+        };
+        // Hosts registered by other crates may return versions in any order
+        versioning::sort_versions(&mut versions);
+        if options.stable {
+            versions.retain(|v| !v.prerelease);
+        }
+        if let Some(max) = options.max_versions {
+            versions.truncate(max);
+        }
 
-        let c = colored_json::to_colored_json_auto(&versions)?;
+        // Always an object so the output has one shape; `moved_to` is null
+        // unless the repository was renamed
+        let output = serde_json::json!({
+            "moved_to": host.moved_to().map(|url| url.to_string()),
+            "versions": versions,
+        });
+        let c = colored_json::to_colored_json_auto(&output)?;
         println!("{}", c);
     }
     Ok(())
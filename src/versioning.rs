@@ -17,12 +17,24 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+use std::{
+    cmp::{Ordering, Reverse},
+    fmt,
+    path::Path,
+    str::FromStr,
+    sync::LazyLock,
+};
+
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::Url;
 
+use crate::VersionMetadata;
+
 /// Represents different versioning styles that can be extracted
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum VersionStyle {
     /// Semantic versioning pattern (e.g. 1.2.3)
     Semver,
@@ -34,6 +46,25 @@ pub enum VersionStyle {
     Simple,
 }
 
+/// Archive and compression suffixes recognised after a version
+///
+/// Each compound extension is spelled out so that only a known suffix is
+/// consumed, rather than everything following `.tar`.
+const ARCHIVE_SUFFIX: &str =
+    r"(?:tar(?:\.(?:gz|bz2|xz|zst|lz|lzma|lz4|Z))?|zip|tgz|tbz2?|txz|tzst|tlz)";
+
+/// Qualifier some projects put between the version and the archive suffix
+/// (i.e. Mozilla's `firefox_128.0.source.tar.xz`)
+const SOURCE_QUALIFIER: &str = r"(?:[-._](?:source|src))?";
+
+/// Words commonly followed by a number that is not a version (i.e. `utf-8`)
+///
+/// A bare integer after one of these ends a name like `utf-8.tar.gz` rather
+/// than versioning it, so such matches are dropped.
+const NUMBERED_WORDS: &[&str] = &[
+    "utf", "ucs", "latin", "iso", "base32", "base64", "sha", "md", "crc", "x86", "ipv", "win",
+];
+
 /// Pattern definition for version extraction
 pub struct VersionPattern {
     /// The style of versioning this pattern matches
@@ -63,6 +94,13 @@ impl VersionPattern {
 /// Version extraction engine that matches patterns against paths/URLs
 pub struct VersionExtractor {
     patterns: Vec<VersionPattern>,
+    prefer_first: bool,
+}
+
+impl Default for VersionExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Errors that can occur during version extraction
@@ -74,6 +112,30 @@ pub enum VersionError {
     /// Error creating regex pattern
     #[error("Invalid regex pattern: {0}")]
     RegexError(#[from] regex::Error),
+    /// Patterns of equal priority disagree on the name or version
+    #[error("Ambiguous version, candidates: {candidates:?}")]
+    AmbiguousVersion { candidates: Vec<Extraction> },
+    /// A pattern config could not be read or parsed
+    #[error("Invalid pattern config: {0}")]
+    InvalidConfig(String),
+    /// A custom pattern lacks a capture group that extraction relies on
+    #[error("Pattern `{pattern}` has no `{group}` capture group")]
+    MissingCaptureGroup { pattern: String, group: String },
+}
+
+/// A pattern file, listing patterns as `[[patterns]]` entries
+#[derive(Debug, Deserialize)]
+struct PatternConfig {
+    #[serde(default)]
+    patterns: Vec<PatternEntry>,
+}
+
+/// A single custom pattern in a pattern file
+#[derive(Debug, Deserialize)]
+struct PatternEntry {
+    style: VersionStyle,
+    pattern: String,
+    priority: u8,
 }
 
 impl VersionExtractor {
@@ -81,11 +143,20 @@ impl VersionExtractor {
     pub fn new() -> Self {
         let mut extractor = Self {
             patterns: Vec::with_capacity(5),
+            prefer_first: false,
         };
         extractor.add_default_patterns();
         extractor
     }
 
+    /// Returns the first match instead of an error when equal-priority patterns disagree
+    pub fn prefer_first(self, prefer_first: bool) -> Self {
+        Self {
+            prefer_first,
+            ..self
+        }
+    }
+
     /// Adds a custom pattern to the extractor
     ///
     /// Patterns are tried in order of priority (lowest first)
@@ -94,75 +165,180 @@ impl VersionExtractor {
         self.patterns.sort_by_key(|p| p.priority);
     }
 
+    /// Creates an extractor with the default patterns and those of a pattern file
+    ///
+    /// Files ending in `.json` are read as JSON, anything else as TOML. Both
+    /// hold a `patterns` list of `{style, pattern, priority}` entries.
+    pub fn from_config(path: impl AsRef<Path>) -> Result<Self, VersionError> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .map_err(|e| VersionError::InvalidConfig(format!("{}: {e}", path.display())))?;
+        let config = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str::<PatternConfig>(&data)
+                .map_err(|e| VersionError::InvalidConfig(format!("{}: {e}", path.display())))?,
+            _ => toml::from_str::<PatternConfig>(&data)
+                .map_err(|e| VersionError::InvalidConfig(format!("{}: {e}", path.display())))?,
+        };
+
+        let mut extractor = Self::new();
+        extractor.add_pattern_entries(config.patterns)?;
+        Ok(extractor)
+    }
+
+    /// Adds the patterns of a TOML pattern file to the extractor
+    ///
+    /// ```toml
+    /// [[patterns]]
+    /// style = "semver"
+    /// pattern = '(?P<name>boost)_(?P<version>\d+_\d+_\d+)\.tar\.bz2$'
+    /// priority = 8
+    /// ```
+    ///
+    /// Every pattern is checked before any is added, so an invalid file leaves
+    /// the extractor unchanged.
+    pub fn add_patterns_from_str(&mut self, toml: &str) -> Result<(), VersionError> {
+        let config = toml::from_str::<PatternConfig>(toml)
+            .map_err(|e| VersionError::InvalidConfig(e.to_string()))?;
+        self.add_pattern_entries(config.patterns)
+    }
+
+    /// Compiles and validates pattern file entries, then adds them all
+    fn add_pattern_entries(&mut self, entries: Vec<PatternEntry>) -> Result<(), VersionError> {
+        let patterns = entries
+            .into_iter()
+            .map(|entry| {
+                let pattern = VersionPattern::new(entry.style, &entry.pattern, entry.priority)?;
+                for group in ["name", "version"] {
+                    if !pattern.pattern.capture_names().any(|n| n == Some(group)) {
+                        return Err(VersionError::MissingCaptureGroup {
+                            pattern: entry.pattern,
+                            group: group.to_string(),
+                        });
+                    }
+                }
+                Ok(pattern)
+            })
+            .collect::<Result<Vec<_>, VersionError>>()?;
+        for pattern in patterns {
+            self.add_pattern(pattern);
+        }
+        Ok(())
+    }
+
     /// Initialize with default known patterns
     fn add_default_patterns(&mut self) {
         let patterns = vec![
+            // Debian orig tarballs (i.e. `nano_8.3.orig.tar.xz`), whose upstream
+            // version may carry `+dfsg` repack or `~rc1` prerelease markers
+            VersionPattern::new(
+                VersionStyle::Simple,
+                &format!(
+                    r"(?x)
+                    ^(?P<name>[a-z0-9][a-z0-9.+-]+)
+                    _
+                    (?P<version>\d[0-9A-Za-z.+~]*?)
+                    \.orig(?:-[a-z0-9-]+)?  # Optional component name
+                    \.{ARCHIVE_SUFFIX}$
+                "
+                ),
+                3,
+            )
+            .unwrap(),
             VersionPattern::new(
                 VersionStyle::DateBased,
-                r"(?x)
+                &format!(
+                    r"(?x)
                     (?P<name>[^/]+)
                     [-_]
-                    v?(?P<version>\d{8}(?:[-]\d+\.\d+)?)
-                    (?:\.(?:tar(?:\.[^/]*)?|zip|tgz))?$
-                ",
+                    v?(?P<version>\d{{8}}(?:[-]\d+\.\d+)?)
+                    (?:\.{ARCHIVE_SUFFIX})?$
+                "
+                ),
                 5,
             )
             .unwrap(),
             VersionPattern::new(
                 VersionStyle::Semver,
-                r"(?x)
+                &format!(
+                    r"(?x)
                     (?P<name>[^/]+)
                     [-_]
                     v?(?P<version>(?:\d+[._]\d+[._]\d+
                         (?:[-.](?:rc|alpha|beta|dev|pre|post|build|\d+))*
+                        (?:\+[0-9A-Za-z.-]+?)?  # Build metadata
                     ))
-                    (?:\.(?:tar(?:\.[^/]*)?|zip|tgz))?$
-                ",
+                    {SOURCE_QUALIFIER}
+                    (?:\.{ARCHIVE_SUFFIX})?$
+                "
+                ),
                 10,
             )
             .unwrap(),
             VersionPattern::new(
                 VersionStyle::DateBased,
-                r"(?x)
+                &format!(
+                    r"(?x)
                     (?P<name>[^/]+)
                     [-_]
-                    v?(?P<version>\d{4}[._]\d{2}[._]\d{2})
+                    v?(?P<version>\d{{4}}[._]\d{{2}}[._]\d{{2}})
                     (?:[-_.][\d.]+)?  # Optional version suffix
-                    (?:\.(?:tar(?:\.[^/]*)?|zip|tgz))?$
-                ",
+                    (?:\.{ARCHIVE_SUFFIX})?$
+                "
+                ),
                 25,
             )
             .unwrap(),
             VersionPattern::new(
                 VersionStyle::Simple,
-                r"(?x)
+                &format!(
+                    r"(?x)
                     (?P<name>[^/]+)
                     [-_]
-                    v?(?P<version>\d+\.\d+)
-                    (?:\.(?:tar(?:\.[^/]*)?|zip|tgz))?$
-                ",
+                    v?(?P<version>\d+[._]\d+)
+                    {SOURCE_QUALIFIER}
+                    (?:\.{ARCHIVE_SUFFIX})?$
+                "
+                ),
                 30,
             )
             .unwrap(),
             VersionPattern::new(
                 VersionStyle::Simple,
-                r"(?x)
+                &format!(
+                    r"(?x)
                     (?P<name>[^/]+)
                     [-_]
                     v?(?P<version>\d+)
-                    (?:\.(?:tar(?:\.[^/]*)?|zip|tgz))?$
-                ",
+                    (?:\.{ARCHIVE_SUFFIX})?$
+                "
+                ),
                 35,
             )
             .unwrap(),
+            // Names carrying no separator before the version (i.e. `tcl8.6.13`)
+            VersionPattern::new(
+                VersionStyle::Simple,
+                &format!(
+                    r"(?x)
+                    (?P<name>[^/]*[a-zA-Z])
+                    (?P<version>\d+(?:\.\d+)+)
+                    {SOURCE_QUALIFIER}
+                    (?:\.{ARCHIVE_SUFFIX})?$
+                "
+                ),
+                40,
+            )
+            .unwrap(),
             VersionPattern::new(
                 VersionStyle::Simple,
-                r"(?x)
+                &format!(
+                    r"(?x)
                     (?P<name>.*?)
                     [-]
                     (?P<version>[^-/]+?)
-                    (?:\.(?:tar(?:\.[^/]*)?|zip|tgz)|\.[\w]+)?$
-                ",
+                    (?:\.{ARCHIVE_SUFFIX}|\.(?P<suffix>[a-zA-Z][a-zA-Z0-9]*))?$  # Never a version part
+                "
+                ),
                 100,
             )
             .unwrap(),
@@ -181,66 +357,491 @@ impl VersionExtractor {
     /// * `Ok(Extraction)` containing name and version if successful
     /// * `Err(VersionError)` if no version could be extracted
     pub fn extract(&self, path: &str) -> Result<Extraction, VersionError> {
-        if let Some(result) = self.try_extract_vcs_url(path) {
+        self.pick(self.extract_all(path))
+    }
+
+    /// Extracts version and name information from a URL
+    ///
+    /// Archives that forges generate from tags are named after the tag alone
+    /// (i.e. `v1.2.3.tar.gz`), so for such URLs the repository name from the
+    /// path is returned as the name. Any other URL is matched like
+    /// [`VersionExtractor::extract`] would match it.
+    pub fn extract_url(&self, url: &Url) -> Result<Extraction, VersionError> {
+        if let Some(result) = self.try_extract_vcs_url(url) {
             return result;
         }
+        self.pick(self.url_matches(url.as_str()))
+    }
+
+    /// Picks the best of the matches returned for a path, ordered by priority
+    fn pick(&self, mut matches: Vec<ScoredExtraction>) -> Result<Extraction, VersionError> {
+        if matches.is_empty() {
+            return Err(VersionError::InvalidVersion);
+        }
 
-        if let Some(filename) = path.split('/').last() {
-            for pattern in &self.patterns {
-                if let Some(caps) = pattern.pattern.captures(filename) {
-                    if let (Some(name), Some(version)) = (caps.name("name"), caps.name("version")) {
-                        return Ok(Extraction {
-                            name: name.as_str().to_string(),
-                            version: version.as_str().to_string(),
-                        });
+        // Equal-priority patterns that disagree make the result a guess
+        let top = matches[0].priority;
+        let first = &matches[0].extraction;
+        let ambiguous = matches
+            .iter()
+            .skip(1)
+            .take_while(|m| m.priority == top)
+            .any(|m| m.extraction.name != first.name || m.extraction.version != first.version);
+        if ambiguous && !self.prefer_first {
+            return Err(VersionError::AmbiguousVersion {
+                candidates: matches
+                    .into_iter()
+                    .take_while(|m| m.priority == top)
+                    .map(|m| m.extraction)
+                    .collect(),
+            });
+        }
+
+        Ok(matches.swap_remove(0).extraction)
+    }
+
+    /// Extracts every candidate name and version, in pattern priority order
+    ///
+    /// Unlike [`VersionExtractor::extract`], this returns the matches of all
+    /// patterns so callers can review how a path could be interpreted and
+    /// choose between them. Forge archive URLs yield their tag's version, or
+    /// each candidate when the tag and file name disagree.
+    pub fn extract_all(&self, path: &str) -> Vec<ScoredExtraction> {
+        if let Some(result) = Url::parse(path)
+            .ok()
+            .and_then(|url| self.try_extract_vcs_url(&url))
+        {
+            let candidates = match result {
+                Ok(extraction) => vec![extraction],
+                Err(VersionError::AmbiguousVersion { candidates }) => candidates,
+                Err(_) => vec![],
+            };
+            return candidates
+                .into_iter()
+                .map(|extraction| ScoredExtraction::new(0, extraction))
+                .collect();
+        }
+        self.url_matches(path)
+    }
+
+    /// Matches `path`, falling back to file names carried in a URL's query or fragment
+    ///
+    /// Download scripts often name the file in a `file` or `filename` query
+    /// parameter (i.e. `download.php?file=foo-1.2.3.tar.gz`) or in the fragment,
+    /// which are only consulted when the path itself yields no match.
+    fn url_matches(&self, path: &str) -> Vec<ScoredExtraction> {
+        let Ok(mut url) = Url::parse(path) else {
+            return self.matches(path);
+        };
+        if url.query().is_none() && url.fragment().is_none() {
+            return self.matches(path);
+        }
+
+        let mut filenames = url
+            .query_pairs()
+            .filter(|(key, _)| key == "file" || key == "filename")
+            .map(|(_, value)| value.into_owned())
+            .collect::<Vec<_>>();
+        filenames.extend(url.fragment().map(String::from));
+        url.set_query(None);
+        url.set_fragment(None);
+
+        let matches = self.matches(url.as_str());
+        if !matches.is_empty() {
+            return matches;
+        }
+        filenames
+            .iter()
+            .map(|filename| self.matches(filename))
+            .find(|matches| !matches.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Matches the file name of `path` against each pattern, with its priority
+    fn matches(&self, path: &str) -> Vec<ScoredExtraction> {
+        let mut segments = path.rsplit('/');
+        let Some(filename) = segments.next() else {
+            return vec![];
+        };
+        let parent = segments.next();
+
+        self.patterns
+            .iter()
+            .filter_map(|pattern| {
+                let caps = pattern.pattern.captures(filename)?;
+                let name = caps.name("name")?.as_str();
+                let version = caps.name("version")?;
+                // A dotted prerelease word (i.e. `.rc1`) belongs to the version
+                let version = match caps.name("suffix") {
+                    Some(suffix) if is_prerelease(suffix.as_str()) => {
+                        &filename[version.start()..suffix.end()]
                     }
+                    _ => version.as_str(),
+                };
+                if is_numbered_word(name, version) {
+                    return None;
+                }
+                Some(ScoredExtraction::new(
+                    pattern.priority,
+                    Extraction {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                        style: classify_series(parent, version)
+                            .unwrap_or_else(|| pattern.style.clone()),
+                        from_tag: false,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Attempts to extract version info from archive URLs of known forges
+    ///
+    /// Layouts unique to a forge, such as GitHub's `archive/refs/tags/` or
+    /// GitLab's `-/archive/<ref>/`, are recognised on any host
+    /// so self-hosted instances work too. The shorter `archive/<tag>` layout
+    /// is only trusted on the public forges using it. For release assets the
+    /// file name has to agree with the tag, and its spelling of the version is
+    /// preferred (i.e. `8.11.1` from `curl-8.11.1.tar.xz` over tag `curl-8_11_1`).
+    fn try_extract_vcs_url(&self, url: &Url) -> Option<Result<Extraction, VersionError>> {
+        let segments = url
+            .path_segments()?
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut asset = None;
+        let (project, reference) = match (url.host_str()?, segments.as_slice()) {
+            // i.e. /<owner>/<repo>/tar.gz/refs/tags/v1.2.3
+            ("codeload.github.com", [_, project, _, .., tag]) => (*project, tag.to_string()),
+            (_, [.., project, "archive", "refs", "tags", tag]) => (*project, tag.to_string()),
+            // i.e. /<group>/<project>/-/archive/v1.2.3/<project>-v1.2.3.tar.gz
+            (_, [.., project, "-", "archive", reference, _]) => (*project, reference.to_string()),
+            (_, [.., project, "repository", archive]) if archive.starts_with("archive.") => {
+                let reference = url
+                    .query_pairs()
+                    .find(|(key, _)| key == "ref" || key == "sha")?
+                    .1;
+                let suffix = archive.strip_prefix("archive").unwrap_or_default();
+                (*project, format!("{reference}{suffix}"))
+            }
+            // i.e. /<owner>/<repo>/releases/download/v1.2.3/app-linux.tar.gz
+            (_, [.., project, "releases", "download", tag, file]) => {
+                // Rolling tags such as `nightly` carry no version at all
+                if !tag.contains(|c: char| c.is_ascii_digit()) {
+                    return Some(Err(VersionError::InvalidVersion));
                 }
+                asset = Some(*file);
+                (*project, tag.to_string())
             }
+            ("bitbucket.org", [_, project, "get", tag]) => (*project, tag.to_string()),
+            (
+                "github.com" | "codeberg.org" | "gitea.com" | "git.sr.ht",
+                [_, project, "archive", tag],
+            ) => (*project, tag.to_string()),
+            _ => return None,
+        };
+
+        let faux = format!("{project}-{reference}");
+        let result = self.extract(&faux).map(|matched| Extraction {
+            name: project.to_string(),
+            from_tag: true,
+            ..matched
+        });
+        Some(match asset {
+            Some(asset) => result.and_then(|tagged| self.confirm_asset(tagged, asset)),
+            None => result,
+        })
+    }
+
+    /// Checks a version taken from a release tag against the asset file name
+    ///
+    /// Asset names often carry no version at all (i.e. `app-linux.tar.gz`),
+    /// which is accepted, but one naming a different version is ambiguous.
+    /// When both agree, the file name's spelling of the version is returned.
+    fn confirm_asset(&self, tagged: Extraction, asset: &str) -> Result<Extraction, VersionError> {
+        let Ok(named) = self.extract(asset) else {
+            return Ok(tagged);
+        };
+        if named.normalized_version() == tagged.normalized_version() {
+            return Ok(Extraction {
+                version: named.version,
+                ..tagged
+            });
+        }
+        let conflicting = named.version.starts_with(|c: char| c.is_ascii_digit())
+            && !asset.contains(&tagged.version);
+        if conflicting && !self.prefer_first {
+            return Err(VersionError::AmbiguousVersion {
+                candidates: vec![tagged, named],
+            });
         }
+        Ok(tagged)
+    }
+}
 
-        Err(VersionError::InvalidVersion)
+/// Prerelease markers standing as words of their own, see [`is_prerelease`]
+static PRERELEASE_MARKERS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:^|[^a-z])(?:rc|alpha|beta|dev|pre|preview)(?:[^a-z]|$)").unwrap()
+});
+
+/// Runs of digits or letters, the parts a [`Version`] is compared by
+static VERSION_PARTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+|[a-zA-Z]+").unwrap());
+
+/// Whether a version or tag name looks like a prerelease (i.e. `v2.0.0-rc1`, `1.4-beta.2`)
+///
+/// Matches the rc/alpha/beta/dev/pre/preview markers as standalone words, so
+/// names such as `develop` or `prefix-1.0` are not flagged.
+pub fn is_prerelease(version: &str) -> bool {
+    PRERELEASE_MARKERS.is_match(version)
+}
+
+/// A version string parsed into parts that order the way releases do
+///
+/// Leading numbers are compared numerically (so `1.10` sorts after `1.9`), a
+/// release sorts after its prereleases (`1.0` after `1.0-rc1`), and whatever
+/// follows the leading numbers is compared part by part (`1.0.2` before `1.0.2k`).
+/// A leading `v` is ignored, so `v1.0` and `1.0` are equal. Semver build
+/// metadata or a Debian repack suffix only breaks ties, so `1.2.3+dfsg` sorts
+/// just above `1.2.3` but below `1.2.4`.
+///
+/// # Examples
+/// ```
+/// use upstreams_rs::versioning::Version;
+/// let rc: Version = "2.0.0-rc.1".parse()?;
+/// assert!(rc < Version::from("2.0.0"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Version {
+    raw: String,
+    release: Vec<u64>,
+    stable: bool,
+    suffix: Vec<VersionPart>,
+    build: Vec<VersionPart>,
+}
+
+/// A run of letters or digits in a version string
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum VersionPart {
+    Text(String),
+    Number(u64),
+}
+
+impl Version {
+    /// Parses a version, rejecting strings without any digits such as `main`
+    ///
+    /// Use [`Version::from`] to accept any string instead.
+    pub fn parse(version: &str) -> Result<Self, VersionError> {
+        version.parse()
     }
 
-    /// Attempts to extract version info from GitHub/GitLab URLs
-    fn try_extract_vcs_url(&self, path: &str) -> Option<Result<Extraction, VersionError>> {
-        if !path.contains("github.com") && !path.contains("gitlab.com") {
-            return None;
+    /// Returns the version string this was parsed from
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// The parts that decide the ordering of versions
+    fn key(&self) -> (&[u64], bool, &[VersionPart], &[VersionPart]) {
+        (&self.release, self.stable, &self.suffix, &self.build)
+    }
+}
+
+impl From<&str> for Version {
+    fn from(version: &str) -> Self {
+        let trimmed = version.trim_start_matches(['v', 'V']);
+        // Build metadata (i.e. `+build.5` or `+dfsg`) is only compared last
+        let (trimmed, build) = trimmed.split_once('+').unwrap_or((trimmed, ""));
+        let split = |text: &str| {
+            VERSION_PARTS
+                .find_iter(text)
+                .map(|m| match m.as_str().parse() {
+                    Ok(n) => VersionPart::Number(n),
+                    Err(_) => VersionPart::Text(m.as_str().to_lowercase()),
+                })
+                .collect::<Vec<_>>()
+        };
+        let parts = split(trimmed);
+        let numeric = parts
+            .iter()
+            .take_while(|p| matches!(p, VersionPart::Number(_)))
+            .count();
+
+        Self {
+            raw: version.to_string(),
+            release: parts[..numeric]
+                .iter()
+                .filter_map(|p| match p {
+                    VersionPart::Number(n) => Some(*n),
+                    VersionPart::Text(_) => None,
+                })
+                .collect(),
+            stable: !is_prerelease(trimmed),
+            suffix: parts[numeric..].to_vec(),
+            build: split(build),
         }
+    }
+}
 
-        let url = Url::parse(path).ok()?;
-
-        match url.host_str() {
-            Some("github.com") if url.path().contains("archive/refs/tags/") => {
-                let parts: Vec<&str> = url.path().split('/').collect();
-                let project = parts.get(2)?;
-                let asset = parts.last()?;
-                let faux = format!("{}-{}", project, asset);
-                Some(self.extract(&faux).map(|matched| Extraction {
-                    name: project.to_string(),
-                    ..matched
-                }))
-            }
-            Some("gitlab.com") if url.path().contains("repository/archive.tar.gz") => {
-                let parts: Vec<&str> = url.path().split('/').collect();
-                let project = parts.get(2)?;
-                let faux = format!("{}-archive.tar.gz", project);
-                Some(self.extract(&faux).map(|matched| Extraction {
-                    name: project.to_string(),
-                    ..matched
-                }))
-            }
-            _ => None,
+impl FromStr for Version {
+    type Err = VersionError;
+
+    /// Parses a version, rejecting strings without any digits such as `main`
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        if !version.chars().any(|c| c.is_ascii_digit()) {
+            return Err(VersionError::InvalidVersion);
         }
+        Ok(Self::from(version))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Sorts versions newest first by their [`Version`] ordering
+///
+/// Semver, date-based and mixed schemes are compared part by part, and a
+/// prerelease sorts below the release it leads up to. Versions that compare
+/// equal keep their relative order.
+pub fn sort_versions(versions: &mut [VersionMetadata]) {
+    versions.sort_by_cached_key(|v| Reverse(Version::from(v.version.as_str())));
+}
+
+/// Returns the newest stable version, or the newest prerelease if there is no stable one
+///
+/// The versions do not need to be sorted.
+pub fn latest(versions: &[VersionMetadata]) -> Option<&VersionMetadata> {
+    let newest = |stable: bool| {
+        versions
+            .iter()
+            .filter(|v| stable != v.prerelease)
+            .max_by_key(|v| Version::from(v.version.as_str()))
+    };
+    newest(true).or_else(|| newest(false))
+}
+
+/// Whether `version` is a bare integer completing a word at the end of `name`
+fn is_numbered_word(name: &str, version: &str) -> bool {
+    let word = name.rsplit(['-', '_', '.']).next().unwrap_or(name);
+    version.chars().all(|c| c.is_ascii_digit())
+        && NUMBERED_WORDS.iter().any(|w| w.eq_ignore_ascii_case(word))
+}
+
+/// Detects GNOME-style release series, where the file lives in a directory
+/// named after its series (i.e. `sources/gtk+/3.24/gtk+-3.24.33.tar.xz`)
+fn classify_series(parent: Option<&str>, version: &str) -> Option<VersionStyle> {
+    let series = parent?;
+    let in_series = !series.is_empty()
+        && series.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && version
+            .strip_prefix(series)
+            .is_some_and(|rest| rest.starts_with('.'));
+    in_series.then_some(VersionStyle::ReleaseSeries)
+}
+
 /// Holds the extracted version information
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub struct Extraction {
     /// Project/package name
     pub name: String,
     /// Version string
     pub version: String,
+    /// The versioning style the version was recognised as
+    pub style: VersionStyle,
+    /// Whether the version was taken from the tag in a forge archive or release
+    /// URL (e.g. archive/refs/tags/v1.2.3) rather than from a file name
+    pub from_tag: bool,
+}
+
+/// A candidate returned by [`VersionExtractor::extract_all`]
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ScoredExtraction {
+    /// The name, version and style of the candidate
+    pub extraction: Extraction,
+    /// Priority of the pattern that matched, lower patterns are tried first
+    pub priority: u8,
+    /// How specific the match is, from 0.0 to 1.0
+    ///
+    /// Patterns tried first are stricter, and a version made of several numbers
+    /// leaves less room for another split than a single number or a word.
+    pub confidence: f32,
+}
+
+impl ScoredExtraction {
+    fn new(priority: u8, extraction: Extraction) -> Self {
+        let pattern = 1.0 - f32::from(priority.min(190)) / 200.0;
+        let shape = match Version::from(extraction.version.as_str()).release.len() {
+            0 => 0.25,
+            1 => 0.6,
+            2 => 0.85,
+            _ => 1.0,
+        };
+        Self {
+            extraction,
+            priority,
+            confidence: pattern * shape,
+        }
+    }
+}
+
+/// Replaces underscores separating the numbers of a version with dots
+/// (i.e. `1_84_0` => `1.84.0`)
+///
+/// Versions with a component that is not a number are returned unchanged, as
+/// their underscores may be part of a suffix rather than separators.
+pub fn normalize_version(version: &str) -> String {
+    let numeric = version
+        .split(['.', '_'])
+        .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    if numeric {
+        version.replace('_', ".")
+    } else {
+        version.to_string()
+    }
+}
+
+impl Extraction {
+    /// Returns the version with underscores replaced by dots (i.e. `17_0_2` => `17.0.2`)
+    ///
+    /// [`Extraction::version`] keeps the spelling of the file name so it can be
+    /// substituted back into download URLs; this form is meant for comparisons.
+    /// See [`normalize_version`].
+    pub fn normalized_version(&self) -> String {
+        normalize_version(&self.version)
+    }
+
+    /// Returns the release series of a [`VersionStyle::ReleaseSeries`] version
+    ///
+    /// The series is the version without its final component, i.e. `3.24`
+    /// for `3.24.33` or `46` for `46.1`.
+    pub fn series(&self) -> Option<&str> {
+        if self.style != VersionStyle::ReleaseSeries {
+            return None;
+        }
+        self.version.rsplit_once('.').map(|(series, _)| series)
+    }
 }
 
 #[cfg(test)]
@@ -254,78 +855,160 @@ mod tests {
             Extraction {
                 version: "1.50.0".to_string(),
                 name: "NetworkManager".to_string(),
+                style: VersionStyle::ReleaseSeries,
+                from_tag: false,
             }
         ), (
             "https://github.com/cli/cli/archive/refs/tags/v2.63.2.tar.gz",
             Extraction {
                 version: "2.63.2".to_string(),
                 name: "cli".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: true,
             }
         ), (
             "https://www.x.org/pub/individual/xserver/xwayland-24.1.4.tar.xz",
             Extraction {
                 version: "24.1.4".to_string(),
                 name: "xwayland".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: false,
             }
         ), (
             "https://download.gnome.org/sources/gtk+/3.24/gtk+-3.24.33.tar.xz",
             Extraction {
                 version: "3.24.33".to_string(),
                 name: "gtk+".to_string(),
+                style: VersionStyle::ReleaseSeries,
+                from_tag: false,
             }
         ), (
             "https://www.nano-editor.org/dist/v8/nano-8.3.tar.xz",
             Extraction {
                 version: "8.3".to_string(),
                 name: "nano".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
             }
         ), (
             "https://cdn.kernel.org/pub/linux/kernel/v6.x/linux-6.13.4.tar.xz",
             Extraction {
                 version: "6.13.4".to_string(),
                 name: "linux".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: false,
             }
         ), (
             "https://github.com/intel/Intel-Linux-Processor-Microcode-Data-Files/archive/refs/tags/microcode-20250211.tar.gz",
             Extraction {
                 version: "20250211".to_string(),
                 name: "Intel-Linux-Processor-Microcode-Data-Files".to_string(),
+                style: VersionStyle::DateBased,
+                from_tag: true,
             }
         ), (
             "https://download.gnome.org/sources/gnome-disk-utility/46/gnome-disk-utility-46.1.tar.xz",
             Extraction {
                 version: "46.1".to_string(),
                 name: "gnome-disk-utility".to_string(),
+                style: VersionStyle::ReleaseSeries,
+                from_tag: false,
             }
         ), (
             "https://thrysoee.dk/editline/libedit-20221030-3.1.tar.gz",
             Extraction {
                 version: "20221030-3.1".to_string(),
                 name: "libedit".to_string(),
+                style: VersionStyle::DateBased,
+                from_tag: false,
             }
         ), (
             "https://www.sudo.ws/dist/sudo-1.9.16p2.tar.gz",
             Extraction {
                 version: "1.9.16p2".to_string(),
                 name: "sudo".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
             }
         ), (
             "https://download.nvidia.com/XFree86/nvidia-persistenced/nvidia-persistenced-570.86.16.tar.bz2",
             Extraction {
                 version: "570.86.16".to_string(),
                 name: "nvidia-persistenced".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: false,
             }
         ), (
             "https://us.download.nvidia.com/XFree86/Linux-x86_64/570.86.16/NVIDIA-Linux-x86_64-570.86.16.run",
             Extraction {
                 version: "570.86.16".to_string(),
                 name: "NVIDIA-Linux-x86_64".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
             }
         ), (
             "https://github.com/pop-os/cosmic-applets/archive/refs/tags/epoch-1.0.0-alpha.6.tar.gz",
             Extraction {
                 version: "1.0.0-alpha.6".to_string(),
                 name: "cosmic-applets".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: true,
+            }
+        ), (
+            "https://codeload.github.com/cli/cli/tar.gz/refs/tags/v2.63.2",
+            Extraction {
+                version: "2.63.2".to_string(),
+                name: "cli".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: true,
+            }
+        ), (
+            "https://codeload.github.com/redis/redis/zip/7.2.1",
+            Extraction {
+                version: "7.2.1".to_string(),
+                name: "redis".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: true,
+            }
+        ), (
+            "https://deb.debian.org/debian/pool/main/n/nano/nano_8.3.orig.tar.xz",
+            Extraction {
+                version: "8.3".to_string(),
+                name: "nano".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
+            }
+        ), (
+            "pkg_1.2.3.orig.tar.gz",
+            Extraction {
+                version: "1.2.3".to_string(),
+                name: "pkg".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
+            }
+        ), (
+            "pkg_1.2.3+dfsg.orig.tar.xz",
+            Extraction {
+                version: "1.2.3+dfsg".to_string(),
+                name: "pkg".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
+            }
+        ), (
+            "pkg_1.2.3~rc1.orig.tar.gz",
+            Extraction {
+                version: "1.2.3~rc1".to_string(),
+                name: "pkg".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
+            }
+        ), (
+            "https://deb.debian.org/debian/pool/main/l/lib-ogg/libogg_1.3.5.orig-docs.tar.gz",
+            Extraction {
+                version: "1.3.5".to_string(),
+                name: "libogg".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
             }
         )
         ];
@@ -338,4 +1021,634 @@ mod tests {
             assert_eq!(result, expected);
         }
     }
+
+    /// Tests that forge archive URLs are named after the repository
+    #[test]
+    fn test_extract_url() {
+        let extractor = VersionExtractor::new();
+        for (url, name, version) in [
+            (
+                "https://github.com/cli/cli/archive/refs/tags/v2.63.2.tar.gz",
+                "cli",
+                "2.63.2",
+            ),
+            (
+                "https://github.example.com/team/tool/archive/refs/tags/1.4.0.zip",
+                "tool",
+                "1.4.0",
+            ),
+            (
+                "https://gitlab.com/inkscape/inkscape/repository/archive.tar.gz?ref=INKSCAPE_1_4",
+                "inkscape",
+                "1_4",
+            ),
+            (
+                "https://gitlab.freedesktop.org/mesa/drm/repository/archive.tar.bz2?ref=libdrm-2.4.124",
+                "drm",
+                "2.4.124",
+            ),
+            (
+                "https://gitlab.com/gitlab-org/gitlab-runner/-/archive/v17.6.0/gitlab-runner-v17.6.0.tar.gz",
+                "gitlab-runner",
+                "17.6.0",
+            ),
+            (
+                "https://gitlab.gnome.org/GNOME/libxml2/-/archive/v2.13.5/libxml2-v2.13.5.tar.bz2",
+                "libxml2",
+                "2.13.5",
+            ),
+            (
+                "https://gitlab.com/group/subgroup/proj/-/archive/1.2.3/proj-1.2.3.zip",
+                "proj",
+                "1.2.3",
+            ),
+            (
+                "https://codeberg.org/forgejo/forgejo/archive/v9.0.3.tar.gz",
+                "forgejo",
+                "9.0.3",
+            ),
+            (
+                "https://github.com/owner/repo/releases/download/v1.2.3/app-linux.tar.gz",
+                "repo",
+                "1.2.3",
+            ),
+            (
+                "https://github.com/cli/cli/releases/download/v2.63.2/gh_2.63.2_linux_amd64.tar.gz",
+                "cli",
+                "2.63.2",
+            ),
+            (
+                "https://github.com/curl/curl/releases/download/curl-8_11_1/curl-8.11.1.tar.xz",
+                "curl",
+                "8.11.1",
+            ),
+            (
+                "https://github.com/libexpat/libexpat/releases/download/R_2_6_4/expat-2.6.4.tar.xz",
+                "libexpat",
+                "2.6.4",
+            ),
+            (
+                "https://bitbucket.org/multicoreware/x265_git/get/4.1.tar.gz",
+                "x265_git",
+                "4.1",
+            ),
+        ] {
+            let url = Url::parse(url).unwrap();
+            let result = extractor.extract_url(&url).unwrap();
+            assert_eq!((result.name.as_str(), result.version.as_str()), (name, version), "{url}");
+            assert!(result.from_tag, "{url}");
+            assert_eq!(extractor.extract(url.as_str()).unwrap(), result);
+        }
+
+        // An asset naming another version than its release tag is a guess
+        let url =
+            Url::parse("https://github.com/owner/repo/releases/download/v1.2.3/repo-1.2.2.tar.gz")
+                .unwrap();
+        assert!(matches!(
+            extractor.extract_url(&url),
+            Err(VersionError::AmbiguousVersion { .. })
+        ));
+
+        // Rolling tags carry no version, whatever the asset is called
+        let url = Url::parse(
+            "https://github.com/neovim/neovim/releases/download/nightly/nvim-linux64.tar.gz",
+        )
+        .unwrap();
+        assert!(extractor.extract_url(&url).is_err());
+        assert!(extractor.extract_all(url.as_str()).is_empty());
+
+        // Other URLs are matched by file name
+        let url = Url::parse("https://ftp.gnu.org/gnu/make/make-4.4.1.tar.gz").unwrap();
+        let result = extractor.extract_url(&url).unwrap();
+        assert_eq!(
+            (result.name.as_str(), result.version.as_str()),
+            ("make", "4.4.1")
+        );
+        assert_eq!(result.style, VersionStyle::Semver);
+    }
+
+    /// Tests that each compression suffix is stripped from the version
+    #[test]
+    fn test_archive_suffixes() {
+        let extractor = VersionExtractor::new();
+        for suffix in [
+            "tar", "tar.gz", "tar.bz2", "tar.xz", "tar.zst", "tar.lz", "tar.lzma", "tar.lz4",
+            "tar.Z", "tgz", "tbz", "tbz2", "txz", "tzst", "tlz", "zip",
+        ] {
+            let path = format!("https://example.com/dist/foo-1.2.{suffix}");
+            let result = extractor.extract(&path).expect("Failed to extract version");
+            assert_eq!(
+                result,
+                Extraction {
+                    name: "foo".to_string(),
+                    style: VersionStyle::Simple,
+                    from_tag: false,
+                    version: "1.2".to_string(),
+                },
+                "suffix: {suffix}"
+            );
+        }
+    }
+
+    /// Tests that build metadata is kept in the version and only breaks ties when comparing
+    #[test]
+    fn test_build_metadata() {
+        let extractor = VersionExtractor::new();
+        for path in [
+            "foo-1.2.3+build.5.tar.gz",
+            "https://example.com/dist/foo-1.2.3+build.5.tar.gz",
+            "foo-1.2.3+build.5",
+        ] {
+            let result = extractor.extract(path).expect("Failed to extract version");
+            assert_eq!(
+                result,
+                Extraction {
+                    name: "foo".to_string(),
+                    style: VersionStyle::Semver,
+                    from_tag: false,
+                    version: "1.2.3+build.5".to_string(),
+                },
+                "{path}"
+            );
+        }
+
+        let version = Version::from("1.2.3+build.5");
+        assert!(version > Version::from("1.2.3"));
+        assert!(version < Version::from("1.2.4"));
+
+        // Debian repacks sort above the upstream release they repack
+        let dfsg = Version::from("1.2.3+dfsg");
+        assert!(dfsg > Version::from("1.2.3"));
+        assert!(dfsg < Version::from("1.2.3+dfsg1"));
+        assert!(dfsg < Version::from("1.2.4"));
+        assert_ne!(dfsg, Version::from("1.2.3"));
+    }
+
+    /// Tests that GNOME-style release series are recognised and queryable
+    #[test]
+    fn test_release_series() {
+        let extractor = VersionExtractor::new();
+        let gtk = extractor
+            .extract("https://download.gnome.org/sources/gtk+/3.24/gtk+-3.24.33.tar.xz")
+            .unwrap();
+        assert_eq!(gtk.style, VersionStyle::ReleaseSeries);
+        assert_eq!(gtk.series(), Some("3.24"));
+
+        let gdu = extractor
+            .extract("https://download.gnome.org/sources/gnome-disk-utility/46/gnome-disk-utility-46.1.tar.xz")
+            .unwrap();
+        assert_eq!(gdu.series(), Some("46"));
+
+        let linux = extractor
+            .extract("https://cdn.kernel.org/pub/linux/kernel/v6.x/linux-6.13.4.tar.xz")
+            .unwrap();
+        assert_eq!(linux.style, VersionStyle::Semver);
+        assert_eq!(linux.series(), None);
+    }
+
+    #[test]
+    fn test_is_prerelease() {
+        for version in [
+            "v2.0.0-rc1",
+            "1.4-beta.2",
+            "3.0.0alpha",
+            "1.0.0.dev3",
+            "6.0-pre",
+        ] {
+            assert!(is_prerelease(version), "{version}");
+        }
+        for version in [
+            "v2.63.2",
+            "develop",
+            "prefix-1.0",
+            "20250211",
+            "epoch-1.0.0",
+        ] {
+            assert!(!is_prerelease(version), "{version}");
+        }
+    }
+
+    /// Tests that disagreeing patterns of equal priority are reported
+    #[test]
+    fn test_ambiguous_version() {
+        let mut extractor = VersionExtractor::new();
+        // Competes with the default semver pattern, but drops the patch level
+        extractor.add_pattern(
+            VersionPattern::new(
+                VersionStyle::Semver,
+                r"(?P<name>[^/]+?)-(?P<version>\d+\.\d+)\.\d+-\d+\.tar\.gz$",
+                10,
+            )
+            .unwrap(),
+        );
+
+        let path = "https://example.com/foo-1.2.3-1.tar.gz";
+        match extractor.extract(path) {
+            Err(VersionError::AmbiguousVersion { candidates }) => {
+                let versions = candidates
+                    .iter()
+                    .map(|c| c.version.as_str())
+                    .collect::<Vec<_>>();
+                assert_eq!(versions.len(), 2);
+                assert_eq!(versions, vec!["1.2.3-1", "1.2"]);
+            }
+            other => panic!("expected an ambiguous version, got {other:?}"),
+        }
+        assert!(extractor.extract_all(path).len() > 2);
+
+        let extractor = extractor.prefer_first(true);
+        assert_eq!(extractor.extract(path).unwrap().version, "1.2.3-1");
+
+        // Agreeing candidates are not ambiguous
+        let result = extractor
+            .extract("https://example.com/foo-1.2.3.tar.gz")
+            .unwrap();
+        assert_eq!(result.version, "1.2.3");
+    }
+
+    /// Tests that every candidate is returned with its priority and confidence
+    #[test]
+    fn test_extract_all() {
+        let extractor = VersionExtractor::new();
+        let path = "https://download.gnome.org/sources/gtk+/3.24/gtk+-3.24.33.tar.xz";
+        let all = extractor.extract_all(path);
+        assert!(all.len() > 1);
+        assert!(all.windows(2).all(|w| w[0].priority <= w[1].priority));
+        assert!(all.iter().all(|c| (0.0..=1.0).contains(&c.confidence)));
+        assert_eq!(all[0].extraction, extractor.extract(path).unwrap());
+        assert_eq!(all[0].extraction.style, VersionStyle::ReleaseSeries);
+        assert!(all[0].confidence > all[all.len() - 1].confidence);
+
+        let all = extractor.extract_all("NVIDIA-Linux-x86_64-570.86.16.run");
+        assert_eq!(all[0].extraction.name, "NVIDIA-Linux-x86_64");
+        assert_eq!(all[0].extraction.version, "570.86.16");
+
+        // A release asset disagreeing with its tag yields both versions
+        let all = extractor.extract_all(
+            "https://github.com/owner/repo/releases/download/v1.2.3/repo-1.2.2.tar.gz",
+        );
+        let versions = all
+            .iter()
+            .map(|c| c.extraction.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, ["1.2.3", "1.2.2"]);
+
+        assert!(extractor.extract_all("README").is_empty());
+    }
+
+    /// Tests that underscore separated versions keep their spelling but compare as dotted
+    #[test]
+    fn test_underscore_versions() {
+        let extractor = VersionExtractor::new();
+        let jdk = extractor.extract("jdk_17_0_2.tar.gz").unwrap();
+        assert_eq!(jdk.name, "jdk");
+        assert_eq!(jdk.version, "17_0_2");
+        assert_eq!(jdk.normalized_version(), "17.0.2");
+        assert_eq!(Version::from(jdk.version.as_str()), Version::from("17.0.2"));
+
+        let jdk = extractor.extract("jdk_21_0.tar.gz").unwrap();
+        assert_eq!(jdk.version, "21_0");
+        assert_eq!(jdk.normalized_version(), "21.0");
+
+        for (path, name, version) in [
+            ("boost_1_84_0.tar.bz2", "boost", "1_84_0"),
+            (
+                "https://archives.boost.io/release/1.84.0/source/boost_1_84_0.tar.bz2",
+                "boost",
+                "1_84_0",
+            ),
+            ("boost_python_1_84_0.tar.gz", "boost_python", "1_84_0"),
+            ("wxWidgets-3.2.4.tar.bz2", "wxWidgets", "3.2.4"),
+        ] {
+            let extraction = extractor.extract(path).unwrap();
+            assert_eq!(extraction.name, name, "{path}");
+            assert_eq!(extraction.version, version, "{path}");
+            assert_eq!(extraction.normalized_version(), normalize_version(version));
+        }
+        assert_eq!(normalize_version("1_84_0"), "1.84.0");
+        assert_eq!(normalize_version("3.2.4"), "3.2.4");
+        // Underscores are only separators when every component is a number
+        assert_eq!(normalize_version("1_0_rc1"), "1_0_rc1");
+
+        let firefox = extractor
+            .extract("https://ftp.mozilla.org/pub/firefox/releases/128.0/source/firefox_128.0.source.tar.xz")
+            .unwrap();
+        assert_eq!(firefox.name, "firefox");
+        assert_eq!(firefox.version, "128.0");
+        assert_eq!(firefox.style, VersionStyle::Simple);
+    }
+
+    /// Tests that the fallback pattern only strips a known archive suffix or a
+    /// single dotted word after the version, keeping prerelease words
+    #[test]
+    fn test_fallback_pattern() {
+        let extractor = VersionExtractor::new();
+        for (path, name, version) in [
+            ("foo-bar-1.2.3.tar.gz", "foo-bar", "1.2.3"),
+            ("foo.bar-2.0.zip", "foo.bar", "2.0"),
+            ("foo-bar.baz-1.2", "foo-bar.baz", "1.2"),
+            ("foo-1.2.3a.tar.zst", "foo", "1.2.3a"),
+            ("tool-1.2.3.run", "tool", "1.2.3"),
+            ("foo-1.2.rc1", "foo", "1.2.rc1"),
+            ("foo-1.0.beta", "foo", "1.0.beta"),
+            ("pkg-3.1.dev", "pkg", "3.1.dev"),
+        ] {
+            let fallback = extractor
+                .extract_all(path)
+                .into_iter()
+                .find(|c| c.priority == 100)
+                .unwrap_or_else(|| panic!("no fallback match for {path}"));
+            assert_eq!(fallback.extraction.name, name, "{path}");
+            assert_eq!(fallback.extraction.version, version, "{path}");
+
+            let extraction = extractor.extract(path).unwrap();
+            assert_eq!(
+                (extraction.name.as_str(), extraction.version.as_str()),
+                (name, version)
+            );
+        }
+    }
+
+    /// Tests that versions directly following the name are split off at the first digit
+    #[test]
+    fn test_unseparated_versions() {
+        let extractor = VersionExtractor::new();
+        for (path, name, version) in [
+            ("tcl8.6.13.tar.gz", "tcl", "8.6.13"),
+            (
+                "https://prdownloads.sourceforge.net/tcl/tcl8.6.13-src.tar.gz",
+                "tcl",
+                "8.6.13",
+            ),
+            ("python3.11.tar.xz", "python", "3.11"),
+            ("db-5.3.28.tar.gz", "db", "5.3.28"),
+            ("gtk+-3.24.33.tar.xz", "gtk+", "3.24.33"),
+            (
+                "nvidia-persistenced-570.86.16.tar.bz2",
+                "nvidia-persistenced",
+                "570.86.16",
+            ),
+        ] {
+            let result = extractor.extract(path).unwrap();
+            assert_eq!(
+                (result.name.as_str(), result.version.as_str()),
+                (name, version),
+                "{path}"
+            );
+        }
+
+        // A single number is not enough to tell the version from the name
+        assert!(extractor.extract("mp3.tar.gz").is_err());
+    }
+
+    /// Tests that custom patterns are loaded from TOML and JSON and validated
+    #[test]
+    fn test_pattern_config() {
+        let mut extractor = VersionExtractor::new();
+        let path = "https://archives.boost.io/release/1.84.0/source/boost_1_84_0.tar.bz2";
+
+        extractor
+            .add_patterns_from_str(
+                r#"
+                [[patterns]]
+                style = "semver"
+                pattern = '(?P<name>boost)_(?P<version>\d+_\d+_\d+)\.tar\.bz2$'
+                priority = 1
+                "#,
+            )
+            .unwrap();
+        let boost = extractor.extract(path).unwrap();
+        assert_eq!(
+            (boost.name.as_str(), boost.version.as_str()),
+            ("boost", "1_84_0")
+        );
+        assert_eq!(boost.style, VersionStyle::Semver);
+        assert_eq!(extractor.extract_all(path)[0].priority, 1);
+        // Defaults are kept
+        assert_eq!(extractor.extract("nano-8.3.tar.xz").unwrap().version, "8.3");
+
+        let missing = r#"
+            [[patterns]]
+            style = "simple"
+            pattern = '(?P<name>foo)-(?P<version>\d+)\.tar\.gz$'
+            priority = 1
+
+            [[patterns]]
+            style = "simple"
+            pattern = 'bar-(\d+)\.tar\.gz$'
+            priority = 1
+        "#;
+        let mut extractor = VersionExtractor::new();
+        assert!(matches!(
+            extractor.add_patterns_from_str(missing),
+            Err(VersionError::MissingCaptureGroup { group, .. }) if group == "name"
+        ));
+        for path in ["foo-1.tar.gz", "bar-1.tar.gz"] {
+            assert!(extractor.extract_all(path).iter().all(|c| c.priority != 1));
+        }
+        assert!(matches!(
+            extractor.add_patterns_from_str("[[patterns]]\nstyle = \"odd\""),
+            Err(VersionError::InvalidConfig(_))
+        ));
+
+        let path = std::env::temp_dir().join(format!("patterns-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"patterns": [{"style": "simple", "pattern": "(?P<name>ffmpeg)-(?P<version>\\d+\\.\\d+[a-z])\\.tar\\.gz$", "priority": 1}]}"#,
+        )
+        .unwrap();
+        let extractor = VersionExtractor::from_config(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            extractor.extract("ffmpeg-4.4b.tar.gz").unwrap().version,
+            "4.4b"
+        );
+        assert!(matches!(
+            VersionExtractor::from_config(&path),
+            Err(VersionError::InvalidConfig(_))
+        ));
+    }
+
+    /// Tests that bare integers are versions unless they complete a word such as `utf-8`
+    #[test]
+    fn test_single_number_versions() {
+        let extractor = VersionExtractor::new();
+        let nano = extractor.extract("nano-8.tar.gz").unwrap();
+        assert_eq!((nano.name.as_str(), nano.version.as_str()), ("nano", "8"));
+        let foo = extractor.extract("foo_v7.zip").unwrap();
+        assert_eq!((foo.name.as_str(), foo.version.as_str()), ("foo", "7"));
+
+        for path in [
+            "utf-8.tar.gz",
+            "base64-1.tar.gz",
+            "UTF-8",
+            "iso-8859-UTF-8.zip",
+        ] {
+            assert!(extractor.extract_all(path).is_empty(), "{path}");
+            assert!(extractor.extract(path).is_err(), "{path}");
+        }
+
+        // Only bare integers are affected
+        let sha = extractor.extract("sha-2.1.tar.gz").unwrap();
+        assert_eq!(sha.version, "2.1");
+    }
+
+    /// Tests that versions order numerically, with releases after their prereleases
+    #[test]
+    fn test_version_ordering() {
+        let older_newer = [
+            ("0.9", "1.0"),
+            ("1.9", "1.10"),
+            ("1.0-rc1", "1.0-rc2"),
+            ("2.0.0-rc.1", "2.0.0"),
+            ("2.0.0-beta.2", "2.0.0-rc.1"),
+            ("1.0.2", "1.0.2k"),
+            ("1.0.2k", "1.0.2l"),
+            ("1.0.2u", "1.1.0"),
+            ("1.9.16", "1.9.16p2"),
+            ("1.9.16p2", "1.9.17"),
+            ("1.1.1v", "1.1.1w"),
+            ("1.1.1w", "3.0.0"),
+            ("3.24.33", "3.24.34"),
+            ("46.1", "47.alpha"),
+            ("6.6", "6.13.4"),
+            ("20241130", "20250211"),
+            ("17_0_2", "17.0.3"),
+        ];
+        for (older, newer) in older_newer {
+            let older: Version = older.parse().unwrap();
+            let newer: Version = newer.parse().unwrap();
+            assert!(older < newer, "{older} < {newer}");
+        }
+
+        assert_eq!(Version::from("v2.63.2"), Version::from("2.63.2"));
+        assert!(Version::from("1.2.3+build.5") > Version::from("1.2.3"));
+        assert!(Version::from("1.2.3-rc.1+build.7") < Version::from("1.2.3+build.5"));
+        assert_eq!(Version::from("v2.63.2").to_string(), "v2.63.2");
+        assert!("main".parse::<Version>().is_err());
+        assert!(Version::parse("").is_err());
+        assert_eq!(Version::parse("1.1.1w").unwrap().as_str(), "1.1.1w");
+    }
+
+    /// Tests that metadata lists are sorted newest first and the latest release is picked
+    #[test]
+    fn test_sort_and_latest() {
+        let metadata = |version: &str, prerelease: bool| VersionMetadata {
+            prerelease,
+            ..VersionMetadata::new(version, vec![])
+        };
+        let mut versions = vec![
+            metadata("1.9", false),
+            metadata("1.10", false),
+            metadata("1.11-rc1", true),
+            metadata("1.10.1", false),
+            metadata("1.2", false),
+        ];
+        assert_eq!(latest(&versions).unwrap().version, "1.10.1");
+
+        sort_versions(&mut versions);
+        let order = versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, ["1.11-rc1", "1.10.1", "1.10", "1.9", "1.2"]);
+
+        let mut dates = vec![metadata("20241201", false), metadata("20250211", false)];
+        sort_versions(&mut dates);
+        assert_eq!(dates[0].version, "20250211");
+
+        // Without a stable version the newest prerelease is the latest
+        let prereleases = [metadata("2.0-beta1", true), metadata("2.0-rc1", true)];
+        assert_eq!(latest(&prereleases).unwrap().version, "2.0-rc1");
+        assert!(latest(&[]).is_none());
+    }
+
+    /// Tests that version ordering is a total order over a mix of version schemes
+    #[test]
+    fn test_version_order_properties() {
+        let versions = [
+            "1.0",
+            "v1.0",
+            "1.0.0",
+            "1.0-rc1",
+            "1.0.0-rc.2",
+            "1.0.0-alpha",
+            "1.0.0-beta.3",
+            "1.0.0+build.1",
+            "1.9",
+            "1.10",
+            "1.9.16p2",
+            "1.1.1w",
+            "1.1.1",
+            "1.0.2k",
+            "17_0_2",
+            "20241201",
+            "20250211",
+            "20221030-3.1",
+            "46.1",
+            "47.alpha",
+            "2.0-dev",
+            "0.0.1",
+        ]
+        .map(Version::from);
+
+        for a in &versions {
+            assert_eq!(a.cmp(a), Ordering::Equal, "{a}");
+            for b in &versions {
+                assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{a} <=> {b}");
+                assert_eq!(a == b, a.cmp(b) == Ordering::Equal, "{a} == {b}");
+                for c in &versions {
+                    if a <= b && b <= c {
+                        assert!(a <= c, "{a} <= {b} <= {c}");
+                    }
+                }
+            }
+        }
+
+        // Sorting is independent of the input order
+        let mut forward = versions.to_vec();
+        let mut backward = versions.iter().rev().cloned().collect::<Vec<_>>();
+        forward.sort();
+        backward.sort();
+        assert!(forward.iter().zip(&backward).all(|(a, b)| a == b));
+        assert_eq!(forward.last().map(Version::as_str), Some("20250211"));
+    }
+
+    /// Tests that file names in query parameters and fragments are used when the path has none
+    #[test]
+    fn test_query_and_fragment() {
+        let extractor = VersionExtractor::new();
+        let query = extractor
+            .extract("https://example.com/download.php?file=foo-1.2.3.tar.gz")
+            .unwrap();
+        assert_eq!(
+            query,
+            Extraction {
+                name: "foo".to_string(),
+                version: "1.2.3".to_string(),
+                style: VersionStyle::Semver,
+                from_tag: false,
+            }
+        );
+
+        let fragment = extractor
+            .extract("https://example.com/download#foo-1.2.tar.gz")
+            .unwrap();
+        assert_eq!(
+            fragment,
+            Extraction {
+                name: "foo".to_string(),
+                version: "1.2".to_string(),
+                style: VersionStyle::Simple,
+                from_tag: false,
+            }
+        );
+
+        // The path still wins over the query
+        let path = extractor
+            .extract("https://example.com/bar-2.0.1.tar.gz?file=foo-1.2.3.tar.gz")
+            .unwrap();
+        assert_eq!(path.name, "bar");
+        assert_eq!(path.version, "2.0.1");
+    }
 }